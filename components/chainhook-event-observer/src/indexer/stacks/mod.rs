@@ -595,7 +595,7 @@ pub fn get_tx_description(
                 }),
             )
         }
-        TransactionPayload::SmartContract(ref smart_contract) => {
+        TransactionPayload::SmartContract(ref smart_contract, _) => {
             let contract_identifier = format!("{}.{}", tx.origin_address(), smart_contract.name);
             let data = StacksContractDeploymentData {
                 contract_identifier: contract_identifier.clone(),
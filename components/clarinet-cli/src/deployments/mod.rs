@@ -11,8 +11,12 @@ use clarinet_files::{FileLocation, ProjectManifest};
 
 use chainhook_types::StacksNetwork;
 
+use clarity_repl::clarity::vm::ast::ContractAST;
+use clarity_repl::clarity::vm::types::QualifiedContractIdentifier;
+
 use serde_yaml;
 
+use std::collections::{BTreeMap, HashMap};
 use std::fs::{self};
 
 use std::path::PathBuf;
@@ -44,6 +48,19 @@ pub fn generate_default_deployment(
     hiro_system_kit::nestable_block_on(future)
 }
 
+pub fn load_contracts_asts(
+    manifest: &ProjectManifest,
+) -> Result<
+    (
+        HashMap<QualifiedContractIdentifier, ContractAST>,
+        BTreeMap<QualifiedContractIdentifier, ContractAST>,
+    ),
+    String,
+> {
+    let future = clarinet_deployments::load_contracts_asts(manifest, None);
+    hiro_system_kit::nestable_block_on(future)
+}
+
 pub fn check_deployments(manifest: &ProjectManifest) -> Result<(), String> {
     let project_root_location = manifest.location.get_project_root_location()?;
     let files = get_deployments_files(&project_root_location)?;
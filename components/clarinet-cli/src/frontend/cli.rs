@@ -3,7 +3,7 @@ use crate::chainhooks::{check_chainhooks, load_chainhooks};
 use crate::deployments::types::DeploymentSynthesis;
 use crate::deployments::{
     self, check_deployments, generate_default_deployment, get_absolute_deployment_path,
-    write_deployment,
+    load_contracts_asts, write_deployment,
 };
 use crate::generate::{
     self,
@@ -11,8 +11,8 @@ use crate::generate::{
 };
 use crate::integrate;
 use crate::lsp::run_lsp;
-use crate::runner::run_scripts;
 use crate::runner::DeploymentCache;
+use crate::runner::{display_cost_bounds, run_rule_scripts, run_scripts};
 use chainhook_event_observer::chainhooks::types::ChainhookSpecification;
 use chainhook_types::StacksNetwork;
 use chainhook_types::{BitcoinNetwork, Chain};
@@ -25,20 +25,27 @@ use clarinet_deployments::{
     get_default_deployment_path, load_deployment, setup_session_with_deployment,
 };
 use clarinet_files::{FileLocation, ProjectManifest, ProjectManifestFile, RequirementConfig};
+use clarity_repl::analysis::baseline::{Baseline, BASELINE_FILE_NAME};
 use clarity_repl::analysis::call_checker::ContractAnalysis;
+use clarity_repl::analysis::contract_graph::ContractGraph;
+use clarity_repl::analysis::fix::{apply_fixes, fixes_for, DiagnosticFix, Fix};
 use clarity_repl::clarity::vm::analysis::AnalysisDatabase;
 use clarity_repl::clarity::vm::costs::LimitedCostTracker;
 use clarity_repl::clarity::vm::diagnostic::{Diagnostic, Level};
 use clarity_repl::clarity::vm::types::QualifiedContractIdentifier;
 use clarity_repl::clarity::ClarityVersion;
+use clarity_repl::repl::ast::formatter::{format_source, FormatterSettings};
 use clarity_repl::repl::diagnostic::{output_code, output_diagnostic};
+use clarity_repl::repl::fork::Fork;
 use clarity_repl::repl::{ClarityCodeSource, ClarityContract, ContractDeployer, DEFAULT_EPOCH};
 use clarity_repl::{analysis, repl, Terminal};
 use stacks_network::{self, DevnetOrchestrator};
-use std::collections::HashMap;
+use stacks_rpc_client::{StacksRpc, StacksRpcForkSource};
+use std::collections::{BTreeMap, HashMap};
 use std::fs::{self, File};
 use std::io::prelude::*;
 use std::path::PathBuf;
+use std::sync::Arc;
 use std::{env, process};
 
 use clap::{IntoApp, Parser, Subcommand};
@@ -91,6 +98,9 @@ enum Command {
     /// Check contracts syntax
     #[clap(name = "check", bin_name = "check")]
     Check(Check),
+    /// Format contracts source code
+    #[clap(name = "fmt", bin_name = "fmt")]
+    Fmt(Fmt),
     /// Execute Clarinet extension
     #[clap(name = "run", bin_name = "run")]
     Run(Run),
@@ -114,6 +124,9 @@ enum Contracts {
     /// Generate files and settings for a new contract
     #[clap(name = "new", bin_name = "new")]
     NewContract(NewContract),
+    /// Export the graph of the dependencies between contracts
+    #[clap(name = "graph", bin_name = "graph")]
+    Graph(ContractsGraph),
 }
 
 #[derive(Subcommand, PartialEq, Clone, Debug)]
@@ -170,6 +183,24 @@ struct NewContract {
     pub manifest_path: Option<String>,
 }
 
+#[derive(Parser, PartialEq, Clone, Debug)]
+struct ContractsGraph {
+    /// Path to Clarinet.toml
+    #[clap(long = "manifest-path", short = 'm')]
+    pub manifest_path: Option<String>,
+    /// Output format
+    #[clap(
+        long = "format",
+        short = 'f',
+        possible_values = &["dot", "mermaid", "json"],
+        default_value = "dot"
+    )]
+    pub format: String,
+    /// Write the graph to a file instead of stdout
+    #[clap(long = "output", short = 'o')]
+    pub output: Option<String>,
+}
+
 #[derive(Parser, PartialEq, Clone, Debug)]
 struct AddRequirement {
     /// Contract id (ex. "SP2PABAF9FTAJYNFZH93XENAJ8FVY99RRM50D2JG9.nft-trait")
@@ -362,6 +393,12 @@ struct Console {
         conflicts_with = "use-on-disk-deployment-plan"
     )]
     pub use_computed_deployment_plan: bool,
+    /// Fork the state of a network (mainnet, testnet or a node url), optionally at a block height (<network>@<height>)
+    #[clap(long = "fork")]
+    pub fork: Option<String>,
+    /// Run the expressions and commands of a file non-interactively, failing if an ::expect assertion is not met
+    #[clap(long = "script")]
+    pub script: Option<String>,
 }
 
 #[derive(Parser, PartialEq, Clone, Debug)]
@@ -507,6 +544,33 @@ struct Check {
         conflicts_with = "use-on-disk-deployment-plan"
     )]
     pub use_computed_deployment_plan: bool,
+    /// Write the access control report of the public functions to this JSON file
+    #[clap(long = "access-report")]
+    pub access_report: Option<String>,
+    /// Record the current diagnostics in .clarinet-baseline.json, they will not be reported by later checks
+    #[clap(long = "write-baseline")]
+    pub write_baseline: bool,
+    /// Apply the fixes suggested by the analysis passes
+    #[clap(long = "fix")]
+    pub fix: bool,
+    /// Display the static cost bounds of the public and read-only functions
+    #[clap(long = "costs")]
+    pub costs: bool,
+    /// Write the audit report (data access, calls and asset operations of each function) to this file, as markdown if it ends with .md, JSON otherwise
+    #[clap(long = "report")]
+    pub report: Option<String>,
+}
+
+#[derive(Parser, PartialEq, Clone, Debug)]
+struct Fmt {
+    /// Path to Clarinet.toml
+    #[clap(long = "manifest-path", short = 'm')]
+    pub manifest_path: Option<String>,
+    /// Files to format, instead of the contracts of the project
+    pub files: Vec<String>,
+    /// List the files that are not formatted, without updating them
+    #[clap(long = "check")]
+    pub check: bool,
 }
 
 #[derive(Parser, PartialEq, Clone, Debug)]
@@ -921,6 +985,56 @@ pub fn main() {
                     display_post_check_hint();
                 }
             }
+            Contracts::Graph(cmd) => {
+                let manifest = load_manifest_or_exit(cmd.manifest_path);
+
+                let (contracts_asts, requirements_asts) = match load_contracts_asts(&manifest) {
+                    Ok(asts) => asts,
+                    Err(message) => {
+                        println!("{}: {}", red!("error"), message);
+                        std::process::exit(1);
+                    }
+                };
+                let graph = ContractGraph::build(&contracts_asts, &requirements_asts);
+                let content = match cmd.format.as_str() {
+                    "mermaid" => graph.to_mermaid(),
+                    "json" => graph.to_json(),
+                    _ => graph.to_dot(),
+                };
+
+                match cmd.output {
+                    Some(output) => {
+                        if let Err(e) = fs::write(&output, content) {
+                            println!("{}: unable to write {}: {}", red!("error"), output, e);
+                            std::process::exit(1);
+                        }
+                        println!("{} graph written to {}", green!("✔"), output);
+                    }
+                    None => print!("{}", content),
+                }
+
+                // Report the circular dependencies, with the expressions introducing them
+                for cycle in graph.cycles.iter() {
+                    eprintln!(
+                        "{}: circular dependency between {}",
+                        red!("error"),
+                        cycle.join(", ")
+                    );
+                    for edge in graph.edges.iter().filter(|edge| {
+                        edge.cyclic && cycle.contains(&edge.from) && cycle.contains(&edge.to)
+                    }) {
+                        for location in edge.locations.iter() {
+                            eprintln!(
+                                "  {} {} -> {} at {}:{}",
+                                edge.kind, edge.from, edge.to, location.line, location.column
+                            );
+                        }
+                    }
+                }
+                if !graph.cycles.is_empty() {
+                    std::process::exit(1);
+                }
+            }
         },
         Command::Requirements(subcommand) => match subcommand {
             Requirements::AddRequirement(cmd) => {
@@ -959,7 +1073,11 @@ pub fn main() {
                     );
 
                     if !artifacts.success {
-                        let diags_digest = DiagnosticsDigest::new(&artifacts.diags, &deployment);
+                        let diags_digest = DiagnosticsDigest::new(
+                            &artifacts.diags,
+                            &artifacts.session.interpreter.fixes,
+                            &deployment,
+                        );
                         if diags_digest.has_feedbacks() {
                             println!("{}", diags_digest.message);
                         }
@@ -973,14 +1091,47 @@ pub fn main() {
                         std::process::exit(1);
                     }
 
-                    Terminal::load(artifacts.session)
+                    let mut terminal = Terminal::load(artifacts.session);
+                    // Keep the history of the console in the project directory
+                    if let Ok(mut history_location) = manifest.location.get_parent_location() {
+                        if history_location.append_path("history.txt").is_ok() {
+                            terminal.set_history_location(history_location.to_string());
+                        }
+                    }
+                    terminal
                 }
                 None => Terminal::new(repl::SessionSettings::default()),
             };
-            terminal.start();
 
-            if hints_enabled {
-                display_post_console_hint();
+            if let Some(ref fork) = cmd.fork {
+                let cache_location = match manifest {
+                    Some(ref manifest) if terminal.session.settings.disk_cache_enabled => {
+                        Some(&manifest.project.cache_location)
+                    }
+                    _ => None,
+                };
+                if let Err(e) = fork_session(&mut terminal.session, fork, cache_location) {
+                    println!("{} {}", red!("error:"), e);
+                    std::process::exit(1);
+                }
+            }
+
+            let mut success = true;
+            if let Some(ref script_path) = cmd.script {
+                let script = match fs::read_to_string(script_path) {
+                    Ok(script) => script,
+                    Err(e) => {
+                        println!("{} unable to read {}: {}", red!("error:"), script_path, e);
+                        std::process::exit(1);
+                    }
+                };
+                success = terminal.run_script(&script);
+            } else {
+                terminal.start();
+
+                if hints_enabled {
+                    display_post_console_hint();
+                }
             }
 
             // Report telemetry
@@ -1012,6 +1163,10 @@ pub fn main() {
                     }
                 }
             }
+
+            if !success {
+                std::process::exit(1);
+            }
         }
         Command::Check(cmd) if cmd.file.is_some() => {
             let file = cmd.file.unwrap();
@@ -1047,12 +1202,13 @@ pub fn main() {
                 contract.clarity_version,
             );
             let mut analysis_db = AnalysisDatabase::new(&mut session.interpreter.datastore);
-            let mut analysis_diagnostics = match analysis::run_analysis(
+            let (result, fixes) = analysis::run_analysis(
                 &mut contract_analysis,
                 &mut analysis_db,
                 &annotations,
                 &settings.repl_settings.analysis,
-            ) {
+            );
+            let mut analysis_diagnostics = match result {
                 Ok(diagnostics) => diagnostics,
                 Err(diagnostics) => {
                     success = false;
@@ -1060,15 +1216,71 @@ pub fn main() {
                 }
             };
             diagnostics.append(&mut analysis_diagnostics);
+            if let Some((baseline, contract_id)) =
+                load_contract_baseline(cmd.manifest_path.clone(), &file)
+            {
+                diagnostics = baseline.filter_diagnostics(
+                    &contract_id,
+                    diagnostics,
+                    contract.expect_in_memory_code_source(),
+                );
+            }
+            // A lint level can promote a warning to an error without failing the analysis
+            if diagnostics.iter().any(|d| d.level == Level::Error) {
+                success = false;
+            }
+
+            if let Some(report_path) = cmd.access_report {
+                let mut reports = BTreeMap::new();
+                reports.insert(
+                    file.clone(),
+                    analysis::access_checker::access_report(&contract_analysis),
+                );
+                write_access_report(&report_path, &reports);
+            }
+
+            if let Some(report_path) = cmd.report {
+                let mut reports = BTreeMap::new();
+                reports.insert(
+                    file.clone(),
+                    analysis::audit::audit_report(&contract_analysis),
+                );
+                write_audit_report(&report_path, &reports);
+            }
+
+            if cmd.costs {
+                let mut bounds = BTreeMap::new();
+                bounds.insert(
+                    file.clone(),
+                    analysis::cost_estimator::estimate_costs(&contract_analysis),
+                );
+                display_cost_bounds(&bounds);
+            }
 
             let lines = contract.expect_in_memory_code_source().lines();
             let formatted_lines: Vec<String> = lines.map(|l| l.to_string()).collect();
-            for d in diagnostics {
+            for d in diagnostics.iter() {
                 for line in output_diagnostic(&d, &file, &formatted_lines) {
                     println!("{}", line);
                 }
             }
 
+            if cmd.fix {
+                let location = match FileLocation::from_path_string(&file) {
+                    Ok(location) => location,
+                    Err(e) => {
+                        println!("{}: {}", red!("error"), e);
+                        std::process::exit(1);
+                    }
+                };
+                fix_contract(
+                    &location,
+                    contract.expect_in_memory_code_source(),
+                    &diagnostics,
+                    &fixes,
+                );
+            }
+
             if success {
                 println!("{} Syntax of contract successfully checked", green!("✔"),);
                 return;
@@ -1078,18 +1290,110 @@ pub fn main() {
         }
         Command::Check(cmd) => {
             let manifest = load_manifest_or_exit(cmd.manifest_path);
-            let (deployment, _, results) = load_deployment_and_artifacts_or_exit(
+            let (deployment, _, mut results) = load_deployment_and_artifacts_or_exit(
                 &manifest,
                 &cmd.deployment_plan_path,
                 cmd.use_on_disk_deployment_plan,
                 cmd.use_computed_deployment_plan,
             );
 
-            let diags_digest = DiagnosticsDigest::new(&results.diags, &deployment);
+            if !manifest.repl_settings.analysis.rule_scripts().is_empty() {
+                match run_rule_scripts(&manifest, &deployment, &results.analysis) {
+                    Ok(diagnostics) => {
+                        for (contract_id, diagnostic) in diagnostics.into_iter() {
+                            results
+                                .diags
+                                .entry(contract_id)
+                                .or_insert_with(Vec::new)
+                                .push(diagnostic);
+                        }
+                    }
+                    Err(e) => {
+                        println!("{}: unable to run rule scripts: {}", red!("error"), e);
+                        std::process::exit(1);
+                    }
+                }
+            }
+
+            let mut baseline_location = manifest
+                .location
+                .get_parent_location()
+                .expect("unable to locate project root");
+            baseline_location
+                .append_path(BASELINE_FILE_NAME)
+                .expect("unable to locate baseline");
+            if cmd.write_baseline {
+                // Errors failing the check are never hidden by the baseline
+                if !results.success {
+                    println!(
+                        "{}: unable to write the baseline, the contracts contain errors",
+                        red!("error")
+                    );
+                    std::process::exit(1);
+                }
+                write_baseline(&baseline_location, &results.diags, &deployment);
+            }
+            let diags = match load_baseline(&baseline_location) {
+                Some(baseline) => apply_baseline(&baseline, &results.diags, &deployment),
+                None => results.diags.clone(),
+            };
+
+            let fixes = &results.session.interpreter.fixes;
+            let diags_digest = DiagnosticsDigest::new(&diags, fixes, &deployment);
             if diags_digest.has_feedbacks() {
                 println!("{}", diags_digest.message);
             }
 
+            if cmd.fix {
+                for (contract_id, (source, location)) in deployment.contracts.iter() {
+                    if let (Some(diags), Some(fixes)) =
+                        (diags.get(contract_id), fixes.get(contract_id))
+                    {
+                        fix_contract(location, source, diags, fixes);
+                    }
+                }
+            }
+
+            if let Some(report_path) = cmd.access_report {
+                let mut reports = BTreeMap::new();
+                for (contract_id, contract_analysis) in results.analysis.iter() {
+                    // Requirements are not part of the project, skip them
+                    if deployment.contracts.contains_key(contract_id) {
+                        reports.insert(
+                            contract_id.to_string(),
+                            analysis::access_checker::access_report(contract_analysis),
+                        );
+                    }
+                }
+                write_access_report(&report_path, &reports);
+            }
+
+            if let Some(report_path) = cmd.report {
+                let mut reports = BTreeMap::new();
+                for (contract_id, contract_analysis) in results.analysis.iter() {
+                    if deployment.contracts.contains_key(contract_id) {
+                        reports.insert(
+                            contract_id.to_string(),
+                            analysis::audit::audit_report(contract_analysis),
+                        );
+                    }
+                }
+                write_audit_report(&report_path, &reports);
+            }
+
+            if cmd.costs {
+                let mut bounds = BTreeMap::new();
+                for (contract_id, contract_analysis) in results.analysis.iter() {
+                    if deployment.contracts.contains_key(contract_id) {
+                        bounds.insert(
+                            contract_id.to_string(),
+                            analysis::cost_estimator::estimate_costs(contract_analysis),
+                        );
+                    }
+                }
+                display_cost_bounds(&bounds);
+            }
+
             if diags_digest.warnings > 0 {
                 println!(
                     "{} {} detected",
@@ -1110,7 +1414,9 @@ pub fn main() {
                     pluralize!(diags_digest.contracts_checked, "contract"),
                 );
             }
-            let exit_code = match results.success {
+            // Lints promoted to errors do not abort the analysis, but should
+            // still fail the check.
+            let exit_code = match results.success && diags_digest.errors == 0 {
                 true => 0,
                 false => 1,
             };
@@ -1335,6 +1641,34 @@ pub fn main() {
                 display_deploy_hint();
             }
         }
+        Command::Fmt(cmd) => {
+            let locations = if cmd.files.is_empty() {
+                let manifest = load_manifest_or_exit(cmd.manifest_path);
+                let project_root_location = manifest
+                    .location
+                    .get_project_root_location()
+                    .expect("unable to locate project root");
+                manifest
+                    .contracts
+                    .values()
+                    .map(|contract| {
+                        let mut location = project_root_location.clone();
+                        location
+                            .append_path(contract.expect_contract_path_as_str())
+                            .expect("unable to locate contract");
+                        location
+                    })
+                    .collect()
+            } else {
+                cmd.files
+                    .iter()
+                    .map(|file| FileLocation::from_path(PathBuf::from(file)))
+                    .collect::<Vec<_>>()
+            };
+            if !format_contracts(&locations, cmd.check) {
+                process::exit(1);
+            }
+        }
         Command::LSP => run_lsp(),
         Command::DAP => match super::dap::run_dap() {
             Ok(_) => (),
@@ -1825,6 +2159,7 @@ struct DiagnosticsDigest {
 impl DiagnosticsDigest {
     fn new(
         contracts_diags: &HashMap<QualifiedContractIdentifier, Vec<Diagnostic>>,
+        contracts_fixes: &HashMap<QualifiedContractIdentifier, Vec<DiagnosticFix>>,
         deployment: &DeploymentSpecification,
     ) -> DiagnosticsDigest {
         let mut full_success = 0;
@@ -1888,6 +2223,11 @@ impl DiagnosticsDigest {
                 if let Some(ref suggestion) = diagnostic.suggestion {
                     outputs.push(format!("{}", suggestion));
                 }
+                if let Some(fixes) = contracts_fixes.get(contract_id) {
+                    for fix in fixes_for(fixes, &diagnostic) {
+                        outputs.push(format!("{} {}", blue!("help:"), fix.title));
+                    }
+                }
             }
         }
 
@@ -1923,6 +2263,276 @@ fn display_hint_footer() {
     display_separator();
 }
 
+fn write_access_report(
+    path: &str,
+    reports: &BTreeMap<String, Vec<analysis::access_checker::FunctionAccess>>,
+) {
+    let content = match serde_json::to_string_pretty(reports) {
+        Ok(content) => content,
+        Err(e) => {
+            println!(
+                "{}: unable to serialize access report: {}",
+                red!("error"),
+                e
+            );
+            std::process::exit(1);
+        }
+    };
+    if let Err(e) = fs::write(path, content) {
+        println!("{}: Unable to write file {}: {}", red!("error"), path, e);
+        std::process::exit(1);
+    }
+    println!("{} Access control report written to {}", green!("✔"), path);
+}
+
+fn write_audit_report(path: &str, reports: &BTreeMap<String, analysis::audit::ContractAudit>) {
+    let content = if path.ends_with(".md") {
+        analysis::audit::audit_reports_to_markdown(reports)
+    } else {
+        match serde_json::to_string_pretty(reports) {
+            Ok(content) => content,
+            Err(e) => {
+                println!("{}: unable to serialize audit report: {}", red!("error"), e);
+                std::process::exit(1);
+            }
+        }
+    };
+    if let Err(e) = fs::write(path, content) {
+        println!("{}: Unable to write file {}: {}", red!("error"), path, e);
+        std::process::exit(1);
+    }
+    println!("{} Audit report written to {}", green!("✔"), path);
+}
+
+fn fix_contract(
+    location: &FileLocation,
+    source: &str,
+    diagnostics: &[Diagnostic],
+    fixes: &[DiagnosticFix],
+) {
+    let fixes: Vec<Fix> = diagnostics
+        .iter()
+        .flat_map(|diagnostic| fixes_for(fixes, diagnostic))
+        .cloned()
+        .collect();
+    if fixes.is_empty() {
+        return;
+    }
+    let (fixed, applied) = apply_fixes(source, &fixes);
+    if let Err(e) = location.write_content(fixed.as_bytes()) {
+        println!("{}: {}", red!("error"), e);
+        std::process::exit(1);
+    }
+    let path = match location.get_relative_location() {
+        Ok(path) => path,
+        _ => location.to_string(),
+    };
+    let fixes = if applied > 1 { "fixes" } else { "fix" };
+    println!("{} {} {} applied to {}", green!("✔"), applied, fixes, path);
+}
+
+// Formats the contracts in place, or only lists the ones that would change
+// when `check` is set. Returns false on errors or unformatted contracts.
+fn format_contracts(locations: &[FileLocation], check: bool) -> bool {
+    let settings = FormatterSettings::default();
+    let mut success = true;
+    for location in locations {
+        let path = match location.get_relative_location() {
+            Ok(path) => path,
+            _ => location.to_string(),
+        };
+        let source = match location.read_content_as_utf8() {
+            Ok(source) => source,
+            Err(e) => {
+                println!("{}: {}", red!("error"), e);
+                success = false;
+                continue;
+            }
+        };
+        let formatted = match format_source(&source, &settings) {
+            Ok(formatted) => formatted,
+            Err(diagnostics) => {
+                let lines: Vec<String> = source.lines().map(|l| l.to_string()).collect();
+                for diagnostic in diagnostics.iter() {
+                    for line in output_diagnostic(diagnostic, &path, &lines) {
+                        println!("{}", line);
+                    }
+                }
+                success = false;
+                continue;
+            }
+        };
+        if formatted == source {
+            continue;
+        }
+        if check {
+            println!("{} {} is not formatted", red!("x"), path);
+            success = false;
+        } else if let Err(e) = location.write_content(formatted.as_bytes()) {
+            println!("{}: {}", red!("error"), e);
+            success = false;
+        } else {
+            println!("{} formatted {}", green!("✔"), path);
+        }
+    }
+    success
+}
+
+fn fork_session(
+    session: &mut repl::Session,
+    fork: &str,
+    cache_location: Option<&FileLocation>,
+) -> Result<(), String> {
+    let (network, height) = match fork.rsplit_once('@') {
+        Some((network, height)) => match height.parse::<u32>() {
+            Ok(height) => (network, Some(height)),
+            Err(_) => return Err(format!("invalid block height {}", height)),
+        },
+        None => (fork, None),
+    };
+    let url = match network {
+        "mainnet" => "https://stacks-node-api.mainnet.stacks.co",
+        "testnet" => "https://stacks-node-api.testnet.stacks.co",
+        url if url.starts_with("http://") || url.starts_with("https://") => url,
+        _ => {
+            return Err(format!(
+                "unknown network {} (expected mainnet, testnet or a node url)",
+                network
+            ))
+        }
+    };
+
+    let rpc = StacksRpc::new(url);
+    // Reads are pinned to a block, so that the state does not change under
+    // the session
+    let height = match height {
+        Some(height) => height,
+        None => {
+            let info = rpc
+                .get_info()
+                .map_err(|e| format!("unable to retrieve chain tip: {}", e.to_string()))?;
+            info.stacks_tip_height as u32
+        }
+    };
+    let tip = rpc
+        .get_block_by_height(height.into())
+        .map_err(|e| format!("unable to retrieve block {}: {}", height, e.to_string()))?
+        .index_block_hash;
+
+    // The state at a given block never changes, and can be kept on disk
+    let cache_location = match cache_location {
+        Some(cache_location) => {
+            let network_name: String = network
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+                .collect();
+            let mut location = cache_location.clone();
+            location.append_path("forks")?;
+            location.append_path(&format!("{}@{}.json", network_name, height))?;
+            Some(PathBuf::from(location.to_string()))
+        }
+        _ => None,
+    };
+
+    let source = StacksRpcForkSource::new(url, Some(tip));
+    session.set_fork(Fork::new(Arc::new(source), height, cache_location));
+    println!(
+        "{} Forked {} at block height {}",
+        green!("✔"),
+        network,
+        height
+    );
+    Ok(())
+}
+
+fn load_baseline(location: &FileLocation) -> Option<Baseline> {
+    if !location.exists() {
+        return None;
+    }
+    let baseline = location
+        .read_content_as_utf8()
+        .and_then(|content| Baseline::from_json(&content));
+    match baseline {
+        Ok(baseline) => Some(baseline),
+        Err(e) => {
+            println!("{}: {}", red!("error"), e);
+            std::process::exit(1);
+        }
+    }
+}
+
+// Load the baseline of the project containing the contract at `file`, along
+// with the identifier of this contract in the baseline.
+fn load_contract_baseline(
+    manifest_path: Option<String>,
+    file: &str,
+) -> Option<(Baseline, QualifiedContractIdentifier)> {
+    let manifest_location = get_manifest_location(manifest_path)?;
+    let manifest = ProjectManifest::from_location(&manifest_location).ok()?;
+    let project_root = manifest_location.get_parent_location().ok()?;
+    let file_path = fs::canonicalize(file).ok()?;
+    let contract_name =
+        manifest
+            .contracts
+            .iter()
+            .find_map(|(name, contract)| match &contract.code_source {
+                ClarityCodeSource::ContractOnDisk(path) => {
+                    let mut location = project_root.clone();
+                    location.append_path(path.to_str()?).ok()?;
+                    match fs::canonicalize(location.to_string()) {
+                        Ok(path) if path == file_path => Some(name),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            })?;
+    let mut baseline_location = project_root;
+    baseline_location.append_path(BASELINE_FILE_NAME).ok()?;
+    let baseline = load_baseline(&baseline_location)?;
+    let contract_id = QualifiedContractIdentifier::local(contract_name).ok()?;
+    Some((baseline, contract_id))
+}
+
+fn write_baseline(
+    location: &FileLocation,
+    contracts_diags: &HashMap<QualifiedContractIdentifier, Vec<Diagnostic>>,
+    deployment: &DeploymentSpecification,
+) {
+    let mut baseline = Baseline::new();
+    // Iterate over the deployment to keep the file stable between runs
+    for (contract_id, (source, _)) in deployment.contracts.iter() {
+        if let Some(diags) = contracts_diags.get(contract_id) {
+            baseline.add_diagnostics(contract_id, diags, source);
+        }
+    }
+    if let Err(e) = location.write_content(baseline.to_json().as_bytes()) {
+        println!("{}: {}", red!("error"), e);
+        std::process::exit(1);
+    }
+    println!(
+        "{} {} recorded in {}",
+        green!("✔"),
+        pluralize!(baseline.findings.len(), "diagnostic"),
+        BASELINE_FILE_NAME
+    );
+}
+
+fn apply_baseline(
+    baseline: &Baseline,
+    contracts_diags: &HashMap<QualifiedContractIdentifier, Vec<Diagnostic>>,
+    deployment: &DeploymentSpecification,
+) -> HashMap<QualifiedContractIdentifier, Vec<Diagnostic>> {
+    let mut filtered = HashMap::new();
+    for (contract_id, diags) in contracts_diags.iter() {
+        let diags = match deployment.contracts.get(contract_id) {
+            Some((source, _)) => baseline.filter_diagnostics(contract_id, diags.clone(), source),
+            None => diags.clone(),
+        };
+        filtered.insert(contract_id.clone(), diags);
+    }
+    filtered
+}
+
 fn display_post_check_hint() {
     println!("");
     display_hint_header();
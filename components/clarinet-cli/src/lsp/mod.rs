@@ -1,6 +1,7 @@
 mod native_bridge;
 
 use clarity_lsp::utils;
+use clarity_repl::analysis::fix::DiagnosticFix;
 use clarity_repl::clarity::vm::diagnostic::{
     Diagnostic as ClarityDiagnostic, Level as ClarityLevel,
 };
@@ -10,7 +11,8 @@ use crossbeam_channel::unbounded;
 use std::sync::mpsc;
 use tokio;
 use tower_lsp::lsp_types::{
-    Diagnostic, DiagnosticSeverity, Documentation, MarkupContent, MarkupKind, Position, Range,
+    CodeAction, Diagnostic, DiagnosticSeverity, Documentation, MarkupContent, MarkupKind, Position,
+    Range, TextEdit, Url,
 };
 use tower_lsp::{LspService, Server};
 
@@ -125,16 +127,18 @@ pub fn insert_text_format_lsp_type_to_tower_lsp_type(
 
 pub fn clarity_diagnostics_to_tower_lsp_type(
     diagnostics: &mut Vec<ClarityDiagnostic>,
+    fixes: &[DiagnosticFix],
 ) -> Vec<tower_lsp::lsp_types::Diagnostic> {
     let mut dst = vec![];
     for d in diagnostics.iter_mut() {
-        dst.push(clarity_diagnostic_to_tower_lsp_type(d));
+        dst.push(clarity_diagnostic_to_tower_lsp_type(d, fixes));
     }
     dst
 }
 
 pub fn clarity_diagnostic_to_tower_lsp_type(
     diagnostic: &ClarityDiagnostic,
+    fixes: &[DiagnosticFix],
 ) -> tower_lsp::lsp_types::Diagnostic {
     let range = match diagnostic.spans.len() {
         0 => Range::default(),
@@ -163,10 +167,54 @@ pub fn clarity_diagnostic_to_tower_lsp_type(
         message: diagnostic.message.clone(),
         related_information: None,
         tags: None,
-        data: None,
+        data: utils::fixes_to_lsp_data(diagnostic, fixes),
     }
 }
 
+// The quick fixes are built by `clarity_lsp`, whose `lsp_types` differ from the
+// ones of `tower_lsp`, both serializing to the same protocol messages.
+pub fn diagnostics_to_tower_lsp_code_actions(
+    url: &Url,
+    diagnostics: &[Diagnostic],
+) -> Vec<CodeAction> {
+    let url = match clarity_lsp::lsp_types::Url::parse(url.as_str()) {
+        Ok(url) => url,
+        Err(_) => return vec![],
+    };
+    let diagnostics: Vec<clarity_lsp::lsp_types::Diagnostic> =
+        match serde_json::to_value(diagnostics).and_then(serde_json::from_value) {
+            Ok(diagnostics) => diagnostics,
+            Err(_) => return vec![],
+        };
+    utils::diagnostics_to_code_actions(&url, &diagnostics)
+        .into_iter()
+        .filter_map(|action| {
+            serde_json::to_value(action)
+                .and_then(serde_json::from_value)
+                .ok()
+        })
+        .collect()
+}
+
+pub fn build_tower_lsp_formatting_edits(source: &str) -> Vec<TextEdit> {
+    utils::build_formatting_edits(source)
+        .into_iter()
+        .map(|edit| TextEdit {
+            range: Range {
+                start: Position {
+                    line: edit.range.start.line,
+                    character: edit.range.start.character,
+                },
+                end: Position {
+                    line: edit.range.end.line,
+                    character: edit.range.end.character,
+                },
+            },
+            new_text: edit.new_text,
+        })
+        .collect()
+}
+
 #[test]
 fn test_opening_counter_contract_should_return_fresh_analysis() {
     use clarinet_files::FileLocation;
@@ -199,7 +247,7 @@ fn test_opening_counter_contract_should_return_fresh_analysis() {
 
     // the counter project should emit 2 warnings and 2 notes coming from counter.clar
     assert_eq!(response.aggregated_diagnostics.len(), 1);
-    let (_url, diags) = &response.aggregated_diagnostics[0];
+    let (_url, diags, _) = &response.aggregated_diagnostics[0];
     assert_eq!(diags.len(), 4);
 
     // re-opening this contract should not trigger a full analysis
@@ -239,7 +287,7 @@ fn test_opening_counter_manifest_should_return_fresh_analysis() {
 
     // the counter project should emit 2 warnings and 2 notes coming from counter.clar
     assert_eq!(response.aggregated_diagnostics.len(), 1);
-    let (_url, diags) = &response.aggregated_diagnostics[0];
+    let (_url, diags, _) = &response.aggregated_diagnostics[0];
     assert_eq!(diags.len(), 4);
 
     // re-opening this manifest should not trigger a full analysis
@@ -278,7 +326,7 @@ fn test_opening_simple_nft_manifest_should_return_fresh_analysis() {
 
     // the counter project should emit 2 warnings and 2 notes coming from counter.clar
     assert_eq!(response.aggregated_diagnostics.len(), 2);
-    let (_, diags_0) = &response.aggregated_diagnostics[0];
-    let (_, diags_1) = &response.aggregated_diagnostics[1];
+    let (_, diags_0, _) = &response.aggregated_diagnostics[0];
+    let (_, diags_1, _) = &response.aggregated_diagnostics[1];
     assert_eq!(diags_0.len().max(diags_1.len()), 8);
 }
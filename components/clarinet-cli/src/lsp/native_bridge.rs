@@ -1,23 +1,29 @@
 use super::utils;
 
-use crate::lsp::{clarity_diagnostics_to_tower_lsp_type, completion_item_type_to_tower_lsp_type};
+use crate::lsp::{
+    build_tower_lsp_formatting_edits, clarity_diagnostics_to_tower_lsp_type,
+    completion_item_type_to_tower_lsp_type, diagnostics_to_tower_lsp_code_actions,
+};
 use clarity_lsp::backend::{
     process_notification, process_request, LspNotification, LspRequest, LspResponse,
 };
 use clarity_lsp::state::EditorState;
 use crossbeam_channel::{Receiver as MultiplexableReceiver, Select, Sender as MultiplexableSender};
 use serde_json::Value;
+use std::collections::HashMap;
 use std::sync::mpsc::{Receiver, Sender};
 use std::sync::Arc;
 use std::sync::Mutex;
 use tower_lsp::jsonrpc::Result;
 use tower_lsp::lsp_types::{
+    CodeActionOrCommand, CodeActionParams, CodeActionProviderCapability, CodeActionResponse,
     CompletionOptions, CompletionParams, CompletionResponse, DeclarationCapability,
     DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
-    DidSaveTextDocumentParams, ExecuteCommandParams, HoverProviderCapability, InitializeParams,
-    InitializeResult, InitializedParams, MessageType, ServerCapabilities,
+    DidSaveTextDocumentParams, DocumentFormattingParams, ExecuteCommandParams, Hover,
+    HoverContents, HoverParams, HoverProviderCapability, InitializeParams, InitializeResult,
+    InitializedParams, MarkupContent, MarkupKind, MessageType, OneOf, ServerCapabilities,
     TextDocumentSyncCapability, TextDocumentSyncKind, TextDocumentSyncOptions,
-    TextDocumentSyncSaveOptions, Url,
+    TextDocumentSyncSaveOptions, TextEdit, Url,
 };
 use tower_lsp::{async_trait, Client, LanguageServer};
 
@@ -77,6 +83,8 @@ pub struct LspNativeBridge {
     request_tx: Arc<Mutex<MultiplexableSender<LspRequest>>>,
     notification_tx: Arc<Mutex<MultiplexableSender<LspNotification>>>,
     response_rx: Arc<Mutex<Receiver<LspResponse>>>,
+    // Content of the opened documents, including the unsaved changes
+    documents: Arc<Mutex<HashMap<Url, String>>>,
 }
 
 impl LspNativeBridge {
@@ -91,6 +99,7 @@ impl LspNativeBridge {
             request_tx: Arc::new(Mutex::new(request_tx)),
             notification_tx: Arc::new(Mutex::new(notification_tx)),
             response_rx: Arc::new(Mutex::new(response_rx)),
+            documents: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 }
@@ -117,8 +126,10 @@ impl LanguageServer for LspNativeBridge {
                     work_done_progress_options: Default::default(),
                 }),
                 type_definition_provider: None,
-                hover_provider: Some(HoverProviderCapability::Simple(false)),
+                hover_provider: Some(HoverProviderCapability::Simple(true)),
                 declaration_provider: Some(DeclarationCapability::Simple(false)),
+                code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+                document_formatting_provider: Some(OneOf::Left(true)),
                 ..ServerCapabilities::default()
             },
         })
@@ -163,7 +174,73 @@ impl LanguageServer for LspNativeBridge {
         Ok(Some(CompletionResponse::from(completion_items)))
     }
 
+    async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
+        let position = params.text_document_position_params.position;
+        let file_url = params.text_document_position_params.text_document.uri;
+        let contract_location = match utils::get_contract_location(&file_url) {
+            Some(location) => location,
+            _ => return Ok(None),
+        };
+
+        let _ = match self.request_tx.lock() {
+            Ok(tx) => tx.send(LspRequest::GetHover(
+                contract_location,
+                position.line + 1,
+                position.character + 1,
+            )),
+            Err(_) => return Ok(None),
+        };
+
+        let mut hover = None;
+        if let Ok(response_rx) = self.response_rx.lock() {
+            if let Ok(ref mut response) = response_rx.recv() {
+                hover = response.hover.take();
+            }
+        }
+
+        Ok(hover.map(|value| Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value,
+            }),
+            range: None,
+        }))
+    }
+
+    async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
+        // The fixes are attached to the diagnostics published by the server,
+        // so the actions can be built without accessing the editor state.
+        let actions: CodeActionResponse = diagnostics_to_tower_lsp_code_actions(
+            &params.text_document.uri,
+            &params.context.diagnostics,
+        )
+        .into_iter()
+        .map(CodeActionOrCommand::CodeAction)
+        .collect();
+        Ok(Some(actions))
+    }
+
+    async fn formatting(&self, params: DocumentFormattingParams) -> Result<Option<Vec<TextEdit>>> {
+        if utils::get_contract_location(&params.text_document.uri).is_none() {
+            return Ok(None);
+        }
+        let edits = match self.documents.lock() {
+            Ok(documents) => match documents.get(&params.text_document.uri) {
+                Some(source) => build_tower_lsp_formatting_edits(source),
+                None => return Ok(None),
+            },
+            Err(_) => return Ok(None),
+        };
+        Ok(Some(edits))
+    }
+
     async fn did_open(&self, params: DidOpenTextDocumentParams) {
+        if let Ok(mut documents) = self.documents.lock() {
+            documents.insert(
+                params.text_document.uri.clone(),
+                params.text_document.text.clone(),
+            );
+        }
         if let Some(contract_location) = utils::get_contract_location(&params.text_document.uri) {
             let _ = match self.notification_tx.lock() {
                 Ok(tx) => tx.send(LspNotification::ContractOpened(contract_location)),
@@ -197,12 +274,12 @@ impl LanguageServer for LspNativeBridge {
                 notification = response.notification.take();
             }
         }
-        for (location, mut diags) in aggregated_diagnostics.drain(..) {
+        for (location, mut diags, fixes) in aggregated_diagnostics.drain(..) {
             if let Ok(url) = location.to_url_string() {
                 self.client
                     .publish_diagnostics(
                         Url::parse(&url).unwrap(),
-                        clarity_diagnostics_to_tower_lsp_type(&mut diags),
+                        clarity_diagnostics_to_tower_lsp_type(&mut diags, &fixes),
                         None,
                     )
                     .await;
@@ -241,12 +318,12 @@ impl LanguageServer for LspNativeBridge {
             }
         }
 
-        for (location, mut diags) in aggregated_diagnostics.drain(..) {
+        for (location, mut diags, fixes) in aggregated_diagnostics.drain(..) {
             if let Ok(url) = location.to_url_string() {
                 self.client
                     .publish_diagnostics(
                         Url::parse(&url).unwrap(),
-                        clarity_diagnostics_to_tower_lsp_type(&mut diags),
+                        clarity_diagnostics_to_tower_lsp_type(&mut diags, &fixes),
                         None,
                     )
                     .await;
@@ -259,9 +336,20 @@ impl LanguageServer for LspNativeBridge {
         }
     }
 
-    async fn did_change(&self, _changes: DidChangeTextDocumentParams) {}
+    async fn did_change(&self, mut params: DidChangeTextDocumentParams) {
+        // The documents are synchronized in full, the last change is the content
+        if let Some(change) = params.content_changes.pop() {
+            if let Ok(mut documents) = self.documents.lock() {
+                documents.insert(params.text_document.uri, change.text);
+            }
+        }
+    }
 
-    async fn did_close(&self, _: DidCloseTextDocumentParams) {}
+    async fn did_close(&self, params: DidCloseTextDocumentParams) {
+        if let Ok(mut documents) = self.documents.lock() {
+            documents.remove(&params.text_document.uri);
+        }
+    }
 }
 
 pub fn message_level_type_to_tower_lsp_type(
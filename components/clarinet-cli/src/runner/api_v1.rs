@@ -30,6 +30,9 @@ use clarity_repl::clarity::util::hash::hex_bytes;
 use clarity_repl::clarity::util::hash::to_hex;
 use clarity_repl::clarity::util::hash::Sha512Trunc256Sum;
 use clarity_repl::clarity::vm::analysis::contract_interface_builder::build_contract_interface;
+use clarity_repl::clarity::vm::diagnostic::{Diagnostic, Level};
+use clarity_repl::clarity::vm::representations::Span;
+use clarity_repl::clarity::vm::types::QualifiedContractIdentifier;
 use clarity_repl::clarity::vm::EvaluationResult;
 use clarity_repl::clarity::ClarityVersion;
 use clarity_repl::clarity::ExecutionResult;
@@ -87,6 +90,10 @@ pub async fn run_bridge(
     call_read_only_fn_decl.name = "api/v1/call_read_only_fn";
     let mut get_assets_maps_decl = get_assets_maps::decl();
     get_assets_maps_decl.name = "api/v1/get_assets_maps";
+    let mut create_multisig_decl = create_multisig::decl();
+    create_multisig_decl.name = "api/v1/create_multisig";
+    let mut set_burn_block_info_decl = set_burn_block_info::decl();
+    set_burn_block_info_decl.name = "api/v1/set_burn_block_info";
     let mut deprecation_notice_decl = deprecation_notice::decl();
     deprecation_notice_decl.name = "api/v1/mine_empty_blocks";
 
@@ -99,6 +106,8 @@ pub async fn run_bridge(
             mine_empty_blocks_decl,
             call_read_only_fn_decl,
             get_assets_maps_decl,
+            create_multisig_decl,
+            set_burn_block_info_decl,
         ])
         .build();
     custom_extensions.push(clarinet);
@@ -245,6 +254,127 @@ pub async fn run_bridge(
     Ok(artifacts)
 }
 
+/// Run a rule script, exposing the contracts of the project to the rules it
+/// defines, and return the diagnostics they reported.
+pub async fn run_rule_script(
+    program_state: &ProcState,
+    permissions: Permissions,
+    specifier: ModuleSpecifier,
+    contracts: Vec<Value>,
+) -> Result<Vec<(QualifiedContractIdentifier, Diagnostic)>, AnyError> {
+    let mut get_rule_contracts_decl = get_rule_contracts::decl();
+    get_rule_contracts_decl.name = "api/v1/get_rule_contracts";
+    let mut report_rule_diagnostics_decl = report_rule_diagnostics::decl();
+    report_rule_diagnostics_decl.name = "api/v1/report_rule_diagnostics";
+
+    let clarinet = Extension::builder()
+        .ops(vec![get_rule_contracts_decl, report_rule_diagnostics_decl])
+        .build();
+
+    let mut worker = create_main_worker(
+        program_state,
+        specifier.clone(),
+        permissions,
+        vec![clarinet],
+        Stdio::default(),
+    );
+
+    worker.js_runtime.op_state().borrow_mut().put(contracts);
+    worker
+        .js_runtime
+        .op_state()
+        .borrow_mut()
+        .put::<Vec<(QualifiedContractIdentifier, Diagnostic)>>(vec![]);
+
+    worker.execute_main_module(&specifier).await?;
+    worker.run_event_loop(false).await?;
+
+    let diagnostics = worker
+        .js_runtime
+        .op_state()
+        .borrow_mut()
+        .take::<Vec<(QualifiedContractIdentifier, Diagnostic)>>();
+    Ok(diagnostics)
+}
+
+#[op]
+fn get_rule_contracts(state: &mut OpState) -> Result<String, AnyError> {
+    let contracts = state.borrow::<Vec<Value>>();
+    Ok(json!(contracts).to_string())
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RuleSpan {
+    line: u32,
+    column: u32,
+    end_line: u32,
+    end_column: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RuleDiagnostic {
+    contract_id: String,
+    level: Option<String>,
+    message: String,
+    expression: Option<RuleSpan>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReportRuleDiagnosticsArgs {
+    rule: String,
+    diagnostics: Vec<RuleDiagnostic>,
+}
+
+#[op]
+fn report_rule_diagnostics(
+    state: &mut OpState,
+    args: ReportRuleDiagnosticsArgs,
+) -> Result<(), AnyError> {
+    let reported = state.borrow_mut::<Vec<(QualifiedContractIdentifier, Diagnostic)>>();
+    for diagnostic in args.diagnostics.into_iter() {
+        let contract_id =
+            QualifiedContractIdentifier::parse(&diagnostic.contract_id).map_err(|_| {
+                AnyError::msg(format!(
+                    "rule '{}' reported an invalid contract id: {}",
+                    args.rule, diagnostic.contract_id
+                ))
+            })?;
+        let level = match diagnostic.level.as_deref() {
+            None | Some("warning") => Level::Warning,
+            Some("error") => Level::Error,
+            Some("note") => Level::Note,
+            Some(level) => {
+                return Err(AnyError::msg(format!(
+                    "rule '{}' reported an invalid level: {}",
+                    args.rule, level
+                )))
+            }
+        };
+        let spans = match diagnostic.expression {
+            Some(span) => vec![Span {
+                start_line: span.line,
+                start_column: span.column,
+                end_line: span.end_line,
+                end_column: span.end_column,
+            }],
+            None => vec![],
+        };
+        reported.push((
+            contract_id,
+            Diagnostic {
+                level,
+                message: format!("{} (rule '{}')", diagnostic.message, args.rule),
+                spans,
+                suggestion: None,
+            },
+        ));
+    }
+    Ok(())
+}
+
 #[op]
 pub fn deprecation_notice(_state: &mut OpState, _args: Value, _: ()) -> Result<(), AnyError> {
     println!("{}: clarinet v{} is incompatible with the version of the library being imported in the test files.", red!("error"), option_env!("CARGO_PKG_VERSION").expect("Unable to detect version"));
@@ -560,6 +690,71 @@ fn get_assets_maps(state: &mut OpState, args: GetAssetsMapsArgs) -> Result<Strin
     .to_string())
 }
 
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CreateMultisigArgs {
+    session_id: u32,
+    required_signatures: u8,
+    signers: Vec<String>,
+}
+
+#[op]
+fn create_multisig(state: &mut OpState, args: CreateMultisigArgs) -> Result<String, AnyError> {
+    let address = perform_block(state, args.session_id, |_name, session| {
+        session
+            .create_multisig_principal(args.required_signatures, &args.signers)
+            .map_err(|e| AnyError::msg(e))
+    })?;
+    Ok(json!({
+      "session_id": args.session_id,
+      "address": address,
+    })
+    .to_string())
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SetBurnBlockInfoArgs {
+    session_id: u32,
+    height: u32,
+    header_hash: Option<String>,
+    burn_block_height: Option<u32>,
+    pox_addrs: Option<Vec<String>>,
+    payout: Option<u64>,
+}
+
+#[op]
+fn set_burn_block_info(
+    state: &mut OpState,
+    args: SetBurnBlockInfoArgs,
+) -> Result<String, AnyError> {
+    perform_block(state, args.session_id, |_name, session| {
+        if let Some(ref header_hash) = args.header_hash {
+            session
+                .set_burn_block_header_hash(args.height, header_hash)
+                .map_err(|e| AnyError::msg(e))?;
+        }
+        if let Some(burn_block_height) = args.burn_block_height {
+            session.set_burn_block_height(args.height, burn_block_height);
+        }
+        if let Some(ref pox_addrs) = args.pox_addrs {
+            session
+                .set_pox_payout_addrs(
+                    args.height,
+                    &pox_addrs.join(" "),
+                    args.payout.unwrap_or(0).into(),
+                )
+                .map_err(|e| AnyError::msg(e))?;
+        }
+        Ok(())
+    })?;
+    Ok(json!({
+      "session_id": args.session_id,
+      "height": args.height,
+    })
+    .to_string())
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct MineBlockArgs {
@@ -571,6 +766,10 @@ struct MineBlockArgs {
 #[serde(rename_all = "camelCase")]
 pub struct TransactionArgs {
     sender: String,
+    sponsor: Option<String>,
+    fee: Option<u64>,
+    nonce: Option<u64>,
+    signers: Option<Vec<String>>,
     contract_call: Option<ContractCallArgs>,
     deploy_contract: Option<DeployContractArgs>,
     transfer_stx: Option<TransferSTXArgs>,
@@ -603,8 +802,40 @@ struct TransferSTXArgs {
 fn mine_block(state: &mut OpState, args: MineBlockArgs) -> Result<String, AnyError> {
     let (block_height, transactions) = perform_block(state, args.session_id, |name, session| {
         let initial_tx_sender = session.get_tx_sender();
+        let initial_tx_sponsor = session.get_tx_sponsor();
+        // Rejected transactions are reported in the receipts, in place
         let mut transactions = vec![];
         for (index, tx) in args.transactions.iter().enumerate() {
+            let signers = tx.signers.clone().unwrap_or_default();
+            if let Err(e) = session.check_signatures(&tx.sender, &signers) {
+                println!("{}: {}", red!("Transaction rejected"), e);
+                transactions.push(Err(e));
+                continue;
+            }
+            let nonce = match session.check_nonce(&tx.sender, tx.nonce) {
+                Ok(nonce) => nonce,
+                Err(e) => {
+                    println!("{}: {}", red!("Transaction rejected"), e);
+                    transactions.push(Err(e));
+                    continue;
+                }
+            };
+            // Sponsored transactions are paid by their sponsor
+            if let Some(fee) = tx.fee {
+                let payer = tx.sponsor.as_ref().unwrap_or(&tx.sender);
+                if let Err(e) = session.pay_fee(payer, fee) {
+                    println!("{}: {}", red!("Transaction rejected"), e);
+                    transactions.push(Err(e));
+                    continue;
+                }
+            }
+            if let Err(e) = session.increment_nonce(&tx.sender) {
+                println!("{}: {}", red!("Transaction rejected"), e);
+                transactions.push(Err(e));
+                continue;
+            }
+            session.set_tx_sponsor(tx.sponsor.clone());
+
             if let Some(ref args) = tx.contract_call {
                 let (execution, contract_id) = match session.invoke_contract_call(
                     &args.contract,
@@ -630,15 +861,17 @@ fn mine_block(state: &mut OpState, args: MineBlockArgs) -> Result<String, AnyErr
                     }
                 };
 
+                let fee = charge_transaction_fee(session, tx, &execution);
                 let kind = StacksTransactionKind::ContractCall(StacksContractCallData {
                     contract_identifier: contract_id.to_string(),
                     method: args.method.clone(),
                     args: args.args.clone(),
                 });
-                transactions.push((
-                    wrap_result_in_simulated_transaction(index, &tx.sender, kind, &execution),
+                transactions.push(Ok((
+                    wrap_result_in_simulated_transaction(index, tx, fee, kind, &execution),
                     execution.events,
-                ));
+                    nonce,
+                )));
             } else {
                 session.set_tx_sender(tx.sender.clone());
                 if let Some(ref args) = tx.deploy_contract {
@@ -653,10 +886,12 @@ fn mine_block(state: &mut OpState, args: MineBlockArgs) -> Result<String, AnyErr
                         },
                         epoch: DEFAULT_EPOCH,
                     };
+                    // The cost of the deployment is needed for cost-based fees
+                    let cost_track = session.settings.repl_settings.accounting.is_some();
                     let execution = match session.deploy_contract(
                         &contract,
                         None,
-                        false,
+                        cost_track,
                         Some(name.into()),
                         &mut None,
                     ) {
@@ -675,6 +910,7 @@ fn mine_block(state: &mut OpState, args: MineBlockArgs) -> Result<String, AnyErr
                             continue;
                         }
                     };
+                    let fee = charge_transaction_fee(session, tx, &execution);
                     let kind =
                         StacksTransactionKind::ContractDeployment(StacksContractDeploymentData {
                             contract_identifier: contract
@@ -682,10 +918,11 @@ fn mine_block(state: &mut OpState, args: MineBlockArgs) -> Result<String, AnyErr
                                 .to_string(),
                             code: contract.expect_in_memory_code_source().to_string(),
                         });
-                    transactions.push((
-                        wrap_result_in_simulated_transaction(index, &tx.sender, kind, &execution),
+                    transactions.push(Ok((
+                        wrap_result_in_simulated_transaction(index, tx, fee, kind, &execution),
                         execution.events,
-                    ));
+                        nonce,
+                    )));
                 } else if let Some(ref args) = tx.transfer_stx {
                     let execution = match session.stx_transfer(args.amount, &args.recipient) {
                         Ok(res) => res,
@@ -699,15 +936,18 @@ fn mine_block(state: &mut OpState, args: MineBlockArgs) -> Result<String, AnyErr
                             continue;
                         }
                     };
+                    let fee = charge_transaction_fee(session, tx, &execution);
                     let kind = StacksTransactionKind::NativeTokenTransfer;
-                    transactions.push((
-                        wrap_result_in_simulated_transaction(index, &tx.sender, kind, &execution),
+                    transactions.push(Ok((
+                        wrap_result_in_simulated_transaction(index, tx, fee, kind, &execution),
                         execution.events,
-                    ));
+                        nonce,
+                    )));
                 }
                 session.set_tx_sender(initial_tx_sender.clone());
             }
         }
+        session.set_tx_sponsor(initial_tx_sponsor);
         let block_height = session.advance_chain_tip(1);
         Ok((block_height, transactions))
     })?;
@@ -727,12 +967,13 @@ fn mine_block(state: &mut OpState, args: MineBlockArgs) -> Result<String, AnyErr
     if !chainhooks.is_empty() {
         let txids = transactions
             .iter()
+            .filter_map(|t| t.as_ref().ok())
             .map(|t| hex_bytes(&t.0.transaction_identifier.hash[2..]).unwrap())
             .collect::<Vec<Vec<u8>>>();
         let merkle_tree = MerkleTree::<Sha512Trunc256Sum>::new(&txids);
 
         for chainhook in chainhooks.iter() {
-            for (tx, _) in transactions.iter() {
+            for (tx, _, _) in transactions.iter().filter_map(|t| t.as_ref().ok()) {
                 if evaluate_stacks_transaction_predicate_on_transaction(tx, chainhook) {
                     let simulated_block = BlockIdentifier {
                         index: block_height.into(),
@@ -787,14 +1028,23 @@ fn mine_block(state: &mut OpState, args: MineBlockArgs) -> Result<String, AnyErr
     let payload = json!({
       "session_id": args.session_id,
       "block_height": block_height,
-      "receipts":  transactions.iter().map(|(t, events)| {
-        json!({
+      "receipts":  transactions.iter().map(|transaction| match transaction {
+        Ok((t, events, nonce)) => json!({
           "result": t.metadata.result,
+          "sponsor": t.metadata.sponsor,
+          "fee": t.metadata.fee,
+          "nonce": nonce,
           "events": events
           .iter()
           .map(|e| serialize_event(e))
           .collect::<Vec<serde_json::Value>>()
-        })
+        }),
+        Err(e) => json!({
+          "result": "",
+          "rejected": e,
+          "fee": 0,
+          "events": [],
+        }),
       }).collect::<Vec<_>>()
     });
 
@@ -819,9 +1069,34 @@ where
     }
 }
 
+// Charge the fee set by the session to the transactions not setting their own
+// fee, once executed, returning the fee of the transaction.
+fn charge_transaction_fee(
+    session: &mut Session,
+    tx: &TransactionArgs,
+    execution: &ExecutionResult,
+) -> u64 {
+    if let Some(fee) = tx.fee {
+        return fee;
+    }
+    let fee = match session.get_transaction_fee(execution.cost.as_ref()) {
+        Some(fee) => fee,
+        None => return 0,
+    };
+    let payer = tx.sponsor.as_ref().unwrap_or(&tx.sender);
+    match session.pay_fee(payer, fee) {
+        Ok(()) => fee,
+        Err(e) => {
+            println!("{}: {}", yellow!("Fee not charged"), e);
+            0
+        }
+    }
+}
+
 fn wrap_result_in_simulated_transaction(
     index: usize,
-    sender: &str,
+    tx: &TransactionArgs,
+    fee: u64,
     kind: StacksTransactionKind,
     execution: &ExecutionResult,
 ) -> StacksTransactionData {
@@ -849,12 +1124,12 @@ fn wrap_result_in_simulated_transaction(
             success: true,
             raw_tx: String::new(),
             result,
-            sender: sender.to_string(),
-            fee: 0,
+            sender: tx.sender.clone(),
+            fee,
             kind,
             receipt,
             description: String::new(),
-            sponsor: None,
+            sponsor: tx.sponsor.clone(),
             execution_cost: None,
             position: chainhook_types::StacksTransactionPosition::Index(index),
         },
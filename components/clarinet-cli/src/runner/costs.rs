@@ -1,4 +1,5 @@
 use super::SessionArtifacts;
+use clarity_repl::analysis::cost_estimator::FunctionCostBound;
 use clarity_repl::clarity::vm::CostSynthesis;
 use clarity_repl::prettytable::{color, format, Attr, Cell, Row, Table};
 use clarity_repl::repl::session::CostsReport;
@@ -257,6 +258,102 @@ pub fn display_costs_report(sessions_artifacts: &Vec<SessionArtifacts>) {
     }
 }
 
+pub fn display_cost_bounds(contracts_bounds: &BTreeMap<String, Vec<FunctionCostBound>>) {
+    println!("\nStatic cost bounds");
+    let headers = vec![
+        "".to_string(),
+        "Runtime (units)".to_string(),
+        "Read Count".to_string(),
+        "Read Length (bytes)".to_string(),
+        "Write Count".to_string(),
+        "Write Length (bytes)".to_string(),
+    ];
+    let mut headers_cells = vec![];
+    for header in headers.iter() {
+        headers_cells.push(Cell::new(&header).with_style(Attr::Bold));
+    }
+    let limit = &clarity_repl::repl::interpreter::BLOCK_LIMIT_MAINNET;
+
+    for (contract_name, bounds) in contracts_bounds.iter() {
+        if bounds.is_empty() {
+            continue;
+        }
+        let mut table = Table::new();
+        table.set_format(*format::consts::FORMAT_BOX_CHARS);
+
+        let formatted_contract_name = &format!("\n✨  {}\n ", contract_name);
+        table.add_row(Row::new(vec![Cell::new(formatted_contract_name)
+            .with_style(Attr::Bold)
+            .with_style(Attr::ForegroundColor(color::YELLOW))
+            .with_hspan(6)]));
+
+        table.add_row(Row::new(headers_cells.clone()));
+
+        for bound in bounds.iter() {
+            let color = match bound.exceeds_block_limit.is_empty() {
+                true => color::GREEN,
+                false => color::RED,
+            };
+            let cell = |value: u64, dimension: &str| {
+                let cell = Cell::new_align(&format!("{}", value), format::Alignment::RIGHT);
+                match bound.exceeds_block_limit.iter().any(|d| d == dimension) {
+                    true => cell.with_style(Attr::ForegroundColor(color::RED)),
+                    false => cell,
+                }
+            };
+            table.add_row(Row::new(vec![
+                Cell::new_align(&bound.name, format::Alignment::LEFT)
+                    .with_style(Attr::Bold)
+                    .with_style(Attr::ForegroundColor(color)),
+                cell(bound.cost.runtime, "runtime"),
+                cell(bound.cost.read_count, "read_count"),
+                cell(bound.cost.read_length, "read_length"),
+                cell(bound.cost.write_count, "write_count"),
+                cell(bound.cost.write_length, "write_length"),
+            ]));
+        }
+
+        table.add_row(Row::new(vec![
+            Cell::new("Block Limits").with_style(Attr::Bold),
+            Cell::new_align(&format!("{}", limit.runtime), format::Alignment::RIGHT),
+            Cell::new_align(&format!("{}", limit.read_count), format::Alignment::RIGHT),
+            Cell::new_align(&format!("{}", limit.read_length), format::Alignment::RIGHT),
+            Cell::new_align(&format!("{}", limit.write_count), format::Alignment::RIGHT),
+            Cell::new_align(&format!("{}", limit.write_length), format::Alignment::RIGHT),
+        ]));
+
+        table.printstd();
+
+        for bound in bounds.iter() {
+            if !bound.exceeds_block_limit.is_empty() {
+                println!(
+                    "{}: {} can exceed the block limit ({})",
+                    yellow!("warning"),
+                    bound.name,
+                    bound.exceeds_block_limit.join(", ")
+                );
+            }
+            if bound.contract_calls > 0 {
+                println!(
+                    "{}: {} does not include the cost of {} contract call{}",
+                    blue!("note"),
+                    bound.name,
+                    bound.contract_calls,
+                    if bound.contract_calls == 1 { "" } else { "s" }
+                );
+            }
+            if bound.unknown_iterations {
+                println!(
+                    "{}: {} iterates over a sequence of unknown length, not included",
+                    blue!("note"),
+                    bound.name
+                );
+            }
+        }
+        println!("");
+    }
+}
+
 fn execution_costs_summary(min: &ExecutionCost, max: &ExecutionCost, avg: &ExecutionCost) -> Table {
     let mut table = Table::new();
     table.set_format(*format::consts::FORMAT_CLEAN);
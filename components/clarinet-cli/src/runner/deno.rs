@@ -25,6 +25,8 @@ use super::{api_v1, costs, ChainhookEvent, DeploymentCache, SessionArtifacts};
 use chainhook_event_observer::chainhooks::types::StacksChainhookSpecification;
 use clarinet_files::{FileLocation, ProjectManifest};
 use clarity_repl::analysis::coverage::CoverageReporter;
+use clarity_repl::clarity::vm::diagnostic::Diagnostic;
+use clarity_repl::clarity::vm::types::QualifiedContractIdentifier;
 use deno_ast::swc::common::comments::CommentKind;
 use deno_ast::MediaType;
 use deno_ast::SourceRangedForSpanned;
@@ -35,6 +37,7 @@ use deno_core::futures::future;
 use deno_core::futures::stream;
 use deno_core::futures::FutureExt;
 use deno_core::futures::StreamExt;
+use deno_core::serde_json::Value;
 use deno_core::ModuleSpecifier;
 use deno_graph::ModuleKind;
 use indexmap::IndexMap;
@@ -213,6 +216,31 @@ pub async fn do_run_scripts(
     Ok(success)
 }
 
+pub async fn do_run_rule_scripts(
+    manifest: &ProjectManifest,
+    contracts: Vec<Value>,
+) -> Result<Vec<(QualifiedContractIdentifier, Diagnostic)>, AnyError> {
+    let project_root = manifest.location.get_project_root_location().unwrap();
+    let flags = Flags {
+        cache_path: Some(manifest.project.cache_location.to_string().into()),
+        type_check_mode: TypeCheckMode::None,
+        ..Default::default()
+    };
+    let ps = ProcState::build(flags).await?;
+    let permissions = Permissions::from_options(&ps.options.permissions_options());
+
+    let mut diagnostics = vec![];
+    for script in manifest.repl_settings.analysis.rule_scripts().iter() {
+        let mut location = project_root.clone();
+        location.append_path(script).map_err(AnyError::msg)?;
+        let specifier = deno_core::resolve_url_or_path(&location.to_string())?;
+        let mut script_diagnostics =
+            api_v1::run_rule_script(&ps, permissions.clone(), specifier, contracts.clone()).await?;
+        diagnostics.append(&mut script_diagnostics);
+    }
+    Ok(diagnostics)
+}
+
 // pub fn is_supported_ext(path: &Path) -> bool {
 //     if let Some(ext) = fs_util::get_extension(path) {
 //         matches!(ext.as_str(), "ts" | "js" | "clar")
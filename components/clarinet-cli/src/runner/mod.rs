@@ -9,11 +9,16 @@ use clarity_repl::analysis::coverage::TestCoverageReport;
 use clarity_repl::clarity::vm::analysis::contract_interface_builder::{
     build_contract_interface, ContractInterface,
 };
+use clarity_repl::clarity::vm::analysis::ContractAnalysis;
 use clarity_repl::clarity::vm::ast::ContractAST;
+use clarity_repl::clarity::vm::diagnostic::Diagnostic;
+use clarity_repl::clarity::vm::representations::SymbolicExpression;
+use clarity_repl::clarity::vm::representations::SymbolicExpressionType::*;
 use clarity_repl::clarity::vm::types::QualifiedContractIdentifier;
 use clarity_repl::clarity::vm::EvaluationResult;
 use clarity_repl::repl::{session::CostsReport, Session};
 use deno_core::error::AnyError;
+use deno_core::serde_json::{json, Value};
 use std::collections::HashMap;
 
 use clarinet_deployments::types::DeploymentSpecification;
@@ -23,6 +28,8 @@ mod costs;
 mod deno;
 mod vendor;
 
+pub use costs::display_cost_bounds;
+
 #[derive(Clone)]
 pub struct DeploymentCache {
     pub session: Session,
@@ -139,6 +146,59 @@ pub fn run_scripts(
     ))
 }
 
+/// Run the rule scripts of the project against its contracts, and return the
+/// diagnostics they reported.
+pub fn run_rule_scripts(
+    manifest: &ProjectManifest,
+    deployment: &DeploymentSpecification,
+    analysis: &HashMap<QualifiedContractIdentifier, ContractAnalysis>,
+) -> Result<Vec<(QualifiedContractIdentifier, Diagnostic)>, AnyError> {
+    let mut contracts = vec![];
+    for (contract_id, (source, _)) in deployment.contracts.iter() {
+        if let Some(contract_analysis) = analysis.get(contract_id) {
+            contracts.push(json!({
+                "contract_id": contract_id.to_string(),
+                "contract_interface": build_contract_interface(contract_analysis),
+                "source": source,
+                "expressions": contract_analysis
+                    .expressions
+                    .iter()
+                    .map(serialize_expression)
+                    .collect::<Vec<_>>(),
+            }));
+        }
+    }
+    block_on(deno::do_run_rule_scripts(manifest, contracts))
+}
+
+// Serialize an expression for the rule scripts, as a tree of lists, atoms
+// and values, with their location.
+fn serialize_expression(expr: &SymbolicExpression) -> Value {
+    let mut serialized = match &expr.expr {
+        List(list) => json!({
+            "type": "list",
+            "items": list.iter().map(serialize_expression).collect::<Vec<_>>(),
+        }),
+        Atom(name) | TraitReference(name, _) => json!({
+            "type": "atom",
+            "value": name.to_string(),
+        }),
+        Field(field) => json!({
+            "type": "atom",
+            "value": format!("{}.{}", field.contract_identifier, field.name),
+        }),
+        AtomValue(value) | LiteralValue(value) => json!({
+            "type": "value",
+            "value": value.to_string(),
+        }),
+    };
+    serialized["line"] = json!(expr.span.start_line);
+    serialized["column"] = json!(expr.span.start_column);
+    serialized["endLine"] = json!(expr.span.end_line);
+    serialized["endColumn"] = json!(expr.span.end_column);
+    serialized
+}
+
 pub fn block_on<F, R>(future: F) -> R
 where
     F: std::future::Future<Output = R>,
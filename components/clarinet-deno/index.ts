@@ -1,6 +1,10 @@
 export class Tx {
   type: number;
   sender: string;
+  sponsor?: string;
+  fee?: number;
+  nonce?: number;
+  signers?: Array<string>;
   contractCall?: TxContractCall;
   transferStx?: TxTransfer;
   deployContract?: TxDeployContract;
@@ -42,6 +46,29 @@ export class Tx {
     };
     return tx;
   }
+
+  sponsoredBy(sponsor: string, fee?: number) {
+    this.sponsor = sponsor;
+    if (fee !== undefined) {
+      this.fee = fee;
+    }
+    return this;
+  }
+
+  withFee(fee: number) {
+    this.fee = fee;
+    return this;
+  }
+
+  withNonce(nonce: number) {
+    this.nonce = nonce;
+    return this;
+  }
+
+  signedBy(signers: Array<string>) {
+    this.signers = signers;
+    return this;
+  }
 }
 
 export interface TxContractCall {
@@ -62,6 +89,10 @@ export interface TxTransfer {
 
 export interface TxReceipt {
   result: string;
+  sponsor?: string;
+  fee: number;
+  nonce?: number;
+  rejected?: string;
   events: Array<any>;
 }
 
@@ -70,6 +101,13 @@ export interface Block {
   receipts: Array<TxReceipt>;
 }
 
+export interface BurnBlockInfo {
+  headerHash?: string;
+  burnBlockHeight?: number;
+  poxAddrs?: Array<string>;
+  payout?: number;
+}
+
 export interface Account {
   address: string;
   balance: number;
@@ -185,6 +223,30 @@ export class Chain {
     };
     return assetsMaps;
   }
+
+  createMultisig(requiredSignatures: number, signers: Array<string>): string {
+    let result = JSON.parse(
+      // @ts-ignore
+      Deno.core.opSync("api/v1/create_multisig", {
+        sessionId: this.sessionId,
+        requiredSignatures: requiredSignatures,
+        signers: signers,
+      }),
+    );
+    return result.address;
+  }
+
+  setBurnBlockInfo(height: number, info: BurnBlockInfo) {
+    // @ts-ignore
+    Deno.core.opSync("api/v1/set_burn_block_info", {
+      sessionId: this.sessionId,
+      height: height,
+      headerHash: info.headerHash,
+      burnBlockHeight: info.burnBlockHeight,
+      poxAddrs: info.poxAddrs,
+      payout: info.payout,
+    });
+  }
 }
 
 type PreDeploymentFunction = (
@@ -228,6 +290,38 @@ interface ScriptOptions {
   fn: ScriptFunction;
 }
 
+export interface RuleExpression {
+  type: "list" | "atom" | "value";
+  // Name of the atom, or value, e.g. `u1`
+  value?: string;
+  items?: Array<RuleExpression>;
+  line: number;
+  column: number;
+  endLine: number;
+  endColumn: number;
+}
+
+export interface RuleContract extends Contract {
+  expressions: Array<RuleExpression>;
+}
+
+export interface RuleDiagnostic {
+  contractId: string;
+  level?: "warning" | "error" | "note";
+  message: string;
+  // Expression reported, used for the location of the diagnostic
+  expression?: RuleExpression;
+}
+
+type RuleFunction = (
+  contracts: Map<string, RuleContract>,
+) => Array<RuleDiagnostic> | Promise<Array<RuleDiagnostic>>;
+
+interface RuleOptions {
+  name: string;
+  fn: RuleFunction;
+}
+
 export class Clarinet {
   static test(options: UnitTestOptions) {
     // @ts-ignore
@@ -311,6 +405,23 @@ export class Clarinet {
       },
     });
   }
+
+  static async rule(options: RuleOptions) {
+    let result = JSON.parse(
+      // @ts-ignore
+      Deno.core.opSync("api/v1/get_rule_contracts"),
+    );
+    let contracts: Map<string, RuleContract> = new Map();
+    for (let contract of result) {
+      contracts.set(contract.contract_id, contract);
+    }
+    let diagnostics = await options.fn(contracts);
+    // @ts-ignore
+    Deno.core.opSync("api/v1/report_rule_diagnostics", {
+      rule: options.name,
+      diagnostics,
+    });
+  }
 }
 
 export namespace types {
@@ -24,7 +24,7 @@ use clarity_repl::analysis::ast_dependency_detector::{ASTDependencyDetector, Dep
 use clarity_repl::clarity::vm::ast::ContractAST;
 use clarity_repl::clarity::vm::diagnostic::Diagnostic;
 use clarity_repl::clarity::vm::types::PrincipalData;
-use clarity_repl::clarity::vm::types::QualifiedContractIdentifier;
+use clarity_repl::clarity::vm::types::{QualifiedContractIdentifier, StandardPrincipalData};
 use clarity_repl::clarity::vm::ContractName;
 use clarity_repl::clarity::vm::EvaluationResult;
 use clarity_repl::clarity::vm::ExecutionResult;
@@ -161,7 +161,7 @@ pub fn update_session_with_contracts_executions(
                         deployer: ContractDeployer::Address(tx.emulated_sender.to_string()),
                         name: tx.contract_name.to_string(),
                         clarity_version: tx.clarity_version,
-                        epoch: forced_epoch.unwrap_or(DEFAULT_EPOCH),
+                        epoch: forced_epoch.unwrap_or(tx.epoch),
                     };
 
                     let result = session.deploy_contract(
@@ -193,6 +193,297 @@ pub fn update_session_with_contracts_executions(
     results
 }
 
+/// Build the ASTs of the contracts of the project, and of the requirements
+/// they depend on, without ordering them, so that they can be inspected even
+/// when their dependencies are circular.
+pub async fn load_contracts_asts(
+    manifest: &ProjectManifest,
+    file_accessor: Option<&Box<dyn FileAccessor>>,
+) -> Result<
+    (
+        HashMap<QualifiedContractIdentifier, ContractAST>,
+        BTreeMap<QualifiedContractIdentifier, ContractAST>,
+    ),
+    String,
+> {
+    let network = StacksNetwork::Simnet;
+    let network_manifest = match file_accessor {
+        None => NetworkManifest::from_project_manifest_location(
+            &manifest.location,
+            &network.get_networks(),
+            Some(&manifest.project.cache_location),
+        )?,
+        Some(file_accessor) => {
+            NetworkManifest::from_project_manifest_location_using_file_accessor(
+                &manifest.location,
+                &network.get_networks(),
+                file_accessor,
+            )
+            .await?
+        }
+    };
+
+    let mut settings = SessionSettings::default();
+    settings.repl_settings = manifest.repl_settings.clone();
+    let session = Session::new(settings);
+
+    let base_location = manifest.location.clone().get_parent_location()?;
+    let sources = read_contracts_sources(manifest, &base_location, file_accessor).await?;
+    let mut contract_asts = HashMap::new();
+    for (name, contract_config) in manifest.contracts.iter() {
+        let sender = resolve_contract_deployer(&network_manifest, &contract_config.deployer)?;
+        let mut contract_location = base_location.clone();
+        contract_location.append_path(&contract_config.expect_contract_path_as_str())?;
+        let source = sources
+            .get(&contract_location.to_string())
+            .ok_or(format!(
+                "Invalid Clarinet.toml, source file not found for: {}",
+                name
+            ))?
+            .clone();
+
+        let contract = ClarityContract {
+            code_source: ClarityCodeSource::ContractInMemory(source),
+            deployer: ContractDeployer::Address(sender.to_address()),
+            name: name.to_string(),
+            clarity_version: contract_config.clarity_version,
+            epoch: contract_config.epoch,
+        };
+        let (ast, _, _) = session.interpreter.build_ast(&contract);
+        contract_asts.insert(contract.expect_resolved_contract_identifier(None), ast);
+    }
+
+    // Requirements are discovered like when generating a deployment, the boot
+    // contracts being only used to resolve their dependencies.
+    let mut requirements_asts = BTreeMap::new();
+    let mut requirements_deps = HashMap::new();
+    let mut boot_contracts_ids = Vec::new();
+    for (id, (_, ast)) in BOOT_CONTRACTS_DATA.clone() {
+        boot_contracts_ids.push(id.clone());
+        requirements_asts.insert(id, ast);
+    }
+    let queue = queue_requirements(manifest, &network_manifest)?;
+    retrieve_requirements(
+        &session,
+        queue,
+        &manifest.project.cache_location,
+        file_accessor,
+        DEFAULT_EPOCH,
+        &mut requirements_asts,
+        &mut requirements_deps,
+    )
+    .await?;
+    requirements_asts.retain(|contract_id, _| {
+        !boot_contracts_ids.contains(contract_id) && !contract_asts.contains_key(contract_id)
+    });
+
+    Ok((contract_asts, requirements_asts))
+}
+
+/// Resolve the address of the account deploying a contract of the manifest.
+fn resolve_contract_deployer(
+    network_manifest: &NetworkManifest,
+    deployer: &ContractDeployer,
+) -> Result<StandardPrincipalData, String> {
+    let deployer = match deployer {
+        ContractDeployer::DefaultDeployer => "deployer",
+        ContractDeployer::LabeledDeployer(deployer) => deployer,
+        _ => unreachable!(),
+    };
+    let account = match network_manifest.accounts.get(deployer) {
+        Some(account) => account,
+        None => {
+            return Err(format!("unable to retrieve account '{}'", deployer));
+        }
+    };
+    match PrincipalData::parse_standard_principal(&account.stx_address) {
+        Ok(res) => Ok(res),
+        Err(_) => Err(format!(
+            "unable to turn emulated_sender {} as a valid Stacks address",
+            account.stx_address
+        )),
+    }
+}
+
+/// Read the sources of the contracts listed in the manifest, keyed by location.
+async fn read_contracts_sources(
+    manifest: &ProjectManifest,
+    base_location: &FileLocation,
+    file_accessor: Option<&Box<dyn FileAccessor>>,
+) -> Result<HashMap<String, String>, String> {
+    match file_accessor {
+        None => {
+            let mut sources = HashMap::new();
+            for (_, contract_config) in manifest.contracts.iter() {
+                let mut contract_location = base_location.clone();
+                contract_location
+                    .append_path(&contract_config.expect_contract_path_as_str())
+                    .map_err(|_| {
+                        format!(
+                            "unable to build path for contract {}",
+                            contract_config.expect_contract_path_as_str()
+                        )
+                    })?;
+
+                let source = contract_location.read_content_as_utf8().map_err(|_| {
+                    format!(
+                        "unable to find contract at path {}",
+                        contract_config.expect_contract_path_as_str()
+                    )
+                })?;
+                sources.insert(contract_location.to_string(), source);
+            }
+            Ok(sources)
+        }
+        Some(file_accessor) => {
+            let contracts_location = manifest
+                .contracts
+                .iter()
+                .map(|(_, contract_config)| {
+                    let mut contract_location = base_location.clone();
+                    contract_location
+                        .append_path(&contract_config.expect_contract_path_as_str())
+                        .unwrap();
+                    contract_location.to_string()
+                })
+                .collect();
+            file_accessor
+                .read_contracts_content(contracts_location)
+                .await
+        }
+    }
+}
+
+/// Queue the requirements explicitly listed in the manifest, and the subnet
+/// contract when a subnet node is enabled in the devnet.
+fn queue_requirements(
+    manifest: &ProjectManifest,
+    network_manifest: &NetworkManifest,
+) -> Result<VecDeque<QualifiedContractIdentifier>, String> {
+    let mut queue = VecDeque::new();
+
+    if let Some(ref devnet) = network_manifest.devnet {
+        if devnet.enable_subnet_node {
+            let contract_id = match QualifiedContractIdentifier::parse(&devnet.subnet_contract_id) {
+                Ok(contract_id) => contract_id,
+                Err(_e) => {
+                    return Err(format!(
+                        "malformatted subnet_contract_id: {}",
+                        devnet.subnet_contract_id
+                    ))
+                }
+            };
+            queue.push_front(contract_id)
+        }
+    }
+
+    for requirement in manifest.project.requirements.iter().flatten() {
+        let contract_id = match QualifiedContractIdentifier::parse(&requirement.contract_id) {
+            Ok(contract_id) => contract_id,
+            Err(_e) => {
+                return Err(format!(
+                    "malformatted contract_id: {}",
+                    requirement.contract_id
+                ))
+            }
+        };
+        queue.push_front(contract_id);
+    }
+
+    Ok(queue)
+}
+
+/// Retrieve the requirements of the queue, from the cache or the network, and
+/// the requirements they depend on, discovered as their ASTs are computed.
+/// Their ASTs and dependencies are added to `requirements_asts` and
+/// `requirements_deps`, and the sources and locations of the retrieved
+/// contracts are returned.
+async fn retrieve_requirements(
+    session: &Session,
+    mut queue: VecDeque<QualifiedContractIdentifier>,
+    cache_location: &FileLocation,
+    file_accessor: Option<&Box<dyn FileAccessor>>,
+    epoch: StacksEpochId,
+    requirements_asts: &mut BTreeMap<QualifiedContractIdentifier, ContractAST>,
+    requirements_deps: &mut HashMap<QualifiedContractIdentifier, DependencySet>,
+) -> Result<HashMap<QualifiedContractIdentifier, (String, FileLocation)>, String> {
+    let mut requirements_sources = HashMap::new();
+
+    while let Some(contract_id) = queue.pop_front() {
+        // Extract principal from contract_id
+        if requirements_deps.contains_key(&contract_id) {
+            continue;
+        }
+
+        // Did we already get the source in a prior cycle?
+        let ast = match requirements_asts.remove(&contract_id) {
+            Some(ast) => ast,
+            None => {
+                // Download the code
+                let (source, contract_location) =
+                    requirements::retrieve_contract(&contract_id, cache_location, &file_accessor)
+                        .await?;
+                requirements_sources
+                    .insert(contract_id.clone(), (source.clone(), contract_location));
+
+                // Compute the AST
+                let contract = ClarityContract {
+                    code_source: ClarityCodeSource::ContractInMemory(source),
+                    name: contract_id.name.to_string(),
+                    deployer: ContractDeployer::ContractIdentifier(contract_id.clone()),
+                    clarity_version: ClarityVersion::Clarity1,
+                    epoch,
+                };
+                let (ast, _, _) = session.interpreter.build_ast(&contract);
+                ast
+            }
+        };
+
+        // Detect the eventual dependencies for this AST
+        let mut contract_ast = HashMap::new();
+        contract_ast.insert(contract_id.clone(), ast);
+        let dependencies =
+            ASTDependencyDetector::detect_dependencies(&contract_ast, requirements_asts);
+        let ast = contract_ast
+            .remove(&contract_id)
+            .expect("unable to retrieve ast");
+
+        // Extract the known / unknown dependencies
+        match dependencies {
+            Ok(inferable_dependencies) => {
+                // Looping could be confusing - in this case, we submitted a HashMap with one contract, so we have at most one
+                // result in the `inferable_dependencies` map. We will just extract and keep the associated data (source, ast, deps).
+                for (contract_id, dependencies) in inferable_dependencies.into_iter() {
+                    for dependency in dependencies.iter() {
+                        queue.push_back(dependency.contract_id.clone());
+                    }
+                    requirements_deps.insert(contract_id.clone(), dependencies);
+                    requirements_asts.insert(contract_id.clone(), ast);
+                    break;
+                }
+            }
+            Err((inferable_dependencies, non_inferable_dependencies)) => {
+                // In the case of unknown dependencies, we were unable to construct an exhaustive list of dependencies.
+                // As such, we will re-enqueue the present (front) and push all the unknown contract_ids in front of it,
+                // and we will keep the source in memory to avoid useless disk access.
+                for (_, dependencies) in inferable_dependencies.iter() {
+                    for dependency in dependencies.iter() {
+                        queue.push_back(dependency.contract_id.clone());
+                    }
+                }
+                requirements_asts.insert(contract_id.clone(), ast);
+                queue.push_front(contract_id);
+
+                for non_inferable_contract_id in non_inferable_dependencies.into_iter() {
+                    queue.push_front(non_inferable_contract_id);
+                }
+            }
+        };
+    }
+
+    Ok(requirements_sources)
+}
+
 pub async fn generate_default_deployment(
     manifest: &ProjectManifest,
     network: &StacksNetwork,
@@ -302,155 +593,64 @@ pub async fn generate_default_deployment(
     }
     requirements_asts.append(&mut boot_contracts_asts);
 
-    let mut queue = VecDeque::new();
-
-    if let Some(ref devnet) = network_manifest.devnet {
-        if devnet.enable_subnet_node {
-            let contract_id = match QualifiedContractIdentifier::parse(&devnet.subnet_contract_id) {
-                Ok(contract_id) => contract_id,
-                Err(_e) => {
-                    return Err(format!(
-                        "malformatted subnet_contract_id: {}",
-                        devnet.subnet_contract_id
-                    ))
-                }
-            };
-            queue.push_front(contract_id)
-        }
-    }
+    let queue = queue_requirements(manifest, &network_manifest)?;
 
     // Build the ASTs / DependencySet for requirements - step required for Simnet/Devnet/Testnet/Mainnet
-    if let Some(ref requirements) = manifest.project.requirements {
-        let cache_location = &manifest.project.cache_location;
+    if manifest.project.requirements.is_some() {
         let mut emulated_contracts_publish = HashMap::new();
         let mut requirements_publish = HashMap::new();
 
         // Load all the requirements
         // Some requirements are explicitly listed, some are discovered as we compute the ASTs.
-
-        for requirement in requirements.iter() {
-            let contract_id = match QualifiedContractIdentifier::parse(&requirement.contract_id) {
-                Ok(contract_id) => contract_id,
-                Err(_e) => {
-                    return Err(format!(
-                        "malformatted contract_id: {}",
-                        requirement.contract_id
-                    ))
-                }
-            };
-            queue.push_front(contract_id);
-        }
-
-        while let Some(contract_id) = queue.pop_front() {
-            // Extract principal from contract_id
-            if requirements_deps.contains_key(&contract_id) {
-                continue;
-            }
-
-            // Did we already get the source in a prior cycle?
-            let ast = match requirements_asts.remove(&contract_id) {
-                Some(ast) => ast,
-                None => {
-                    // Download the code
-                    let (source, contract_location) = requirements::retrieve_contract(
-                        &contract_id,
-                        &cache_location,
-                        &file_accessor,
-                    )
-                    .await?;
-
-                    // Build the struct representing the requirement in the deployment
-                    if network.is_simnet() {
-                        let data = EmulatedContractPublishSpecification {
-                            contract_name: contract_id.name.clone(),
-                            emulated_sender: contract_id.issuer.clone(),
-                            source: source.clone(),
-                            location: contract_location,
-                            clarity_version: ClarityVersion::Clarity1,
-                        };
-                        emulated_contracts_publish.insert(contract_id.clone(), data);
-                    } else if network.either_devnet_or_testnet() {
-                        let mut remap_principals = BTreeMap::new();
-                        remap_principals
-                            .insert(contract_id.issuer.clone(), default_deployer_address.clone());
-                        match network_manifest.devnet {
-                            Some(ref devnet)
-                                if devnet.subnet_contract_id == contract_id.to_string() =>
-                            {
-                                remap_principals.insert(
-                                    contract_id.issuer.clone(),
-                                    PrincipalData::parse_standard_principal(
-                                        &devnet.subnet_leader_stx_address,
-                                    )
-                                    .unwrap(),
-                                );
-                            }
-                            _ => {}
-                        }
-                        let data = RequirementPublishSpecification {
-                            contract_id: contract_id.clone(),
-                            remap_sender: default_deployer_address.clone(),
-                            source: source.clone(),
-                            location: contract_location,
-                            cost: deployment_fee_rate * source.len() as u64,
-                            remap_principals,
-                        };
-                        requirements_publish.insert(contract_id.clone(), data);
-                    }
-
-                    // Compute the AST
-                    let contract = ClarityContract {
-                        code_source: ClarityCodeSource::ContractInMemory(source),
-                        name: contract_id.name.to_string(),
-                        deployer: ContractDeployer::ContractIdentifier(contract_id.clone()),
-                        clarity_version: ClarityVersion::Clarity1,
-                        epoch: forced_epoch.unwrap_or(DEFAULT_EPOCH),
-                    };
-                    let (ast, _, _) = session.interpreter.build_ast(&contract);
-                    ast
-                }
-            };
-
-            // Detect the eventual dependencies for this AST
-            let mut contract_ast = HashMap::new();
-            contract_ast.insert(contract_id.clone(), ast);
-            let dependencies =
-                ASTDependencyDetector::detect_dependencies(&contract_ast, &requirements_asts);
-            let ast = contract_ast
-                .remove(&contract_id)
-                .expect("unable to retrieve ast");
-
-            // Extract the known / unknown dependencies
-            match dependencies {
-                Ok(inferable_dependencies) => {
-                    // Looping could be confusing - in this case, we submitted a HashMap with one contract, so we have at most one
-                    // result in the `inferable_dependencies` map. We will just extract and keep the associated data (source, ast, deps).
-                    for (contract_id, dependencies) in inferable_dependencies.into_iter() {
-                        for dependency in dependencies.iter() {
-                            queue.push_back(dependency.contract_id.clone());
-                        }
-                        requirements_deps.insert(contract_id.clone(), dependencies);
-                        requirements_asts.insert(contract_id.clone(), ast);
-                        break;
-                    }
-                }
-                Err((inferable_dependencies, non_inferable_dependencies)) => {
-                    // In the case of unknown dependencies, we were unable to construct an exhaustive list of dependencies.
-                    // As such, we will re-enqueue the present (front) and push all the unknown contract_ids in front of it,
-                    // and we will keep the source in memory to avoid useless disk access.
-                    for (_, dependencies) in inferable_dependencies.iter() {
-                        for dependency in dependencies.iter() {
-                            queue.push_back(dependency.contract_id.clone());
-                        }
-                    }
-                    requirements_asts.insert(contract_id.clone(), ast);
-                    queue.push_front(contract_id);
-
-                    for non_inferable_contract_id in non_inferable_dependencies.into_iter() {
-                        queue.push_front(non_inferable_contract_id);
+        let requirements_sources = retrieve_requirements(
+            &session,
+            queue,
+            &manifest.project.cache_location,
+            file_accessor,
+            forced_epoch.unwrap_or(DEFAULT_EPOCH),
+            &mut requirements_asts,
+            &mut requirements_deps,
+        )
+        .await?;
+
+        // Build the structs representing the requirements in the deployment
+        for (contract_id, (source, contract_location)) in requirements_sources.into_iter() {
+            if network.is_simnet() {
+                let data = EmulatedContractPublishSpecification {
+                    contract_name: contract_id.name.clone(),
+                    emulated_sender: contract_id.issuer.clone(),
+                    source,
+                    location: contract_location,
+                    clarity_version: ClarityVersion::Clarity1,
+                    epoch: DEFAULT_EPOCH,
+                };
+                emulated_contracts_publish.insert(contract_id, data);
+            } else if network.either_devnet_or_testnet() {
+                let mut remap_principals = BTreeMap::new();
+                remap_principals
+                    .insert(contract_id.issuer.clone(), default_deployer_address.clone());
+                match network_manifest.devnet {
+                    Some(ref devnet) if devnet.subnet_contract_id == contract_id.to_string() => {
+                        remap_principals.insert(
+                            contract_id.issuer.clone(),
+                            PrincipalData::parse_standard_principal(
+                                &devnet.subnet_leader_stx_address,
+                            )
+                            .unwrap(),
+                        );
                     }
+                    _ => {}
                 }
-            };
+                let data = RequirementPublishSpecification {
+                    contract_id: contract_id.clone(),
+                    remap_sender: default_deployer_address.clone(),
+                    cost: deployment_fee_rate * source.len() as u64,
+                    source,
+                    location: contract_location,
+                    remap_principals,
+                };
+                requirements_publish.insert(contract_id, data);
+            }
         }
 
         // Avoid listing requirements as deployment transactions to the deployment specification on Mainnet
@@ -486,47 +686,7 @@ pub async fn generate_default_deployment(
 
     let base_location = manifest.location.clone().get_parent_location()?;
 
-    let sources: HashMap<String, String> = match file_accessor {
-        None => {
-            let mut sources = HashMap::new();
-            for (_, contract_config) in manifest.contracts.iter() {
-                let mut contract_location = base_location.clone();
-                contract_location
-                    .append_path(&contract_config.expect_contract_path_as_str())
-                    .map_err(|_| {
-                        format!(
-                            "unable to build path for contract {}",
-                            contract_config.expect_contract_path_as_str()
-                        )
-                    })?;
-
-                let source = contract_location.read_content_as_utf8().map_err(|_| {
-                    format!(
-                        "unable to find contract at path {}",
-                        contract_config.expect_contract_path_as_str()
-                    )
-                })?;
-                sources.insert(contract_location.to_string(), source);
-            }
-            sources
-        }
-        Some(file_accessor) => {
-            let contracts_location = manifest
-                .contracts
-                .iter()
-                .map(|(_, contract_config)| {
-                    let mut contract_location = base_location.clone();
-                    contract_location
-                        .append_path(&contract_config.expect_contract_path_as_str())
-                        .unwrap();
-                    contract_location.to_string()
-                })
-                .collect();
-            file_accessor
-                .read_contracts_content(contracts_location)
-                .await?
-        }
-    };
+    let sources = read_contracts_sources(manifest, &base_location, file_accessor).await?;
 
     for (name, contract_config) in manifest.contracts.iter() {
         let contract_name = match ContractName::try_from(name.to_string()) {
@@ -534,29 +694,7 @@ pub async fn generate_default_deployment(
             Err(_) => return Err(format!("unable to use {} as a valid contract name", name)),
         };
 
-        let deployer = match &contract_config.deployer {
-            ContractDeployer::DefaultDeployer => default_deployer,
-            ContractDeployer::LabeledDeployer(deployer) => {
-                let deployer = match network_manifest.accounts.get(deployer) {
-                    Some(deployer) => deployer,
-                    None => {
-                        return Err(format!("unable to retrieve account '{}'", deployer));
-                    }
-                };
-                deployer
-            }
-            _ => unreachable!(),
-        };
-
-        let sender = match PrincipalData::parse_standard_principal(&deployer.stx_address) {
-            Ok(res) => res,
-            Err(_) => {
-                return Err(format!(
-                    "unable to turn emulated_sender {} as a valid Stacks address",
-                    deployer.stx_address
-                ))
-            }
-        };
+        let sender = resolve_contract_deployer(&network_manifest, &contract_config.deployer)?;
 
         let mut contract_location = base_location.clone();
         contract_location.append_path(&contract_config.expect_contract_path_as_str())?;
@@ -577,7 +715,7 @@ pub async fn generate_default_deployment(
                 deployer: ContractDeployer::Address(sender.to_address()),
                 name: contract_name.to_string(),
                 clarity_version: contract_config.clarity_version,
-                epoch: forced_epoch.unwrap_or(DEFAULT_EPOCH),
+                epoch: forced_epoch.unwrap_or(contract_config.epoch),
             },
         );
 
@@ -589,6 +727,7 @@ pub async fn generate_default_deployment(
                     source,
                     location: contract_location,
                     clarity_version: contract_config.clarity_version,
+                    epoch: contract_config.epoch,
                 },
             )
         } else {
@@ -601,6 +740,7 @@ pub async fn generate_default_deployment(
                 source,
                 anchor_block_only: true,
                 clarity_version: contract_config.clarity_version,
+                epoch: contract_config.epoch,
             })
         };
 
@@ -621,8 +761,15 @@ pub async fn generate_default_deployment(
         asts_success = asts_success && ast_success;
     }
 
-    let dependencies =
-        ASTDependencyDetector::detect_dependencies(&contract_asts, &requirements_asts);
+    let (dependencies, trait_diags) =
+        ASTDependencyDetector::check_trait_conformance(&contract_asts, &requirements_asts);
+
+    for (contract_id, mut diags) in trait_diags {
+        contract_diags
+            .entry(contract_id)
+            .or_insert_with(Vec::new)
+            .append(&mut diags);
+    }
 
     let mut dependencies = match dependencies {
         Ok(dependencies) => dependencies,
@@ -4,6 +4,7 @@ use clarinet_files::{AccountConfig, NetworkManifest, ProjectManifest};
 use clarinet_utils::get_bip39_seed_from_mnemonic;
 use clarity_repl::clarity::codec::StacksMessageCodec;
 use clarity_repl::clarity::stacks_common::types::chainstate::StacksAddress;
+use clarity_repl::clarity::stacks_common::types::StacksEpochId;
 use clarity_repl::clarity::util::secp256k1::{
     MessageSignature, Secp256k1PrivateKey, Secp256k1PublicKey,
 };
@@ -11,7 +12,7 @@ use clarity_repl::clarity::vm::types::{
     PrincipalData, QualifiedContractIdentifier, StandardPrincipalData,
 };
 use clarity_repl::clarity::vm::{ClarityName, Value};
-use clarity_repl::clarity::{ContractName, EvaluationResult};
+use clarity_repl::clarity::{ClarityVersion, ContractName, EvaluationResult};
 use clarity_repl::codec::{
     SinglesigHashMode, SinglesigSpendingCondition, StacksString, StacksTransactionSigner,
     TokenTransferMemo, TransactionAuth, TransactionContractCall, TransactionPayload,
@@ -171,9 +172,22 @@ pub fn encode_stx_transfer(
     sign_transaction_payload(account, payload, nonce, tx_fee, anchor_mode, network)
 }
 
+// Versioned smart contract payloads are only accepted from epoch 2.1
+fn publish_clarity_version(
+    clarity_version: ClarityVersion,
+    epoch: StacksEpochId,
+) -> Option<ClarityVersion> {
+    if epoch >= StacksEpochId::Epoch21 {
+        Some(clarity_version)
+    } else {
+        None
+    }
+}
+
 pub fn encode_contract_publish(
     contract_name: &ContractName,
     source: &str,
+    clarity_version: Option<ClarityVersion>,
     account: &AccountConfig,
     nonce: u64,
     tx_fee: u64,
@@ -186,7 +200,7 @@ pub fn encode_contract_publish(
     };
     sign_transaction_payload(
         account,
-        TransactionPayload::SmartContract(payload),
+        TransactionPayload::SmartContract(payload, clarity_version),
         nonce,
         tx_fee,
         anchor_mode,
@@ -291,11 +305,13 @@ pub fn update_deployment_costs(
                     };
                 }
                 TransactionSpecification::ContractPublish(tx) => {
-                    let transaction_payload =
-                        TransactionPayload::SmartContract(TransactionSmartContract {
+                    let transaction_payload = TransactionPayload::SmartContract(
+                        TransactionSmartContract {
                             name: tx.contract_name.clone(),
                             code_body: StacksString::from_str(&tx.source).unwrap(),
-                        });
+                        },
+                        publish_clarity_version(tx.clarity_version, tx.epoch),
+                    );
 
                     match stacks_rpc.estimate_transaction_fee(&transaction_payload, priority) {
                         Ok(fee) => {
@@ -544,6 +560,7 @@ pub fn apply_on_chain_deployment(
                     let transaction = match encode_contract_publish(
                         &tx.contract_name,
                         &source,
+                        publish_clarity_version(tx.clarity_version, tx.epoch),
                         *account,
                         nonce,
                         tx.cost,
@@ -619,6 +636,7 @@ pub fn apply_on_chain_deployment(
                     let transaction = match encode_contract_publish(
                         &tx.contract_id.name,
                         &source,
+                        None,
                         *account,
                         nonce,
                         tx.cost,
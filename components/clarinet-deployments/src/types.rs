@@ -15,7 +15,8 @@ use serde_yaml;
 use std::collections::BTreeMap;
 
 use clarity_repl::analysis::ast_dependency_detector::DependencySet;
-use clarity_repl::repl::{Session, DEFAULT_CLARITY_VERSION};
+use clarity_repl::clarity::stacks_common::types::StacksEpochId;
+use clarity_repl::repl::{Session, DEFAULT_CLARITY_VERSION, DEFAULT_EPOCH};
 use std::collections::HashMap;
 
 pub struct DeploymentGenerationArtifacts {
@@ -122,6 +123,8 @@ pub struct ContractPublishSpecificationFile {
     pub anchor_block_only: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub clarity_version: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub epoch: Option<String>,
 }
 
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
@@ -146,6 +149,8 @@ pub struct EmulatedContractPublishSpecificationFile {
     pub url: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub clarity_version: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub epoch: Option<String>,
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -305,6 +310,25 @@ impl ContractCallSpecification {
     }
 }
 
+fn parse_epoch(epoch: &Option<String>) -> Result<StacksEpochId, String> {
+    match epoch.as_deref() {
+        Some("2.0") => Ok(StacksEpochId::Epoch20),
+        Some("2.05") => Ok(StacksEpochId::Epoch2_05),
+        Some("2.1") => Ok(StacksEpochId::Epoch21),
+        Some(_) => Err("unable to parse epoch (can either be '2.0', '2.05' or '2.1')".to_string()),
+        None => Ok(DEFAULT_EPOCH),
+    }
+}
+
+fn epoch_to_string(epoch: &StacksEpochId) -> String {
+    match epoch {
+        StacksEpochId::Epoch2_05 => "2.05",
+        StacksEpochId::Epoch21 => "2.1",
+        _ => "2.0",
+    }
+    .to_string()
+}
+
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
 pub struct ContractPublishSpecification {
     pub contract_name: ContractName,
@@ -312,6 +336,7 @@ pub struct ContractPublishSpecification {
     pub location: FileLocation,
     pub source: String,
     pub clarity_version: ClarityVersion,
+    pub epoch: StacksEpochId,
     pub cost: u64,
     pub anchor_block_only: bool,
 }
@@ -369,6 +394,8 @@ impl ContractPublishSpecification {
             _ => Ok(DEFAULT_CLARITY_VERSION),
         }?;
 
+        let epoch = parse_epoch(&specs.epoch)?;
+
         Ok(ContractPublishSpecification {
             contract_name,
             expected_sender,
@@ -377,6 +404,7 @@ impl ContractPublishSpecification {
             cost: specs.cost,
             anchor_block_only: specs.anchor_block_only.unwrap_or(true),
             clarity_version,
+            epoch,
         })
     }
 }
@@ -522,6 +550,7 @@ pub struct EmulatedContractPublishSpecification {
     pub emulated_sender: StandardPrincipalData,
     pub source: String,
     pub clarity_version: ClarityVersion,
+    pub epoch: StacksEpochId,
     pub location: FileLocation,
 }
 
@@ -576,6 +605,8 @@ impl EmulatedContractPublishSpecification {
             _ => Ok(DEFAULT_CLARITY_VERSION),
         }?;
 
+        let epoch = parse_epoch(&specs.epoch)?;
+
         let source = location.read_content_as_utf8()?;
 
         Ok(EmulatedContractPublishSpecification {
@@ -584,6 +615,7 @@ impl EmulatedContractPublishSpecification {
             source,
             location,
             clarity_version,
+            epoch,
         })
     }
 }
@@ -912,6 +944,7 @@ impl TransactionPlanSpecification {
                                     ClarityVersion::Clarity1 => Some(1),
                                     ClarityVersion::Clarity2 => Some(2),
                                 },
+                                epoch: Some(epoch_to_string(&tx.epoch)),
                             },
                         )
                     }
@@ -937,6 +970,7 @@ impl TransactionPlanSpecification {
                                     ClarityVersion::Clarity1 => Some(1),
                                     ClarityVersion::Clarity2 => Some(2),
                                 },
+                                epoch: Some(epoch_to_string(&tx.epoch)),
                             },
                         )
                     }
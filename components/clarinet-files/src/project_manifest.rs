@@ -1,6 +1,7 @@
 use crate::FileAccessor;
 
 use super::FileLocation;
+use clarity_repl::analysis::LintSettings;
 use clarity_repl::clarity::stacks_common::types::StacksEpochId;
 use clarity_repl::clarity::ClarityVersion;
 use clarity_repl::repl;
@@ -101,7 +102,20 @@ impl ProjectManifest {
                 return Err(format!("Clarinet.toml file malformatted {:?}", e));
             }
         };
-        ProjectManifest::from_project_manifest_file(project_manifest_file, &location)
+        let mut manifest =
+            ProjectManifest::from_project_manifest_file(project_manifest_file, &location)?;
+
+        let project_root_location = location.get_parent_location()?;
+        for path in manifest.repl_settings.analysis.rules_files().to_vec() {
+            let mut rules_location = project_root_location.clone();
+            rules_location.append_path(&path)?;
+            let content = file_accessor.read_file(rules_location.to_string()).await?;
+            manifest
+                .repl_settings
+                .analysis
+                .add_rules_from_json(&path, &content)?;
+        }
+        Ok(manifest)
     }
 
     pub fn from_location(location: &FileLocation) -> Result<ProjectManifest, String> {
@@ -114,7 +128,20 @@ impl ProjectManifest {
                 }
             };
 
-        ProjectManifest::from_project_manifest_file(project_manifest_file, location)
+        let mut manifest =
+            ProjectManifest::from_project_manifest_file(project_manifest_file, location)?;
+
+        let project_root_location = location.get_parent_location()?;
+        for path in manifest.repl_settings.analysis.rules_files().to_vec() {
+            let mut rules_location = project_root_location.clone();
+            rules_location.append_path(&path)?;
+            let content = rules_location.read_content_as_utf8()?;
+            manifest
+                .repl_settings
+                .analysis
+                .add_rules_from_json(&path, &content)?;
+        }
+        Ok(manifest)
     }
 
     pub fn from_project_manifest_file(
@@ -131,6 +158,7 @@ impl ProjectManifest {
         if let Some(passes) = project_manifest_file.project.analysis {
             repl_settings.analysis.set_passes(passes);
         }
+        repl_settings.analysis.validate()?;
 
         let project_name = project_manifest_file.project.name;
         let mut project_root_location = manifest_location.get_parent_location()?;
@@ -243,6 +271,30 @@ impl ProjectManifest {
                                 }
                                 _ => DEFAULT_EPOCH,
                             };
+                            if clarity_version == ClarityVersion::Clarity2
+                                && epoch < StacksEpochId::Epoch21
+                            {
+                                return Err(format!(
+                                    "contract {} uses clarity_version 2, which requires epoch '2.1'",
+                                    contract_name
+                                ));
+                            }
+                            if let Some(lints) = contract_settings.get("lints") {
+                                let lints: BTreeMap<String, LintSettings> =
+                                    match lints.clone().try_into() {
+                                        Ok(lints) => lints,
+                                        Err(e) => {
+                                            return Err(format!(
+                                                "lints field invalid in contract {} ({})",
+                                                contract_name, e
+                                            ))
+                                        }
+                                    };
+                                config
+                                    .repl_settings
+                                    .analysis
+                                    .set_contract_lints(contract_name.clone(), lints)?;
+                            }
                             config_contracts.insert(
                                 contract_name.to_string(),
                                 ClarityContract {
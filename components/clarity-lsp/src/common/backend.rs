@@ -2,6 +2,7 @@ use crate::lsp_types::MessageType;
 use crate::state::{build_state, EditorState, ProtocolState};
 use crate::types::{CompletionItem, CompletionItemKind};
 use clarinet_files::{FileAccessor, FileLocation};
+use clarity_repl::analysis::fix::DiagnosticFix;
 use clarity_repl::clarity::diagnostic::Diagnostic;
 use serde::{Deserialize, Serialize};
 
@@ -16,13 +17,16 @@ pub enum LspNotification {
 #[derive(Debug, Serialize, Deserialize)]
 pub enum LspRequest {
     GetIntellisense(FileLocation),
+    // Location of the contract, and 1-based line and column of the cursor
+    GetHover(FileLocation, u32, u32),
 }
 
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 pub struct LspResponse {
-    pub aggregated_diagnostics: Vec<(FileLocation, Vec<Diagnostic>)>,
+    pub aggregated_diagnostics: Vec<(FileLocation, Vec<Diagnostic>, Vec<DiagnosticFix>)>,
     pub notification: Option<(MessageType, String)>,
     pub completion_items: Vec<CompletionItem>,
+    pub hover: Option<String>,
 }
 
 impl LspResponse {
@@ -31,6 +35,7 @@ impl LspResponse {
             aggregated_diagnostics: vec![],
             notification: None,
             completion_items: vec![],
+            hover: None,
         }
     }
 }
@@ -40,6 +45,7 @@ impl LspResponse {
         LspResponse {
             aggregated_diagnostics: vec![],
             completion_items: vec![],
+            hover: None,
             notification: Some((MessageType::ERROR, format!("Internal error: {}", message))),
         }
     }
@@ -69,6 +75,7 @@ pub async fn process_notification(
                         aggregated_diagnostics,
                         notification,
                         completion_items: vec![],
+                        hover: None,
                     });
                 }
                 Err(e) => return Ok(LspResponse::error(&e)),
@@ -97,6 +104,7 @@ pub async fn process_notification(
                         aggregated_diagnostics,
                         notification,
                         completion_items: vec![],
+                        hover: None,
                     });
                 }
                 Err(e) => return Ok(LspResponse::error(&e)),
@@ -116,6 +124,7 @@ pub async fn process_notification(
                         aggregated_diagnostics,
                         notification,
                         completion_items: vec![],
+                        hover: None,
                     });
                 }
                 Err(e) => return Ok(LspResponse::error(&e)),
@@ -145,6 +154,7 @@ pub async fn process_notification(
                         aggregated_diagnostics,
                         notification,
                         completion_items: vec![],
+                        hover: None,
                     });
                 }
                 Err(e) => return Ok(LspResponse::error(&e)),
@@ -191,7 +201,14 @@ pub fn process_request(command: LspRequest, editor_state: &EditorState) -> LspRe
                 aggregated_diagnostics: vec![],
                 notification: None,
                 completion_items,
+                hover: None,
             }
         }
+        LspRequest::GetHover(contract_location, line, column) => LspResponse {
+            aggregated_diagnostics: vec![],
+            notification: None,
+            completion_items: vec![],
+            hover: editor_state.get_hover_for_contract(&contract_location, line, column),
+        },
     }
 }
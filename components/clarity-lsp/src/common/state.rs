@@ -8,6 +8,9 @@ use clarinet_deployments::{
 use clarinet_files::ProjectManifest;
 use clarinet_files::{FileAccessor, FileLocation};
 use clarity_repl::analysis::ast_dependency_detector::DependencySet;
+use clarity_repl::analysis::baseline::{Baseline, BASELINE_FILE_NAME};
+use clarity_repl::analysis::cost_estimator::{estimate_costs, FunctionCostBound};
+use clarity_repl::analysis::fix::DiagnosticFix;
 use clarity_repl::clarity::analysis::ContractAnalysis;
 use clarity_repl::clarity::diagnostic::{Diagnostic as ClarityDiagnostic, Level as ClarityLevel};
 use clarity_repl::clarity::stacks_common::types::StacksEpochId;
@@ -24,8 +27,10 @@ pub struct ContractState {
     errors: Vec<ClarityDiagnostic>,
     warnings: Vec<ClarityDiagnostic>,
     notes: Vec<ClarityDiagnostic>,
+    fixes: Vec<DiagnosticFix>,
     contract_id: QualifiedContractIdentifier,
     analysis: Option<ContractAnalysis>,
+    cost_bounds: Vec<FunctionCostBound>,
     location: FileLocation,
 }
 
@@ -35,6 +40,7 @@ impl ContractState {
         _ast: ContractAST,
         _deps: DependencySet,
         mut diags: Vec<ClarityDiagnostic>,
+        fixes: Vec<DiagnosticFix>,
         analysis: Option<ContractAnalysis>,
         location: FileLocation,
     ) -> ContractState {
@@ -61,16 +67,32 @@ impl ContractState {
             None => CompletionMaps::default(),
         };
 
+        let cost_bounds = match analysis {
+            Some(ref analysis) => estimate_costs(analysis),
+            None => vec![],
+        };
+
         ContractState {
             contract_id,
             intellisense,
             errors,
             warnings,
             notes,
+            fixes,
             analysis,
+            cost_bounds,
             location,
         }
     }
+
+    pub fn get_hover(&self, line: u32, column: u32) -> Option<String> {
+        let bound = self.cost_bounds.iter().find(|bound| {
+            bound.span.start_line == line
+                && bound.span.start_column <= column
+                && column <= bound.span.end_column
+        })?;
+        Some(utils::build_cost_bound_hover(bound))
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -170,10 +192,23 @@ impl EditorState {
         keywords
     }
 
+    pub fn get_hover_for_contract(
+        &self,
+        contract_location: &FileLocation,
+        line: u32,
+        column: u32,
+    ) -> Option<String> {
+        self.contracts_lookup
+            .get(&contract_location)
+            .and_then(|d| self.protocols.get(&d.manifest_location))
+            .and_then(|p| p.contracts.get(contract_location))
+            .and_then(|contract| contract.get_hover(line, column))
+    }
+
     pub fn get_aggregated_diagnostics(
         &self,
     ) -> (
-        Vec<(FileLocation, Vec<ClarityDiagnostic>)>,
+        Vec<(FileLocation, Vec<ClarityDiagnostic>, Vec<DiagnosticFix>)>,
         Option<(MessageType, String)>,
     ) {
         let mut contracts = vec![];
@@ -209,7 +244,7 @@ impl EditorState {
                 for note in state.notes.iter() {
                     diags.push(note.clone());
                 }
-                contracts.push((contract_url.clone(), diags));
+                contracts.push((contract_url.clone(), diags, state.fixes.clone()));
             }
         }
 
@@ -260,6 +295,7 @@ impl ProtocolState {
         asts: &mut HashMap<QualifiedContractIdentifier, ContractAST>,
         deps: &mut HashMap<QualifiedContractIdentifier, DependencySet>,
         diags: &mut HashMap<QualifiedContractIdentifier, Vec<ClarityDiagnostic>>,
+        fixes: &mut HashMap<QualifiedContractIdentifier, Vec<DiagnosticFix>>,
         analyses: &mut HashMap<QualifiedContractIdentifier, Option<ContractAnalysis>>,
     ) {
         // Remove old paths
@@ -279,6 +315,10 @@ impl ProtocolState {
                 Some(diags) => diags,
                 None => vec![],
             };
+            let fixes = match fixes.remove(&contract_id) {
+                Some(fixes) => fixes,
+                None => vec![],
+            };
             let analysis = match analyses.remove(&contract_id) {
                 Some(analysis) => analysis,
                 None => None,
@@ -289,6 +329,7 @@ impl ProtocolState {
                 ast,
                 deps,
                 diags,
+                fixes,
                 analysis,
                 contract_location.clone(),
             );
@@ -352,6 +393,8 @@ pub async fn build_state(
     )
     .await?;
 
+    let baseline = load_baseline(manifest_location, file_accessor).await?;
+
     let mut session = initiate_session_from_deployment(&manifest);
     let results = update_session_with_contracts_executions(
         &mut session,
@@ -361,7 +404,7 @@ pub async fn build_state(
         Some(StacksEpochId::Epoch21),
     );
     for (contract_id, mut result) in results.into_iter() {
-        let (_, contract_location) = match deployment.contracts.get(&contract_id) {
+        let (source, contract_location) = match deployment.contracts.get(&contract_id) {
             Some(entry) => entry,
             None => continue,
         };
@@ -384,9 +427,15 @@ pub async fn build_state(
                 if let Some(entry) = artifacts.diags.get_mut(&contract_id) {
                     entry.append(diags);
                 }
-                continue;
             }
         };
+
+        if let Some(ref baseline) = baseline {
+            if let Some(diags) = artifacts.diags.remove(&contract_id) {
+                let diags = baseline.filter_diagnostics(&contract_id, diags, source);
+                artifacts.diags.insert(contract_id, diags);
+            }
+        }
     }
 
     protocol_state.consolidate(
@@ -394,8 +443,32 @@ pub async fn build_state(
         &mut artifacts.asts,
         &mut artifacts.deps,
         &mut artifacts.diags,
+        &mut session.interpreter.fixes,
         &mut analyses,
     );
 
     Ok(())
 }
+
+async fn load_baseline(
+    manifest_location: &FileLocation,
+    file_accessor: Option<&Box<dyn FileAccessor>>,
+) -> Result<Option<Baseline>, String> {
+    let mut location = manifest_location.get_parent_location()?;
+    location.append_path(BASELINE_FILE_NAME)?;
+    let content = match file_accessor {
+        None => {
+            if !location.exists() {
+                return Ok(None);
+            }
+            location.read_content_as_utf8()?
+        }
+        Some(file_accessor) => {
+            if !file_accessor.file_exists(location.to_string()).await? {
+                return Ok(None);
+            }
+            file_accessor.read_file(location.to_string()).await?
+        }
+    };
+    Baseline::from_json(&content).map(Some)
+}
@@ -1,5 +1,7 @@
 use super::types::*;
 use clarinet_files::FileLocation;
+use clarity_repl::analysis::cost_estimator::FunctionCostBound;
+use clarity_repl::analysis::fix::{fixes_for, DiagnosticFix, Fix, Position as FixPosition};
 use clarity_repl::clarity::functions::define::DefineFunctions;
 use clarity_repl::clarity::functions::NativeFunctions;
 use clarity_repl::clarity::variables::NativeVariables;
@@ -11,9 +13,13 @@ use clarity_repl::clarity::vm::docs::{
     make_api_reference, make_define_reference, make_keyword_reference,
 };
 use clarity_repl::clarity::vm::types::{BlockInfoProperty, FunctionType};
+use clarity_repl::repl::ast::formatter::{format_source, FormatterSettings};
 use lsp_types::Diagnostic as LspDiagnostic;
 use lsp_types::Url;
-use lsp_types::{DiagnosticSeverity, Position, Range};
+use lsp_types::{
+    CodeAction, CodeActionKind, DiagnosticSeverity, Position, Range, TextEdit, WorkspaceEdit,
+};
+use std::collections::HashMap;
 
 #[cfg(feature = "wasm")]
 macro_rules! log {
@@ -25,15 +31,21 @@ macro_rules! log {
 #[cfg(feature = "wasm")]
 pub(crate) use log;
 
-pub fn clarity_diagnostics_to_lsp_type(diagnostics: &Vec<ClarityDiagnostic>) -> Vec<LspDiagnostic> {
+pub fn clarity_diagnostics_to_lsp_type(
+    diagnostics: &Vec<ClarityDiagnostic>,
+    fixes: &[DiagnosticFix],
+) -> Vec<LspDiagnostic> {
     let mut dst = vec![];
     for d in diagnostics {
-        dst.push(clarity_diagnostic_to_lsp_type(d));
+        dst.push(clarity_diagnostic_to_lsp_type(d, fixes));
     }
     dst
 }
 
-pub fn clarity_diagnostic_to_lsp_type(diagnostic: &ClarityDiagnostic) -> LspDiagnostic {
+pub fn clarity_diagnostic_to_lsp_type(
+    diagnostic: &ClarityDiagnostic,
+    fixes: &[DiagnosticFix],
+) -> LspDiagnostic {
     let range = match diagnostic.spans.len() {
         0 => Range::default(),
         _ => Range {
@@ -61,7 +73,91 @@ pub fn clarity_diagnostic_to_lsp_type(diagnostic: &ClarityDiagnostic) -> LspDiag
         message: diagnostic.message.clone(),
         related_information: None,
         tags: None,
-        data: None,
+        data: fixes_to_lsp_data(diagnostic, fixes),
+    }
+}
+
+// The fixes resolving a diagnostic are sent to the client in its `data` field,
+// and sent back with the code action requests.
+pub fn fixes_to_lsp_data(
+    diagnostic: &ClarityDiagnostic,
+    fixes: &[DiagnosticFix],
+) -> Option<serde_json::Value> {
+    let fixes = fixes_for(fixes, diagnostic);
+    if fixes.is_empty() {
+        return None;
+    }
+    serde_json::to_value(fixes).ok()
+}
+
+fn fix_position_to_lsp_type(position: &FixPosition) -> Position {
+    Position {
+        line: position.line - 1,
+        character: position.column - 1,
+    }
+}
+
+// Build the quick fixes for the diagnostics of a code action request.
+pub fn diagnostics_to_code_actions(url: &Url, diagnostics: &[LspDiagnostic]) -> Vec<CodeAction> {
+    let mut actions = vec![];
+    for diagnostic in diagnostics {
+        let fixes: Vec<Fix> = match diagnostic
+            .data
+            .as_ref()
+            .and_then(|data| serde_json::from_value(data.clone()).ok())
+        {
+            Some(fixes) => fixes,
+            None => continue,
+        };
+        for fix in fixes {
+            let edits = fix
+                .edits
+                .iter()
+                .map(|edit| TextEdit {
+                    range: Range {
+                        start: fix_position_to_lsp_type(&edit.start),
+                        end: fix_position_to_lsp_type(&edit.end),
+                    },
+                    new_text: edit.text.clone(),
+                })
+                .collect();
+            let mut changes = HashMap::new();
+            changes.insert(url.clone(), edits);
+            actions.push(CodeAction {
+                title: fix.title,
+                kind: Some(CodeActionKind::QUICKFIX),
+                diagnostics: Some(vec![diagnostic.clone()]),
+                edit: Some(WorkspaceEdit::new(changes)),
+                ..CodeAction::default()
+            });
+        }
+    }
+    actions
+}
+
+// Build the edit replacing the whole document with its formatted source. No
+// edits are returned when it is already formatted or has syntax errors.
+pub fn build_formatting_edits(source: &str) -> Vec<TextEdit> {
+    match format_source(source, &FormatterSettings::default()) {
+        Ok(formatted) if formatted != source => vec![TextEdit {
+            range: Range {
+                start: Position {
+                    line: 0,
+                    character: 0,
+                },
+                end: end_of_document(source),
+            },
+            new_text: formatted,
+        }],
+        _ => vec![],
+    }
+}
+
+fn end_of_document(source: &str) -> Position {
+    let last_line = source.rsplit('\n').next().unwrap_or("");
+    Position {
+        line: source.matches('\n').count() as u32,
+        character: last_line.encode_utf16().count() as u32,
     }
 }
 
@@ -78,6 +174,47 @@ fn build_intellisense_args(signature: &FunctionType) -> Vec<String> {
     args
 }
 
+pub fn build_cost_bound_hover(bound: &FunctionCostBound) -> String {
+    let limit = &clarity_repl::repl::interpreter::BLOCK_LIMIT_MAINNET;
+    let mut lines = vec![
+        format!(
+            "**Static cost bound** of `{}` ({})",
+            bound.name, bound.access
+        ),
+        "".to_string(),
+        "| | bound | block limit |".to_string(),
+        "|---|---:|---:|".to_string(),
+    ];
+    for (dimension, value, limit) in [
+        ("runtime", bound.cost.runtime, limit.runtime),
+        ("read_count", bound.cost.read_count, limit.read_count),
+        ("read_length", bound.cost.read_length, limit.read_length),
+        ("write_count", bound.cost.write_count, limit.write_count),
+        ("write_length", bound.cost.write_length, limit.write_length),
+    ] {
+        lines.push(format!("| {} | {} | {} |", dimension, value, limit));
+    }
+    if !bound.exceeds_block_limit.is_empty() {
+        lines.push("".to_string());
+        lines.push(format!(
+            "**Warning**: can exceed the block limit ({})",
+            bound.exceeds_block_limit.join(", ")
+        ));
+    }
+    if bound.contract_calls > 0 {
+        lines.push("".to_string());
+        lines.push(format!(
+            "Does not include the cost of {} contract call(s)",
+            bound.contract_calls
+        ));
+    }
+    if bound.unknown_iterations {
+        lines.push("".to_string());
+        lines.push("Does not include iterations over sequences of unknown length".to_string());
+    }
+    lines.join("\n")
+}
+
 pub fn build_intellisense(analysis: &ContractAnalysis) -> CompletionMaps {
     let mut intra_contract = vec![];
     let mut inter_contract = vec![];
@@ -2,19 +2,23 @@ extern crate console_error_panic_hook;
 use crate::backend::{process_notification, process_request, LspNotification, LspRequest};
 use crate::state::EditorState;
 use crate::utils::{
-    clarity_diagnostics_to_lsp_type, get_contract_location, get_manifest_location, log,
+    build_formatting_edits, clarity_diagnostics_to_lsp_type, diagnostics_to_code_actions,
+    get_contract_location, get_manifest_location, log,
 };
 use clarinet_files::{FileAccessor, WASMFileSystemAccessor};
 use js_sys::{Function as JsFunction, Promise};
 use lsp_types::notification::{
-    DidOpenTextDocument, DidSaveTextDocument, Initialized, Notification,
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, DidSaveTextDocument,
+    Initialized, Notification,
 };
 use lsp_types::{
-    request::{Completion, Request},
-    CompletionParams, DidOpenTextDocumentParams, DidSaveTextDocumentParams,
-    PublishDiagnosticsParams, Url,
+    request::{CodeActionRequest, Completion, Formatting, HoverRequest, Request},
+    CodeActionParams, CompletionParams, DidChangeTextDocumentParams, DidCloseTextDocumentParams,
+    DidOpenTextDocumentParams, DidSaveTextDocumentParams, DocumentFormattingParams, Hover,
+    HoverContents, HoverParams, MarkupContent, MarkupKind, PublishDiagnosticsParams, Url,
 };
 use serde_wasm_bindgen::{from_value as decode_from_js, to_value as encode_to_js};
+use std::collections::HashMap;
 use std::panic;
 use std::sync::{Arc, RwLock};
 use wasm_bindgen::prelude::*;
@@ -26,6 +30,8 @@ pub struct LspVscodeBridge {
     client_diagnostic_tx: JsFunction,
     _client_notification_tx: JsFunction,
     backend_to_client_tx: JsFunction,
+    // Content of the opened documents, including the unsaved changes
+    documents: Arc<RwLock<HashMap<Url, String>>>,
 }
 
 #[wasm_bindgen]
@@ -44,6 +50,7 @@ impl LspVscodeBridge {
             client_diagnostic_tx,
             _client_notification_tx,
             backend_to_client_tx,
+            documents: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
@@ -64,6 +71,9 @@ impl LspVscodeBridge {
                     Err(err) => return Promise::reject(&JsValue::from(format!("error: {}", err))),
                 };
                 let uri = params.text_document.uri;
+                if let Ok(mut documents) = self.documents.write() {
+                    documents.insert(uri.clone(), params.text_document.text);
+                }
 
                 let command = if let Some(contract_location) = get_contract_location(&uri) {
                     LspNotification::ContractOpened(contract_location)
@@ -91,11 +101,11 @@ impl LspVscodeBridge {
                         aggregated_diagnostics.append(&mut response.aggregated_diagnostics);
                     }
 
-                    for (location, mut diags) in aggregated_diagnostics.into_iter() {
+                    for (location, diags, fixes) in aggregated_diagnostics.into_iter() {
                         if let Ok(uri) = Url::parse(&location.to_string()) {
                             let value = PublishDiagnosticsParams {
                                 uri,
-                                diagnostics: clarity_diagnostics_to_lsp_type(&mut diags),
+                                diagnostics: clarity_diagnostics_to_lsp_type(&diags, &fixes),
                                 version: None,
                             };
 
@@ -140,11 +150,11 @@ impl LspVscodeBridge {
                         aggregated_diagnostics.append(&mut response.aggregated_diagnostics);
                     }
 
-                    for (location, mut diags) in aggregated_diagnostics.into_iter() {
+                    for (location, diags, fixes) in aggregated_diagnostics.into_iter() {
                         if let Ok(uri) = Url::parse(&location.to_string()) {
                             let value = PublishDiagnosticsParams {
                                 uri,
-                                diagnostics: clarity_diagnostics_to_lsp_type(&mut diags),
+                                diagnostics: clarity_diagnostics_to_lsp_type(&diags, &fixes),
                                 version: None,
                             };
 
@@ -155,6 +165,29 @@ impl LspVscodeBridge {
                     Ok(JsValue::TRUE)
                 });
             }
+
+            DidChangeTextDocument::METHOD => {
+                let mut params: DidChangeTextDocumentParams = match decode_from_js(js_params) {
+                    Ok(params) => params,
+                    Err(err) => return Promise::reject(&JsValue::from(format!("error: {}", err))),
+                };
+                // The documents are synchronized in full, the last change is the content
+                if let Some(change) = params.content_changes.pop() {
+                    if let Ok(mut documents) = self.documents.write() {
+                        documents.insert(params.text_document.uri, change.text);
+                    }
+                }
+            }
+
+            DidCloseTextDocument::METHOD => {
+                let params: DidCloseTextDocumentParams = match decode_from_js(js_params) {
+                    Ok(params) => params,
+                    Err(err) => return Promise::reject(&JsValue::from(format!("error: {}", err))),
+                };
+                if let Ok(mut documents) = self.documents.write() {
+                    documents.remove(&params.text_document.uri);
+                }
+            }
             _ => {
                 #[cfg(debug_assertions)]
                 log!("unexpected notification ({})", method);
@@ -180,6 +213,47 @@ impl LspVscodeBridge {
 
                 return encode_to_js(&lsp_response.completion_items).map_err(|_| JsValue::NULL);
             }
+            CodeActionRequest::METHOD => {
+                let params: CodeActionParams = decode_from_js(js_params)?;
+                let actions = diagnostics_to_code_actions(
+                    &params.text_document.uri,
+                    &params.context.diagnostics,
+                );
+                return encode_to_js(&actions).map_err(|_| JsValue::NULL);
+            }
+            HoverRequest::METHOD => {
+                let params: HoverParams = decode_from_js(js_params)?;
+                let position = params.text_document_position_params.position;
+                let file_url = params.text_document_position_params.text_document.uri;
+                let location = get_contract_location(&file_url).ok_or(JsValue::NULL)?;
+                let command =
+                    LspRequest::GetHover(location, position.line + 1, position.character + 1);
+                let editor_state = self
+                    .editor_state_lock
+                    .try_read()
+                    .map_err(|_| JsValue::NULL)?;
+                let hover = process_request(command, &editor_state)
+                    .hover
+                    .map(|value| Hover {
+                        contents: HoverContents::Markup(MarkupContent {
+                            kind: MarkupKind::Markdown,
+                            value,
+                        }),
+                        range: None,
+                    });
+
+                return encode_to_js(&hover).map_err(|_| JsValue::NULL);
+            }
+            Formatting::METHOD => {
+                let params: DocumentFormattingParams = decode_from_js(js_params)?;
+                let uri = params.text_document.uri;
+                get_contract_location(&uri).ok_or(JsValue::NULL)?;
+                let documents = self.documents.try_read().map_err(|_| JsValue::NULL)?;
+                let source = documents.get(&uri).ok_or(JsValue::NULL)?;
+                let edits = build_formatting_edits(source);
+
+                return encode_to_js(&edits).map_err(|_| JsValue::NULL);
+            }
             _ => {
                 #[cfg(debug_assertions)]
                 log!("unexpected request ({})", method);
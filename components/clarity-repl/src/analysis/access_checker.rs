@@ -0,0 +1,1073 @@
+use crate::analysis::annotation::{Annotation, WarningKind};
+use crate::analysis::ast_visitor::{definition_name_span, traverse, ASTVisitor, TypedVar};
+use crate::analysis::{AnalysisPass, PassResult, Settings};
+use clarity::vm::analysis::analysis_db::AnalysisDatabase;
+use clarity::vm::analysis::types::ContractAnalysis;
+use clarity::vm::diagnostic::{Diagnostic, Level};
+use clarity::vm::representations::{Span, SymbolicExpression};
+use clarity::vm::ClarityName;
+use std::collections::{HashMap, HashSet};
+
+/// The principal checked by an authorization guard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CallerCheck {
+    TxSender,
+    ContractCaller,
+}
+
+/// Summary of the access control of a public function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AccessLevel {
+    /// Anyone can trigger at least one of the operations.
+    Unrestricted,
+    /// All operations are guarded, but some only by a `tx-sender` check.
+    TxSender,
+    /// All operations are guarded by a `contract-caller` check.
+    ContractCaller,
+    /// All operations only affect the caller's own assets or entries.
+    CallerScoped,
+    /// The function does not write state or move assets.
+    NoEffects,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Location {
+    pub line: u32,
+    pub column: u32,
+}
+
+impl From<&Span> for Location {
+    fn from(span: &Span) -> Self {
+        Location {
+            line: span.start_line,
+            column: span.start_column,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Guard {
+    pub check: CallerCheck,
+    pub location: Location,
+    #[serde(skip)]
+    span: Span,
+}
+
+/// An operation writing state or moving assets.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Operation {
+    pub operation: String,
+    pub target: Option<String>,
+    pub location: Location,
+    /// Check on the caller performed before this operation, if any.
+    pub guard: Option<Guard>,
+    /// True if the operation only affects the caller's own assets or entries,
+    /// e.g. `(stx-transfer? amount tx-sender recipient)` outside of
+    /// `as-contract`.
+    pub caller_scoped: bool,
+    #[serde(skip)]
+    span: Span,
+}
+
+impl Operation {
+    fn is_unguarded(&self) -> bool {
+        self.guard.is_none() && !self.caller_scoped
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FunctionAccess {
+    pub function: String,
+    pub location: Location,
+    pub access: AccessLevel,
+    pub operations: Vec<Operation>,
+    #[serde(skip)]
+    span: Span,
+}
+
+impl FunctionAccess {
+    fn new(function: &ClarityName, span: &Span, operations: Vec<Operation>) -> FunctionAccess {
+        let access = if operations.is_empty() {
+            AccessLevel::NoEffects
+        } else if operations.iter().any(|op| op.is_unguarded()) {
+            AccessLevel::Unrestricted
+        } else if operations
+            .iter()
+            .any(|op| matches!(&op.guard, Some(guard) if guard.check == CallerCheck::TxSender))
+        {
+            AccessLevel::TxSender
+        } else if operations.iter().any(|op| op.guard.is_some()) {
+            AccessLevel::ContractCaller
+        } else {
+            AccessLevel::CallerScoped
+        };
+        FunctionAccess {
+            function: function.to_string(),
+            location: Location::from(span),
+            access,
+            operations,
+            span: span.clone(),
+        }
+    }
+}
+
+struct FunctionSummary {
+    // Strongest check on the caller made in the body of the function
+    references: Option<CallerCheck>,
+    // Check unconditionally asserted by the function
+    asserts: Option<CallerCheck>,
+    // Operations performed by the function
+    operations: Vec<Operation>,
+}
+
+pub struct AccessChecker<'a> {
+    // Names of the constants of the contract
+    constants: HashSet<&'a ClarityName>,
+    // Summaries of private and read-only functions
+    user_funcs: HashMap<&'a ClarityName, FunctionSummary>,
+    // Guards in effect at the current point of the traversal
+    guards: Vec<Guard>,
+    // Operations performed by the current function
+    operations: Vec<Operation>,
+    // Depth of nested `as-contract` expressions
+    as_contract_depth: u32,
+    report: Vec<FunctionAccess>,
+}
+
+impl<'a> AccessChecker<'a> {
+    fn new() -> AccessChecker<'a> {
+        Self {
+            constants: HashSet::new(),
+            user_funcs: HashMap::new(),
+            guards: Vec::new(),
+            operations: Vec::new(),
+            as_contract_depth: 0,
+            report: Vec::new(),
+        }
+    }
+
+    fn run(mut self, contract_analysis: &'a ContractAnalysis) -> Vec<FunctionAccess> {
+        // Constants can be referenced before their definition
+        for expr in contract_analysis.expressions.iter() {
+            if let Some([define, name, ..]) = expr.match_list() {
+                if define.match_atom().map(|define| define.as_str()) == Some("define-constant") {
+                    if let Some(name) = name.match_atom() {
+                        self.constants.insert(name);
+                    }
+                }
+            }
+        }
+        traverse(&mut self, &contract_analysis.expressions);
+        self.report
+    }
+
+    fn enter_function(&mut self) {
+        self.guards.clear();
+        self.operations.clear();
+        self.as_contract_depth = 0;
+    }
+
+    // Returns the strongest guard currently in effect.
+    fn current_guard(&self) -> Option<Guard> {
+        self.guards
+            .iter()
+            .find(|guard| guard.check == CallerCheck::ContractCaller)
+            .or_else(|| self.guards.last())
+            .cloned()
+    }
+
+    fn push_guard(&mut self, check: CallerCheck, span: &Span) {
+        self.guards.push(Guard {
+            check,
+            location: Location::from(span),
+            span: span.clone(),
+        });
+    }
+
+    // Returns the strongest check on the caller made in `expr`, comparing
+    // `tx-sender` or `contract-caller` with a trusted principal using
+    // `is-eq`, either directly or through a call to a private or read-only
+    // function. A mere reference to the caller, e.g.
+    // `(stx-get-balance tx-sender)`, is not a check, and neither is a
+    // comparison inside `as-contract`, where the caller is the contract.
+    fn caller_check(&self, expr: &SymbolicExpression) -> Option<CallerCheck> {
+        if self.as_contract_depth > 0 {
+            return None;
+        }
+        let list = expr.match_list()?;
+        let function = list.get(0).and_then(|name| name.match_atom());
+        match function.map(|name| name.as_str()) {
+            Some("is-eq") => {
+                let args = &list[1..];
+                let mut found = None;
+                for (i, arg) in args.iter().enumerate() {
+                    let check = match match_caller(arg) {
+                        Some(check) => check,
+                        None => continue,
+                    };
+                    let trusted = args
+                        .iter()
+                        .enumerate()
+                        .all(|(j, other)| i == j || self.is_trusted_principal(other));
+                    match check {
+                        _ if !trusted => (),
+                        CallerCheck::ContractCaller => return Some(CallerCheck::ContractCaller),
+                        _ => found = Some(check),
+                    }
+                }
+                if found.is_some() {
+                    return found;
+                }
+            }
+            // A negated check does not authorize the caller
+            Some("not") => return None,
+            Some("as-contract") => return None,
+            _ => (),
+        }
+        let mut found = function
+            .and_then(|name| self.user_funcs.get(name))
+            .and_then(|summary| summary.references);
+        for item in list {
+            match self.caller_check(item) {
+                Some(CallerCheck::ContractCaller) => return Some(CallerCheck::ContractCaller),
+                Some(check) => found = Some(check),
+                None => (),
+            }
+        }
+        found
+    }
+
+    // A principal the caller can be compared with to authorize them: a
+    // literal, a constant or a principal read from storage. Comparing the
+    // caller with a parameter only checks that the caller passed themselves.
+    fn is_trusted_principal(&self, expr: &SymbolicExpression) -> bool {
+        if let Some(name) = expr.match_atom() {
+            return self.constants.contains(name);
+        }
+        if expr.match_literal_value().is_some() || expr.match_atom_value().is_some() {
+            return true;
+        }
+        let list = match expr.match_list() {
+            Some(list) => list,
+            None => return false,
+        };
+        let is_trusted = |i: usize| {
+            list.get(i)
+                .map_or(false, |inner| self.is_trusted_principal(inner))
+        };
+        match list
+            .get(0)
+            .and_then(|name| name.match_atom())
+            .map(|name| name.as_str())
+        {
+            Some("var-get") | Some("map-get?") | Some("nft-get-owner?") => true,
+            Some("unwrap!") | Some("unwrap-panic") | Some("some") => is_trusted(1),
+            Some("get") => is_trusted(2),
+            Some("default-to") => is_trusted(1) && is_trusted(2),
+            _ => false,
+        }
+    }
+
+    // Returns the check on the caller asserted by the top-level statements
+    // of a function body, which applies to any code following a call to it.
+    fn asserted_check(&self, body: &SymbolicExpression) -> Option<CallerCheck> {
+        fn name(expr: &SymbolicExpression) -> Option<&str> {
+            expr.match_atom().map(|name| name.as_str())
+        }
+        let statements = match body.match_list() {
+            Some([function, statements @ ..]) if name(function) == Some("begin") => statements,
+            Some([function, _, statements @ ..]) if name(function) == Some("let") => statements,
+            _ => std::slice::from_ref(body),
+        };
+        statements.iter().find_map(|statement| {
+            let list = statement.match_list()?;
+            let function = list.get(0)?.match_atom()?;
+            match function.as_str() {
+                "asserts!" => self.caller_check(list.get(1)?),
+                _ => self.user_funcs.get(function)?.asserts,
+            }
+        })
+    }
+
+    // A principal expression refers to the caller if it is `tx-sender` or
+    // `contract-caller`, unless it is inside `as-contract`, where these refer
+    // to the contract itself.
+    fn is_caller(&self, expr: &SymbolicExpression) -> bool {
+        self.as_contract_depth == 0 && match_caller(expr).is_some()
+    }
+
+    fn record_operation(
+        &mut self,
+        expr: &SymbolicExpression,
+        operation: &str,
+        target: Option<&ClarityName>,
+        caller_scoped: bool,
+    ) {
+        self.operations.push(Operation {
+            operation: operation.to_string(),
+            target: target.map(|name| name.to_string()),
+            location: Location::from(&expr.span),
+            guard: self.current_guard(),
+            caller_scoped,
+            span: expr.span.clone(),
+        });
+    }
+
+    fn record_map_write(
+        &mut self,
+        expr: &SymbolicExpression,
+        operation: &str,
+        name: &ClarityName,
+        key: &HashMap<Option<&'a ClarityName>, &'a SymbolicExpression>,
+    ) {
+        let caller_scoped = key.values().any(|value| self.is_caller(value));
+        self.record_operation(expr, operation, Some(name), caller_scoped);
+    }
+
+    fn traverse_function(&mut self, body: &'a SymbolicExpression) -> bool {
+        self.enter_function();
+        self.traverse_expr(body)
+    }
+}
+
+fn match_caller(expr: &SymbolicExpression) -> Option<CallerCheck> {
+    match expr.match_atom().map(|name| name.as_str()) {
+        Some("tx-sender") => Some(CallerCheck::TxSender),
+        Some("contract-caller") => Some(CallerCheck::ContractCaller),
+        _ => None,
+    }
+}
+
+fn generate_diagnostics(function: &FunctionAccess) -> Vec<(WarningKind, Vec<Diagnostic>)> {
+    let mut diagnostics = Vec::new();
+    let unguarded: Vec<&Operation> = function
+        .operations
+        .iter()
+        .filter(|op| op.is_unguarded())
+        .collect();
+    if !unguarded.is_empty() {
+        let mut group = vec![Diagnostic {
+            level: Level::Warning,
+            message: format!(
+                "public function '{}' writes state or moves assets without checking tx-sender or contract-caller",
+                function.function
+            ),
+            spans: vec![function.span.clone()],
+            suggestion: None,
+        }];
+        for op in unguarded {
+            group.push(Diagnostic {
+                level: Level::Note,
+                message: format!("unguarded {} here", op.operation),
+                spans: vec![op.span.clone()],
+                suggestion: None,
+            });
+        }
+        diagnostics.push((WarningKind::MissingCallerCheck, group));
+    }
+
+    // A check on `tx-sender` is satisfied when a user calls a malicious
+    // contract, which then calls this function on their behalf.
+    let mut phishable: Vec<&Span> = Vec::new();
+    for op in function.operations.iter().filter(|op| !op.caller_scoped) {
+        if let Some(guard) = &op.guard {
+            if guard.check == CallerCheck::TxSender && !phishable.contains(&&guard.span) {
+                phishable.push(&guard.span);
+            }
+        }
+    }
+    for span in phishable {
+        let diagnostic = Diagnostic {
+            level: Level::Warning,
+            message: "authorization based on tx-sender can be phished through an intermediate contract, consider checking contract-caller".to_string(),
+            spans: vec![span.clone()],
+            suggestion: None,
+        };
+        diagnostics.push((WarningKind::PhishableCallerCheck, vec![diagnostic]));
+    }
+    diagnostics
+}
+
+impl<'a> ASTVisitor<'a> for AccessChecker<'a> {
+    fn traverse_define_private(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        parameters: Option<Vec<TypedVar<'a>>>,
+        body: &'a SymbolicExpression,
+    ) -> bool {
+        let rv = self.traverse_function(body);
+        let summary = FunctionSummary {
+            references: self.caller_check(body),
+            asserts: self.asserted_check(body),
+            operations: std::mem::take(&mut self.operations),
+        };
+        self.user_funcs.insert(name, summary);
+        rv
+    }
+
+    fn traverse_define_read_only(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        parameters: Option<Vec<TypedVar<'a>>>,
+        body: &'a SymbolicExpression,
+    ) -> bool {
+        let rv = self.traverse_function(body);
+        let summary = FunctionSummary {
+            references: self.caller_check(body),
+            asserts: self.asserted_check(body),
+            operations: vec![],
+        };
+        self.user_funcs.insert(name, summary);
+        rv
+    }
+
+    fn traverse_define_public(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        parameters: Option<Vec<TypedVar<'a>>>,
+        body: &'a SymbolicExpression,
+    ) -> bool {
+        let rv = self.traverse_function(body);
+        let operations = std::mem::take(&mut self.operations);
+        self.report.push(FunctionAccess::new(
+            name,
+            &definition_name_span(expr),
+            operations,
+        ));
+        rv
+    }
+
+    fn traverse_if(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        cond: &'a SymbolicExpression,
+        then_expr: &'a SymbolicExpression,
+        else_expr: &'a SymbolicExpression,
+    ) -> bool {
+        if !self.traverse_expr(cond) {
+            return false;
+        }
+        // The check made by the condition guards the `then` branch, or the
+        // `else` branch when negated with `not`. Guards introduced in the
+        // branches only apply within that branch.
+        let (check, negated) = match cond.match_list() {
+            Some([function, inner])
+                if function.match_atom().map(|name| name.as_str()) == Some("not") =>
+            {
+                (self.caller_check(inner), true)
+            }
+            _ => (self.caller_check(cond), false),
+        };
+        let guards_len = self.guards.len();
+        if let (Some(check), false) = (check, negated) {
+            self.push_guard(check, &cond.span);
+        }
+        let rv = self.traverse_expr(then_expr) && {
+            self.guards.truncate(guards_len);
+            if let (Some(check), true) = (check, negated) {
+                self.push_guard(check, &cond.span);
+            }
+            self.traverse_expr(else_expr)
+        };
+        self.guards.truncate(guards_len);
+        rv && self.visit_if(expr, cond, then_expr, else_expr)
+    }
+
+    // Guards asserted in a `let` or a `match` branch only apply within it.
+    fn traverse_let(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        bindings: &HashMap<&'a ClarityName, &'a SymbolicExpression>,
+        body: &'a [SymbolicExpression],
+    ) -> bool {
+        let guards_len = self.guards.len();
+        let rv = bindings.values().all(|value| self.traverse_expr(value))
+            && body.iter().all(|item| self.traverse_expr(item));
+        self.guards.truncate(guards_len);
+        rv && self.visit_let(expr, bindings, body)
+    }
+
+    fn traverse_match_option(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        input: &'a SymbolicExpression,
+        some_name: &'a ClarityName,
+        some_branch: &'a SymbolicExpression,
+        none_branch: &'a SymbolicExpression,
+    ) -> bool {
+        if !self.traverse_expr(input) {
+            return false;
+        }
+        let guards_len = self.guards.len();
+        let rv = self.traverse_expr(some_branch) && {
+            self.guards.truncate(guards_len);
+            self.traverse_expr(none_branch)
+        };
+        self.guards.truncate(guards_len);
+        rv && self.visit_match_option(expr, input, some_name, some_branch, none_branch)
+    }
+
+    fn traverse_match_response(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        input: &'a SymbolicExpression,
+        ok_name: &'a ClarityName,
+        ok_branch: &'a SymbolicExpression,
+        err_name: &'a ClarityName,
+        err_branch: &'a SymbolicExpression,
+    ) -> bool {
+        if !self.traverse_expr(input) {
+            return false;
+        }
+        let guards_len = self.guards.len();
+        let rv = self.traverse_expr(ok_branch) && {
+            self.guards.truncate(guards_len);
+            self.traverse_expr(err_branch)
+        };
+        self.guards.truncate(guards_len);
+        rv && self.visit_match_response(expr, input, ok_name, ok_branch, err_name, err_branch)
+    }
+
+    fn traverse_as_contract(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        inner: &'a SymbolicExpression,
+    ) -> bool {
+        self.as_contract_depth += 1;
+        let rv = self.traverse_expr(inner);
+        self.as_contract_depth -= 1;
+        rv && self.visit_as_contract(expr, inner)
+    }
+
+    fn visit_asserts(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        cond: &'a SymbolicExpression,
+        thrown: &'a SymbolicExpression,
+    ) -> bool {
+        if let Some(check) = self.caller_check(cond) {
+            self.push_guard(check, &cond.span);
+        }
+        true
+    }
+
+    fn visit_call_user_defined(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        args: &'a [SymbolicExpression],
+    ) -> bool {
+        let (asserts, operations) = match self.user_funcs.get(name) {
+            Some(summary) => (summary.asserts, summary.operations.clone()),
+            None => return true,
+        };
+        // Operations in the callee that are not guarded there are guarded by
+        // the checks made by the caller before the call.
+        let guard = self.current_guard();
+        for mut op in operations {
+            if op.guard.is_none() {
+                op.guard = guard.clone();
+            }
+            self.operations.push(op);
+        }
+        if let Some(check) = asserts {
+            self.push_guard(check, &expr.span);
+        }
+        true
+    }
+
+    fn visit_var_set(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        value: &'a SymbolicExpression,
+    ) -> bool {
+        self.record_operation(expr, "var-set", Some(name), false);
+        true
+    }
+
+    fn visit_map_set(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        key: &HashMap<Option<&'a ClarityName>, &'a SymbolicExpression>,
+        value: &HashMap<Option<&'a ClarityName>, &'a SymbolicExpression>,
+    ) -> bool {
+        self.record_map_write(expr, "map-set", name, key);
+        true
+    }
+
+    fn visit_map_insert(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        key: &HashMap<Option<&'a ClarityName>, &'a SymbolicExpression>,
+        value: &HashMap<Option<&'a ClarityName>, &'a SymbolicExpression>,
+    ) -> bool {
+        self.record_map_write(expr, "map-insert", name, key);
+        true
+    }
+
+    fn visit_map_delete(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        key: &HashMap<Option<&'a ClarityName>, &'a SymbolicExpression>,
+    ) -> bool {
+        self.record_map_write(expr, "map-delete", name, key);
+        true
+    }
+
+    fn visit_stx_burn(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        amount: &'a SymbolicExpression,
+        sender: &'a SymbolicExpression,
+    ) -> bool {
+        let caller_scoped = self.is_caller(sender);
+        self.record_operation(expr, "stx-burn?", None, caller_scoped);
+        true
+    }
+
+    fn visit_stx_transfer(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        amount: &'a SymbolicExpression,
+        sender: &'a SymbolicExpression,
+        recipient: &'a SymbolicExpression,
+        memo: Option<&'a SymbolicExpression>,
+    ) -> bool {
+        let caller_scoped = self.is_caller(sender);
+        self.record_operation(expr, "stx-transfer?", None, caller_scoped);
+        true
+    }
+
+    fn visit_ft_burn(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        token: &'a ClarityName,
+        amount: &'a SymbolicExpression,
+        sender: &'a SymbolicExpression,
+    ) -> bool {
+        let caller_scoped = self.is_caller(sender);
+        self.record_operation(expr, "ft-burn?", Some(token), caller_scoped);
+        true
+    }
+
+    fn visit_ft_transfer(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        token: &'a ClarityName,
+        amount: &'a SymbolicExpression,
+        sender: &'a SymbolicExpression,
+        recipient: &'a SymbolicExpression,
+    ) -> bool {
+        let caller_scoped = self.is_caller(sender);
+        self.record_operation(expr, "ft-transfer?", Some(token), caller_scoped);
+        true
+    }
+
+    fn visit_ft_mint(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        token: &'a ClarityName,
+        amount: &'a SymbolicExpression,
+        recipient: &'a SymbolicExpression,
+    ) -> bool {
+        self.record_operation(expr, "ft-mint?", Some(token), false);
+        true
+    }
+
+    fn visit_nft_burn(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        token: &'a ClarityName,
+        identifier: &'a SymbolicExpression,
+        sender: &'a SymbolicExpression,
+    ) -> bool {
+        let caller_scoped = self.is_caller(sender);
+        self.record_operation(expr, "nft-burn?", Some(token), caller_scoped);
+        true
+    }
+
+    fn visit_nft_transfer(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        token: &'a ClarityName,
+        identifier: &'a SymbolicExpression,
+        sender: &'a SymbolicExpression,
+        recipient: &'a SymbolicExpression,
+    ) -> bool {
+        let caller_scoped = self.is_caller(sender);
+        self.record_operation(expr, "nft-transfer?", Some(token), caller_scoped);
+        true
+    }
+
+    fn visit_nft_mint(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        token: &'a ClarityName,
+        identifier: &'a SymbolicExpression,
+        recipient: &'a SymbolicExpression,
+    ) -> bool {
+        self.record_operation(expr, "nft-mint?", Some(token), false);
+        true
+    }
+}
+
+/// Build the access control report of the public functions of a contract.
+pub fn access_report(contract_analysis: &ContractAnalysis) -> Vec<FunctionAccess> {
+    AccessChecker::new().run(contract_analysis)
+}
+
+impl AnalysisPass for AccessChecker<'_> {
+    fn run_pass(
+        contract_analysis: &mut ContractAnalysis,
+        analysis_db: &mut AnalysisDatabase,
+        annotations: &Vec<Annotation>,
+        settings: &Settings,
+    ) -> PassResult {
+        let diagnostics = access_report(contract_analysis)
+            .iter()
+            .flat_map(generate_diagnostics)
+            .flat_map(|(kind, group)| {
+                settings.apply_lint_level(contract_analysis, annotations, kind, group)
+            })
+            .collect();
+        Ok((diagnostics, vec![]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::Pass;
+    use crate::repl::session::Session;
+    use crate::repl::SessionSettings;
+    use clarity::vm::EvaluationResult;
+
+    #[test]
+    fn unguarded_var_set() {
+        let mut settings = SessionSettings::default();
+        settings.repl_settings.analysis.passes = vec![Pass::AccessChecker];
+        let mut session = Session::new(settings);
+        let snippet = "
+(define-data-var owner principal tx-sender)
+(define-public (set-owner (new-owner principal))
+    (ok (var-set owner new-owner))
+)
+"
+        .to_string();
+        match session.formatted_interpretation(
+            snippet,
+            Some("checker".to_string()),
+            false,
+            None,
+            None,
+        ) {
+            Ok((output, _)) => {
+                assert_eq!(output.len(), 6);
+                assert_eq!(
+                    output[0],
+                    format!(
+                        "checker:3:17: {}: public function 'set-owner' writes state or moves assets without checking tx-sender or contract-caller",
+                        yellow!("warning")
+                    )
+                );
+                assert_eq!(
+                    output[1],
+                    "(define-public (set-owner (new-owner principal))"
+                );
+                assert_eq!(output[2], "                ^~~~~~~~");
+                assert_eq!(
+                    output[3],
+                    format!("checker:4:9: {}: unguarded var-set here", blue!("note"))
+                );
+                assert_eq!(output[4], "    (ok (var-set owner new-owner))");
+                assert_eq!(output[5], "        ^~~~~~~~~~~~~~~~~~~~~~~~~");
+            }
+            _ => panic!("Expected successful interpretation"),
+        };
+    }
+
+    #[test]
+    fn contract_caller_guard() {
+        let mut settings = SessionSettings::default();
+        settings.repl_settings.analysis.passes = vec![Pass::AccessChecker];
+        let mut session = Session::new(settings);
+        let snippet = "
+(define-data-var owner principal tx-sender)
+(define-private (is-owner)
+    (is-eq contract-caller (var-get owner))
+)
+(define-public (set-owner (new-owner principal))
+    (begin
+        (asserts! (is-owner) (err u401))
+        (ok (var-set owner new-owner))
+    )
+)
+"
+        .to_string();
+        match session.formatted_interpretation(
+            snippet,
+            Some("checker".to_string()),
+            false,
+            None,
+            None,
+        ) {
+            Ok((_, result)) => {
+                assert_eq!(result.diagnostics.len(), 0);
+            }
+            _ => panic!("Expected successful interpretation"),
+        };
+    }
+
+    #[test]
+    fn tx_sender_guard() {
+        let mut settings = SessionSettings::default();
+        settings.repl_settings.analysis.passes = vec![Pass::AccessChecker];
+        let mut session = Session::new(settings);
+        let snippet = "
+(define-fungible-token token)
+(define-constant owner tx-sender)
+(define-public (mint (amount uint) (recipient principal))
+    (begin
+        (asserts! (is-eq tx-sender owner) (err u401))
+        (ft-mint? token amount recipient)
+    )
+)
+"
+        .to_string();
+        match session.formatted_interpretation(
+            snippet,
+            Some("checker".to_string()),
+            false,
+            None,
+            None,
+        ) {
+            Ok((output, _)) => {
+                assert_eq!(output.len(), 3);
+                assert_eq!(
+                    output[0],
+                    format!(
+                        "checker:6:19: {}: authorization based on tx-sender can be phished through an intermediate contract, consider checking contract-caller",
+                        yellow!("warning")
+                    )
+                );
+                assert_eq!(output[2], "                  ^~~~~~~~~~~~~~~~~~~~~~~");
+            }
+            _ => panic!("Expected successful interpretation"),
+        };
+    }
+
+    #[test]
+    fn caller_scoped_operations() {
+        let mut settings = SessionSettings::default();
+        settings.repl_settings.analysis.passes = vec![Pass::AccessChecker];
+        let mut session = Session::new(settings);
+        let snippet = "
+(define-map deposits principal uint)
+(define-public (deposit (amount uint))
+    (begin
+        (try! (stx-transfer? amount tx-sender (as-contract tx-sender)))
+        (ok (map-set deposits tx-sender amount))
+    )
+)
+"
+        .to_string();
+        match session.formatted_interpretation(
+            snippet,
+            Some("checker".to_string()),
+            false,
+            None,
+            None,
+        ) {
+            Ok((_, result)) => {
+                assert_eq!(result.diagnostics.len(), 0);
+            }
+            _ => panic!("Expected successful interpretation"),
+        };
+    }
+
+    #[test]
+    fn report() {
+        let mut settings = SessionSettings::default();
+        settings.repl_settings.analysis.passes = vec![Pass::AccessChecker];
+        let mut session = Session::new(settings);
+        let snippet = "
+(define-map deposits principal uint)
+(define-public (deposit (amount uint))
+    (begin
+        (try! (stx-transfer? amount tx-sender (as-contract tx-sender)))
+        (ok (map-set deposits tx-sender amount))
+    )
+)
+(define-public (withdraw (amount uint) (recipient principal))
+    (as-contract (stx-transfer? amount tx-sender recipient))
+)
+(define-read-only (get-deposit (who principal))
+    (map-get? deposits who)
+)
+"
+        .to_string();
+        let (_, result) = session
+            .formatted_interpretation(snippet, Some("checker".to_string()), false, None, None)
+            .expect("Expected successful interpretation");
+        let contract_analysis = match result.result {
+            EvaluationResult::Contract(contract_result) => contract_result.contract.analysis,
+            _ => panic!("Expected contract"),
+        };
+        let report = access_report(&contract_analysis);
+        assert_eq!(report.len(), 2);
+        assert_eq!(report[0].function, "deposit");
+        assert_eq!(report[0].access, AccessLevel::CallerScoped);
+        assert_eq!(report[0].operations.len(), 2);
+        assert_eq!(report[1].function, "withdraw");
+        assert_eq!(report[1].access, AccessLevel::Unrestricted);
+        assert_eq!(report[1].operations[0].operation, "stx-transfer?");
+    }
+
+    #[test]
+    fn guard_only_applies_to_its_branch() {
+        let mut settings = SessionSettings::default();
+        settings.repl_settings.analysis.passes = vec![Pass::AccessChecker];
+        let mut session = Session::new(settings);
+        let snippet = "
+(define-data-var owner principal tx-sender)
+(define-data-var count uint u0)
+(define-public (incr)
+    (if (is-eq contract-caller (var-get owner))
+        (ok (var-set count (+ (var-get count) u1)))
+        (ok (var-set count u0))
+    )
+)
+(define-public (reset)
+    (if (not (is-eq contract-caller (var-get owner)))
+        (err u401)
+        (ok (var-set count u0))
+    )
+)
+"
+        .to_string();
+        let (_, result) = session
+            .formatted_interpretation(snippet, Some("checker".to_string()), false, None, None)
+            .expect("Expected successful interpretation");
+        let contract_analysis = match result.result {
+            EvaluationResult::Contract(contract_result) => contract_result.contract.analysis,
+            _ => panic!("Expected contract"),
+        };
+        let report = access_report(&contract_analysis);
+        assert_eq!(report[0].function, "incr");
+        assert_eq!(report[0].access, AccessLevel::Unrestricted);
+        assert!(report[0].operations[0].guard.is_some());
+        assert!(report[0].operations[1].guard.is_none());
+        assert_eq!(report[1].function, "reset");
+        assert_eq!(report[1].access, AccessLevel::ContractCaller);
+    }
+
+    #[test]
+    fn caller_reference_is_not_a_guard() {
+        let mut settings = SessionSettings::default();
+        settings.repl_settings.analysis.passes = vec![Pass::AccessChecker];
+        let mut session = Session::new(settings);
+        let snippet = "
+(define-data-var total uint u0)
+(define-public (update)
+    (begin
+        (asserts! (> (stx-get-balance tx-sender) u0) (err u1))
+        (ok (var-set total (stx-get-balance tx-sender)))
+    )
+)
+"
+        .to_string();
+        let (_, result) = session
+            .formatted_interpretation(snippet, Some("checker".to_string()), false, None, None)
+            .expect("Expected successful interpretation");
+        assert_eq!(result.diagnostics.len(), 2);
+        assert_eq!(
+            result.diagnostics[0].message,
+            "public function 'update' writes state or moves assets without checking tx-sender or contract-caller"
+        );
+    }
+
+    #[test]
+    fn untrusted_comparisons_are_not_guards() {
+        let mut settings = SessionSettings::default();
+        settings.repl_settings.analysis.passes = vec![Pass::AccessChecker];
+        let mut session = Session::new(settings);
+        let snippet = "
+(define-data-var owner principal tx-sender)
+(define-public (set-owner (current principal) (new-owner principal))
+    (begin
+        (asserts! (is-eq contract-caller current) (err u401))
+        (ok (var-set owner new-owner))
+    )
+)
+(define-public (reset)
+    (as-contract (begin
+        (asserts! (is-eq contract-caller (var-get owner)) (err u401))
+        (ok (var-set owner tx-sender))
+    ))
+)
+"
+        .to_string();
+        let (_, result) = session
+            .formatted_interpretation(snippet, Some("checker".to_string()), false, None, None)
+            .expect("Expected successful interpretation");
+        let contract_analysis = match result.result {
+            EvaluationResult::Contract(contract_result) => contract_result.contract.analysis,
+            _ => panic!("Expected contract"),
+        };
+        let report = access_report(&contract_analysis);
+        assert_eq!(report[0].function, "set-owner");
+        assert_eq!(report[0].access, AccessLevel::Unrestricted);
+        assert_eq!(report[1].function, "reset");
+        assert_eq!(report[1].access, AccessLevel::Unrestricted);
+    }
+
+    #[test]
+    fn guard_only_applies_to_its_scope() {
+        let mut settings = SessionSettings::default();
+        settings.repl_settings.analysis.passes = vec![Pass::AccessChecker];
+        let mut session = Session::new(settings);
+        let snippet = "
+(define-data-var owner principal tx-sender)
+(define-data-var count uint u0)
+(define-public (incr (step (optional uint)))
+    (begin
+        (match step
+            value (asserts! (is-eq contract-caller (var-get owner)) (err u401))
+            true
+        )
+        (ok (var-set count (+ (var-get count) u1)))
+    )
+)
+(define-public (reset)
+    (begin
+        (let ((current (var-get owner)))
+            (asserts! (is-eq contract-caller (var-get owner)) (err u401))
+        )
+        (ok (var-set count u0))
+    )
+)
+"
+        .to_string();
+        let (_, result) = session
+            .formatted_interpretation(snippet, Some("checker".to_string()), false, None, None)
+            .expect("Expected successful interpretation");
+        let contract_analysis = match result.result {
+            EvaluationResult::Contract(contract_result) => contract_result.contract.analysis,
+            _ => panic!("Expected contract"),
+        };
+        let report = access_report(&contract_analysis);
+        assert_eq!(report[0].function, "incr");
+        assert_eq!(report[0].access, AccessLevel::Unrestricted);
+        assert_eq!(report[1].function, "reset");
+        assert_eq!(report[1].access, AccessLevel::Unrestricted);
+    }
+}
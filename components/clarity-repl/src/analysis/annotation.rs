@@ -50,10 +50,44 @@ impl std::str::FromStr for AnnotationKind {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum WarningKind {
     UncheckedData,
     UncheckedParams,
+    UncheckedAsContract,
+    StateAfterCall,
+    Unused,
+    MissingCallerCheck,
+    PhishableCallerCheck,
+    UncheckedSubtraction,
+    UncheckedDivision,
+    UncheckedPow,
+    ReadOnlyContractCall,
+    DynamicAtBlock,
+    FixedBlockTime,
+    IncorrectArgumentCount,
+}
+
+impl std::fmt::Display for WarningKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            WarningKind::UncheckedData => "unchecked_data",
+            WarningKind::UncheckedParams => "unchecked_params",
+            WarningKind::UncheckedAsContract => "unchecked_as_contract",
+            WarningKind::StateAfterCall => "state_after_call",
+            WarningKind::Unused => "unused",
+            WarningKind::MissingCallerCheck => "missing_caller_check",
+            WarningKind::PhishableCallerCheck => "phishable_caller_check",
+            WarningKind::UncheckedSubtraction => "unchecked_subtraction",
+            WarningKind::UncheckedDivision => "unchecked_division",
+            WarningKind::UncheckedPow => "unchecked_pow",
+            WarningKind::ReadOnlyContractCall => "read_only_contract_call",
+            WarningKind::DynamicAtBlock => "dynamic_at_block",
+            WarningKind::FixedBlockTime => "fixed_block_time",
+            WarningKind::IncorrectArgumentCount => "incorrect_argument_count",
+        };
+        write!(f, "{}", name)
+    }
 }
 
 impl std::str::FromStr for WarningKind {
@@ -63,6 +97,18 @@ impl std::str::FromStr for WarningKind {
         match s {
             "unchecked_data" => Ok(WarningKind::UncheckedData),
             "unchecked_params" => Ok(WarningKind::UncheckedParams),
+            "unchecked_as_contract" => Ok(WarningKind::UncheckedAsContract),
+            "state_after_call" => Ok(WarningKind::StateAfterCall),
+            "unused" => Ok(WarningKind::Unused),
+            "missing_caller_check" => Ok(WarningKind::MissingCallerCheck),
+            "phishable_caller_check" => Ok(WarningKind::PhishableCallerCheck),
+            "unchecked_subtraction" => Ok(WarningKind::UncheckedSubtraction),
+            "unchecked_division" => Ok(WarningKind::UncheckedDivision),
+            "unchecked_pow" => Ok(WarningKind::UncheckedPow),
+            "read_only_contract_call" => Ok(WarningKind::ReadOnlyContractCall),
+            "dynamic_at_block" => Ok(WarningKind::DynamicAtBlock),
+            "fixed_block_time" => Ok(WarningKind::FixedBlockTime),
+            "incorrect_argument_count" => Ok(WarningKind::IncorrectArgumentCount),
             _ => Err(format!("'{}' is not a valid warning identifier", s)),
         }
     }
@@ -74,6 +120,18 @@ pub struct Annotation {
     pub span: Span,
 }
 
+/// Check if a warning of `kind` at `span` is allowed by an annotation on the
+/// line before it.
+pub fn is_allowed(annotations: &[Annotation], kind: WarningKind, span: &Span) -> bool {
+    annotations.iter().any(|annotation| {
+        annotation.span.start_line + 1 == span.start_line
+            && match annotation.kind {
+                AnnotationKind::Allow(allowed) => allowed == kind,
+                _ => false,
+            }
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -86,6 +144,30 @@ mod tests {
         };
     }
 
+    #[test]
+    fn parse_unused() {
+        match "unused".parse::<WarningKind>() {
+            Ok(WarningKind::Unused) => (),
+            _ => panic!("failed to parse warning kind correectly"),
+        };
+    }
+
+    #[test]
+    fn warning_kind_round_trip() {
+        for name in &[
+            "unchecked_data",
+            "unchecked_as_contract",
+            "state_after_call",
+            "missing_caller_check",
+            "unchecked_pow",
+        ] {
+            match name.parse::<WarningKind>() {
+                Ok(kind) => assert_eq!(&kind.to_string(), name),
+                _ => panic!("failed to parse warning kind correctly"),
+            };
+        }
+    }
+
     #[test]
     fn parse_warning_error() {
         match "invalid_string".parse::<WarningKind>() {
@@ -154,4 +236,25 @@ mod tests {
             _ => panic!("failed to return error for 'filter' with no parameters"),
         };
     }
+
+    #[test]
+    fn allowed_on_line_before() {
+        let span = |line| Span {
+            start_line: line,
+            start_column: 5,
+            end_line: line,
+            end_column: 20,
+        };
+        let annotations = vec![Annotation {
+            kind: AnnotationKind::Allow(WarningKind::Unused),
+            span: span(2),
+        }];
+        assert!(is_allowed(&annotations, WarningKind::Unused, &span(3)));
+        assert!(!is_allowed(&annotations, WarningKind::Unused, &span(4)));
+        assert!(!is_allowed(
+            &annotations,
+            WarningKind::UncheckedData,
+            &span(3)
+        ));
+    }
 }
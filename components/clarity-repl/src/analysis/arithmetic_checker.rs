@@ -0,0 +1,771 @@
+use crate::analysis::annotation::{Annotation, WarningKind};
+use crate::analysis::ast_visitor::{traverse, ASTVisitor, TypedVar};
+use crate::analysis::{AnalysisPass, AnalysisResult, PassResult, Settings};
+use clarity::vm::analysis::analysis_db::AnalysisDatabase;
+use clarity::vm::analysis::types::{ContractAnalysis, TypeMap};
+use clarity::vm::diagnostic::{Diagnostic, Level};
+use clarity::vm::functions::NativeFunctions;
+use clarity::vm::representations::SymbolicExpressionType::*;
+use clarity::vm::types::{TypeSignature, Value};
+use clarity::vm::{ClarityName, SymbolicExpression};
+use std::collections::HashMap;
+
+// A fact about the values of expressions, established by a guard.
+#[derive(Clone, Copy)]
+enum Fact<'a> {
+    // lhs > rhs
+    Greater(&'a SymbolicExpression, &'a SymbolicExpression),
+    // lhs >= rhs
+    GreaterOrEqual(&'a SymbolicExpression, &'a SymbolicExpression),
+    // lhs != rhs
+    NotEqual(&'a SymbolicExpression, &'a SymbolicExpression),
+    // A fact about data written since the guard, kept in place so that the
+    // facts of enclosing branches keep their position
+    Invalidated,
+}
+
+pub struct ArithmeticChecker<'a> {
+    type_map: Option<&'a TypeMap>,
+    // Facts established by the guards dominating the current expression
+    facts: Vec<Fact<'a>>,
+    diagnostics: Vec<(WarningKind, Diagnostic)>,
+}
+
+impl<'a> ArithmeticChecker<'a> {
+    fn new(type_map: Option<&'a TypeMap>) -> ArithmeticChecker<'a> {
+        Self {
+            type_map,
+            facts: Vec::new(),
+            diagnostics: Vec::new(),
+        }
+    }
+
+    fn run(
+        mut self,
+        contract_analysis: &'a ContractAnalysis,
+        annotations: &Vec<Annotation>,
+        settings: &Settings,
+    ) -> AnalysisResult {
+        traverse(&mut self, &contract_analysis.expressions);
+        self.diagnostics
+            .sort_by(|(_, a), (_, b)| a.spans[0].cmp(&b.spans[0]));
+        Ok(self
+            .diagnostics
+            .into_iter()
+            .flat_map(|(kind, diagnostic)| {
+                settings.apply_lint_level(contract_analysis, annotations, kind, vec![diagnostic])
+            })
+            .collect())
+    }
+
+    // Add the facts implied by `cond` evaluating to `holds`.
+    fn add_facts(&mut self, cond: &'a SymbolicExpression, holds: bool) {
+        let list = match cond.match_list() {
+            Some(list) => list,
+            None => return,
+        };
+        let (function, args) = match list.split_first() {
+            Some((function, args)) => match function.match_atom() {
+                Some(function) => (function.as_str(), args),
+                None => return,
+            },
+            None => return,
+        };
+        match (function, args, holds) {
+            (">", [a, b], true) | ("<", [b, a], true) => {
+                self.facts.push(Fact::Greater(a, b));
+            }
+            (">=", [a, b], true) | ("<=", [b, a], true) => {
+                self.facts.push(Fact::GreaterOrEqual(a, b));
+            }
+            (">", [b, a], false) | ("<", [a, b], false) => {
+                self.facts.push(Fact::GreaterOrEqual(a, b));
+            }
+            (">=", [b, a], false) | ("<=", [a, b], false) => {
+                self.facts.push(Fact::Greater(a, b));
+            }
+            ("is-eq", [a, b], true) => {
+                self.facts.push(Fact::GreaterOrEqual(a, b));
+                self.facts.push(Fact::GreaterOrEqual(b, a));
+            }
+            ("is-eq", [a, b], false) => {
+                self.facts.push(Fact::NotEqual(a, b));
+            }
+            ("not", [inner], _) => self.add_facts(inner, !holds),
+            ("and", conds, true) | ("or", conds, false) => {
+                for cond in conds {
+                    self.add_facts(cond, holds);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    // Check if `a >= n` is known.
+    fn is_at_least(&self, a: &SymbolicExpression, n: i128) -> bool {
+        if let Some(value) = literal(a) {
+            return value >= n;
+        }
+        self.facts.iter().any(|fact| match fact {
+            Fact::Greater(lhs, rhs) => {
+                same_expr(lhs, a) && literal(rhs).map_or(false, |m| m.saturating_add(1) >= n)
+            }
+            Fact::GreaterOrEqual(lhs, rhs) => {
+                same_expr(lhs, a) && literal(rhs).map_or(false, |m| m >= n)
+            }
+            Fact::NotEqual(..) | Fact::Invalidated => false,
+        })
+    }
+
+    // Check if `a >= b` is known.
+    fn is_greater_or_equal(&self, a: &SymbolicExpression, b: &SymbolicExpression) -> bool {
+        if let Some(n) = literal(b) {
+            return self.is_at_least(a, n);
+        }
+        self.facts.iter().any(|fact| match fact {
+            Fact::Greater(lhs, rhs) | Fact::GreaterOrEqual(lhs, rhs) => {
+                same_expr(lhs, a) && same_expr(rhs, b)
+            }
+            Fact::NotEqual(..) | Fact::Invalidated => false,
+        })
+    }
+
+    // Check if `a >= b1 + b2 + ...` is known, either from the literal sum of
+    // the terms or from a guard comparing `a` with `(+ b1 b2 ...)`.
+    fn is_greater_or_equal_sum(
+        &self,
+        a: &SymbolicExpression,
+        terms: &[SymbolicExpression],
+    ) -> bool {
+        if let [b] = terms {
+            return self.is_greater_or_equal(a, b);
+        }
+        let sum = terms
+            .iter()
+            .try_fold(0i128, |sum, term| sum.checked_add(literal(term)?));
+        if let Some(n) = sum {
+            return self.is_at_least(a, n);
+        }
+        self.facts.iter().any(|fact| match fact {
+            Fact::Greater(lhs, rhs) | Fact::GreaterOrEqual(lhs, rhs) => {
+                same_expr(lhs, a)
+                    && match rhs.match_list() {
+                        Some([function, operands @ ..]) => {
+                            function.match_atom().map(|name| name.as_str()) == Some("+")
+                                && operands.len() == terms.len()
+                                && operands.iter().zip(terms).all(|(a, b)| same_expr(a, b))
+                        }
+                        _ => false,
+                    }
+            }
+            Fact::NotEqual(..) | Fact::Invalidated => false,
+        })
+    }
+
+    // Check if `a != 0` is known.
+    fn is_non_zero(&self, a: &SymbolicExpression, unsigned: bool) -> bool {
+        if let Some(value) = literal(a) {
+            return value != 0;
+        }
+        if self.is_at_least(a, 1) {
+            return true;
+        }
+        self.facts.iter().any(|fact| match fact {
+            Fact::NotEqual(lhs, rhs) => {
+                (same_expr(lhs, a) && literal(rhs) == Some(0))
+                    || (same_expr(rhs, a) && literal(lhs) == Some(0))
+            }
+            // A uint greater than anything is at least 1.
+            Fact::Greater(lhs, _) => unsigned && same_expr(lhs, a),
+            Fact::GreaterOrEqual(..) | Fact::Invalidated => false,
+        })
+    }
+
+    // Check if an upper bound of `a` is known.
+    fn is_bounded(&self, a: &SymbolicExpression) -> bool {
+        if literal(a).is_some() {
+            return true;
+        }
+        self.facts.iter().any(|fact| match fact {
+            Fact::Greater(_, rhs) | Fact::GreaterOrEqual(_, rhs) => same_expr(rhs, a),
+            Fact::NotEqual(..) | Fact::Invalidated => false,
+        })
+    }
+
+    fn is_unsigned(&self, expr: &SymbolicExpression, operands: &[SymbolicExpression]) -> bool {
+        match self.type_map.and_then(|type_map| type_map.get_type(expr)) {
+            Some(type_sig) => *type_sig == TypeSignature::UIntType,
+            // Without type information, rely on the literals
+            None => operands.iter().any(|operand| match &operand.expr {
+                LiteralValue(Value::UInt(_)) | AtomValue(Value::UInt(_)) => true,
+                _ => false,
+            }),
+        }
+    }
+
+    fn add_diagnostic(&mut self, kind: WarningKind, expr: &SymbolicExpression, message: &str) {
+        let diagnostic = Diagnostic {
+            level: Level::Warning,
+            message: message.to_string(),
+            spans: vec![expr.span.clone()],
+            suggestion: None,
+        };
+        self.diagnostics.push((kind, diagnostic));
+    }
+
+    // Invalidate the facts about the data read with `(<reader> name ...)`,
+    // e.g. `(var-get name)`, after a write to it.
+    fn invalidate_facts(&mut self, reader: &str, name: &ClarityName) {
+        for fact in self.facts.iter_mut() {
+            let (lhs, rhs) = match *fact {
+                Fact::Greater(lhs, rhs)
+                | Fact::GreaterOrEqual(lhs, rhs)
+                | Fact::NotEqual(lhs, rhs) => (lhs, rhs),
+                Fact::Invalidated => continue,
+            };
+            if reads(lhs, reader, name) || reads(rhs, reader, name) {
+                *fact = Fact::Invalidated;
+            }
+        }
+    }
+
+    fn traverse_function(&mut self, body: &'a SymbolicExpression) -> bool {
+        self.facts.clear();
+        self.traverse_expr(body)
+    }
+}
+
+// Returns the value of an integer literal, if it fits in an `i128`.
+fn literal(expr: &SymbolicExpression) -> Option<i128> {
+    match &expr.expr {
+        LiteralValue(Value::Int(value)) | AtomValue(Value::Int(value)) => Some(*value),
+        LiteralValue(Value::UInt(value)) | AtomValue(Value::UInt(value)) => {
+            if *value <= i128::MAX as u128 {
+                Some(*value as i128)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+// Check if `expr` contains a `(<reader> name ...)` expression.
+fn reads(expr: &SymbolicExpression, reader: &str, name: &ClarityName) -> bool {
+    let list = match expr.match_list() {
+        Some(list) => list,
+        None => return false,
+    };
+    match list {
+        [function, data, ..]
+            if function.match_atom().map(|f| f.as_str()) == Some(reader)
+                && data.match_atom() == Some(name) =>
+        {
+            true
+        }
+        _ => list.iter().any(|item| reads(item, reader, name)),
+    }
+}
+
+// Compare two expressions, ignoring their IDs and spans.
+fn same_expr(a: &SymbolicExpression, b: &SymbolicExpression) -> bool {
+    match (&a.expr, &b.expr) {
+        (List(a), List(b)) => {
+            a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| same_expr(a, b))
+        }
+        (a, b) => a == b,
+    }
+}
+
+impl<'a> ASTVisitor<'a> for ArithmeticChecker<'a> {
+    fn traverse_define_private(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        parameters: Option<Vec<TypedVar<'a>>>,
+        body: &'a SymbolicExpression,
+    ) -> bool {
+        self.traverse_function(body)
+    }
+
+    fn traverse_define_read_only(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        parameters: Option<Vec<TypedVar<'a>>>,
+        body: &'a SymbolicExpression,
+    ) -> bool {
+        self.traverse_function(body)
+    }
+
+    fn traverse_define_public(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        parameters: Option<Vec<TypedVar<'a>>>,
+        body: &'a SymbolicExpression,
+    ) -> bool {
+        self.traverse_function(body)
+    }
+
+    fn traverse_if(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        cond: &'a SymbolicExpression,
+        then_expr: &'a SymbolicExpression,
+        else_expr: &'a SymbolicExpression,
+    ) -> bool {
+        if !self.traverse_expr(cond) {
+            return false;
+        }
+        let facts_len = self.facts.len();
+        self.add_facts(cond, true);
+        let rv = self.traverse_expr(then_expr);
+        self.facts.truncate(facts_len);
+        self.add_facts(cond, false);
+        let rv = rv && self.traverse_expr(else_expr);
+        self.facts.truncate(facts_len);
+        rv && self.visit_if(expr, cond, then_expr, else_expr)
+    }
+
+    fn visit_asserts(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        cond: &'a SymbolicExpression,
+        thrown: &'a SymbolicExpression,
+    ) -> bool {
+        // The rest of the function is only reached if the condition holds.
+        self.add_facts(cond, true);
+        true
+    }
+
+    fn visit_arithmetic(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        func: NativeFunctions,
+        operands: &'a [SymbolicExpression],
+    ) -> bool {
+        match func {
+            NativeFunctions::Subtract if operands.len() > 1 => {
+                if self.is_unsigned(expr, operands)
+                    && !self.is_greater_or_equal_sum(&operands[0], &operands[1..])
+                {
+                    self.add_diagnostic(
+                        WarningKind::UncheckedSubtraction,
+                        expr,
+                        "subtraction may underflow",
+                    );
+                }
+            }
+            NativeFunctions::Divide | NativeFunctions::Modulo => {
+                let unsigned = self.is_unsigned(expr, operands);
+                if operands
+                    .iter()
+                    .skip(1)
+                    .any(|divisor| !self.is_non_zero(divisor, unsigned))
+                {
+                    self.add_diagnostic(
+                        WarningKind::UncheckedDivision,
+                        expr,
+                        "possible division by zero",
+                    );
+                }
+            }
+            NativeFunctions::Power if operands.len() == 2 => {
+                if !self.is_bounded(&operands[1]) {
+                    self.add_diagnostic(
+                        WarningKind::UncheckedPow,
+                        expr,
+                        "exponent is not bounded, pow may overflow",
+                    );
+                }
+            }
+            _ => (),
+        }
+        true
+    }
+
+    fn visit_var_set(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        value: &'a SymbolicExpression,
+    ) -> bool {
+        self.invalidate_facts("var-get", name);
+        true
+    }
+
+    fn visit_map_set(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        key: &HashMap<Option<&'a ClarityName>, &'a SymbolicExpression>,
+        value: &HashMap<Option<&'a ClarityName>, &'a SymbolicExpression>,
+    ) -> bool {
+        self.invalidate_facts("map-get?", name);
+        true
+    }
+
+    fn visit_map_insert(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        key: &HashMap<Option<&'a ClarityName>, &'a SymbolicExpression>,
+        value: &HashMap<Option<&'a ClarityName>, &'a SymbolicExpression>,
+    ) -> bool {
+        self.invalidate_facts("map-get?", name);
+        true
+    }
+
+    fn visit_map_delete(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        key: &HashMap<Option<&'a ClarityName>, &'a SymbolicExpression>,
+    ) -> bool {
+        self.invalidate_facts("map-get?", name);
+        true
+    }
+}
+
+impl AnalysisPass for ArithmeticChecker<'_> {
+    fn run_pass(
+        contract_analysis: &mut ContractAnalysis,
+        analysis_db: &mut AnalysisDatabase,
+        annotations: &Vec<Annotation>,
+        settings: &Settings,
+    ) -> PassResult {
+        let checker = ArithmeticChecker::new(contract_analysis.type_map.as_ref());
+        checker
+            .run(contract_analysis, annotations, settings)
+            .map(|diagnostics| (diagnostics, vec![]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::{LintLevel, LintSettings, Pass};
+    use crate::repl::session::Session;
+    use crate::repl::SessionSettings;
+
+    #[test]
+    fn unchecked_subtraction() {
+        let mut settings = SessionSettings::default();
+        settings.repl_settings.analysis.passes = vec![Pass::ArithmeticChecker];
+        let mut session = Session::new(settings);
+        let snippet = "
+(define-map balances principal uint)
+(define-public (withdraw (amount uint))
+    (let ((balance (default-to u0 (map-get? balances tx-sender))))
+        (ok (map-set balances tx-sender (- balance amount)))
+    )
+)
+"
+        .to_string();
+        match session.formatted_interpretation(
+            snippet,
+            Some("checker".to_string()),
+            false,
+            None,
+            None,
+        ) {
+            Ok((output, _)) => {
+                assert_eq!(output.len(), 3);
+                assert_eq!(
+                    output[0],
+                    format!(
+                        "checker:5:41: {}: subtraction may underflow",
+                        yellow!("warning")
+                    )
+                );
+                assert_eq!(
+                    output[1],
+                    "        (ok (map-set balances tx-sender (- balance amount)))"
+                );
+                assert_eq!(
+                    output[2],
+                    "                                        ^~~~~~~~~~~~~~~~~~"
+                );
+            }
+            _ => panic!("Expected successful interpretation"),
+        };
+    }
+
+    #[test]
+    fn guarded_subtraction() {
+        let mut settings = SessionSettings::default();
+        settings.repl_settings.analysis.passes = vec![Pass::ArithmeticChecker];
+        let mut session = Session::new(settings);
+        let snippet = "
+(define-map balances principal uint)
+(define-data-var supply uint u0)
+(define-public (withdraw (amount uint))
+    (let ((balance (default-to u0 (map-get? balances tx-sender))))
+        (asserts! (<= amount balance) (err u1))
+        (asserts! (> (var-get supply) u0) (err u2))
+        (var-set supply (- (var-get supply) u1))
+        (ok (map-set balances tx-sender (- balance amount)))
+    )
+)
+(define-read-only (diff (a uint) (b uint))
+    (if (< a b) (- b a) (- a b))
+)
+"
+        .to_string();
+        match session.formatted_interpretation(
+            snippet,
+            Some("checker".to_string()),
+            false,
+            None,
+            None,
+        ) {
+            Ok((_, result)) => {
+                assert_eq!(result.diagnostics.len(), 0);
+            }
+            _ => panic!("Expected successful interpretation"),
+        };
+    }
+
+    #[test]
+    fn signed_subtraction() {
+        let mut settings = SessionSettings::default();
+        settings.repl_settings.analysis.passes = vec![Pass::ArithmeticChecker];
+        let mut session = Session::new(settings);
+        let snippet = "
+(define-read-only (delta (a int) (b int))
+    (- a b)
+)
+"
+        .to_string();
+        match session.formatted_interpretation(
+            snippet,
+            Some("checker".to_string()),
+            false,
+            None,
+            None,
+        ) {
+            Ok((_, result)) => {
+                assert_eq!(result.diagnostics.len(), 0);
+            }
+            _ => panic!("Expected successful interpretation"),
+        };
+    }
+
+    #[test]
+    fn division() {
+        let mut settings = SessionSettings::default();
+        settings.repl_settings.analysis.passes = vec![Pass::ArithmeticChecker];
+        let mut session = Session::new(settings);
+        let snippet = "
+(define-read-only (share (total uint) (parts uint))
+    (/ total parts)
+)
+(define-read-only (safe-share (total uint) (parts uint))
+    (if (is-eq parts u0) u0 (/ total parts))
+)
+(define-read-only (half (total uint))
+    (/ total u2)
+)
+"
+        .to_string();
+        match session.formatted_interpretation(
+            snippet,
+            Some("checker".to_string()),
+            false,
+            None,
+            None,
+        ) {
+            Ok((output, result)) => {
+                assert_eq!(result.diagnostics.len(), 1);
+                assert_eq!(
+                    output[0],
+                    format!(
+                        "checker:3:5: {}: possible division by zero",
+                        yellow!("warning")
+                    )
+                );
+            }
+            _ => panic!("Expected successful interpretation"),
+        };
+    }
+
+    #[test]
+    fn power() {
+        let mut settings = SessionSettings::default();
+        settings.repl_settings.analysis.passes = vec![Pass::ArithmeticChecker];
+        let mut session = Session::new(settings);
+        let snippet = "
+(define-read-only (scale (decimals uint))
+    (pow u10 decimals)
+)
+(define-read-only (safe-scale (decimals uint))
+    (begin
+        (asserts! (<= decimals u18) (err u1))
+        (ok (pow u10 decimals))
+    )
+)
+"
+        .to_string();
+        match session.formatted_interpretation(
+            snippet,
+            Some("checker".to_string()),
+            false,
+            None,
+            None,
+        ) {
+            Ok((output, result)) => {
+                assert_eq!(result.diagnostics.len(), 1);
+                assert_eq!(
+                    output[0],
+                    format!(
+                        "checker:3:5: {}: exponent is not bounded, pow may overflow",
+                        yellow!("warning")
+                    )
+                );
+            }
+            _ => panic!("Expected successful interpretation"),
+        };
+    }
+
+    #[test]
+    fn facts_invalidated_by_writes() {
+        let mut settings = SessionSettings::default();
+        settings.repl_settings.analysis.passes = vec![Pass::ArithmeticChecker];
+        let mut session = Session::new(settings);
+        let snippet = "
+(define-data-var supply uint u0)
+(define-public (burn (amount uint))
+    (begin
+        (asserts! (>= (var-get supply) amount) (err u1))
+        (var-set supply u0)
+        (ok (var-set supply (- (var-get supply) amount)))
+    )
+)
+"
+        .to_string();
+        match session.formatted_interpretation(
+            snippet,
+            Some("checker".to_string()),
+            false,
+            None,
+            None,
+        ) {
+            Ok((output, result)) => {
+                assert_eq!(result.diagnostics.len(), 1);
+                assert_eq!(
+                    output[0],
+                    format!(
+                        "checker:7:29: {}: subtraction may underflow",
+                        yellow!("warning")
+                    )
+                );
+            }
+            _ => panic!("Expected successful interpretation"),
+        };
+    }
+
+    #[test]
+    fn n_ary_subtraction() {
+        let mut settings = SessionSettings::default();
+        settings.repl_settings.analysis.passes = vec![Pass::ArithmeticChecker];
+        let mut session = Session::new(settings);
+        let snippet = "
+(define-read-only (net (amount uint) (fee uint))
+    (if (>= amount (+ fee u1)) (- amount fee u1) u0)
+)
+(define-read-only (trim (amount uint))
+    (if (> amount u2) (- amount u1 u2) u0)
+)
+(define-read-only (unchecked (amount uint) (fee uint))
+    (- amount fee u1)
+)
+"
+        .to_string();
+        match session.formatted_interpretation(
+            snippet,
+            Some("checker".to_string()),
+            false,
+            None,
+            None,
+        ) {
+            Ok((output, result)) => {
+                assert_eq!(result.diagnostics.len(), 1);
+                assert_eq!(
+                    output[0],
+                    format!(
+                        "checker:9:5: {}: subtraction may underflow",
+                        yellow!("warning")
+                    )
+                );
+            }
+            _ => panic!("Expected successful interpretation"),
+        };
+    }
+
+    #[test]
+    fn lint_level_error() {
+        let mut settings = SessionSettings::default();
+        settings.repl_settings.analysis.passes = vec![Pass::ArithmeticChecker];
+        settings.repl_settings.analysis.lints.insert(
+            "unchecked_division".to_string(),
+            LintSettings {
+                level: LintLevel::Error,
+            },
+        );
+        let mut session = Session::new(settings);
+        let snippet = "
+(define-read-only (share (total uint) (parts uint))
+    (/ total parts)
+)
+"
+        .to_string();
+        match session.formatted_interpretation(
+            snippet,
+            Some("checker".to_string()),
+            false,
+            None,
+            None,
+        ) {
+            Ok((output, result)) => {
+                assert_eq!(result.diagnostics.len(), 1);
+                assert_eq!(
+                    output[0],
+                    format!("checker:3:5: {}: possible division by zero", red!("error"))
+                );
+            }
+            _ => panic!("Expected successful interpretation"),
+        };
+    }
+
+    #[test]
+    fn lint_level_off() {
+        let mut settings = SessionSettings::default();
+        settings.repl_settings.analysis.passes = vec![Pass::ArithmeticChecker];
+        settings.repl_settings.analysis.lints.insert(
+            "unchecked_pow".to_string(),
+            LintSettings {
+                level: LintLevel::Off,
+            },
+        );
+        let mut session = Session::new(settings);
+        let snippet = "
+(define-read-only (scale (decimals uint))
+    (pow u10 decimals)
+)
+"
+        .to_string();
+        match session.formatted_interpretation(
+            snippet,
+            Some("checker".to_string()),
+            false,
+            None,
+            None,
+        ) {
+            Ok((_, result)) => {
+                assert_eq!(result.diagnostics.len(), 0);
+            }
+            _ => panic!("Expected successful interpretation"),
+        };
+    }
+}
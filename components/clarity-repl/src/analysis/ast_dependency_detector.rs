@@ -1,11 +1,13 @@
 use crate::analysis::annotation::Annotation;
-use crate::analysis::ast_visitor::{traverse, ASTVisitor};
+use crate::analysis::ast_visitor::{definition_name_span, traverse, ASTVisitor};
 use crate::analysis::{AnalysisPass, AnalysisResult, Settings};
 use clarity::vm::analysis::analysis_db::AnalysisDatabase;
 pub use clarity::vm::analysis::types::ContractAnalysis;
 use clarity::vm::analysis::{CheckErrors, CheckResult};
 use clarity::vm::ast::ContractAST;
-use clarity::vm::representations::{SymbolicExpression, TraitDefinition};
+use clarity::vm::diagnostic::{Diagnostic, Level};
+use clarity::vm::functions::define::DefineFunctions;
+use clarity::vm::representations::{Span, SymbolicExpression, TraitDefinition};
 use clarity::vm::types::{
     FixedFunction, FunctionSignature, FunctionType, PrincipalData, QualifiedContractIdentifier,
     TraitIdentifier, TypeSignature, Value,
@@ -33,6 +35,13 @@ pub struct ASTDependencyDetector<'a> {
         (&'a QualifiedContractIdentifier, &'a ClarityName),
         BTreeMap<ClarityName, FunctionSignature>,
     >,
+    // Definitions of the functions and traits, used to check trait conformance
+    function_exprs: HashMap<
+        (&'a QualifiedContractIdentifier, &'a ClarityName),
+        (DefineFunctions, &'a SymbolicExpression),
+    >,
+    trait_exprs:
+        HashMap<(&'a QualifiedContractIdentifier, &'a ClarityName), &'a [SymbolicExpression]>,
     pending_function_checks: HashMap<
         // function identifier whose type is not yet defined
         (&'a QualifiedContractIdentifier, &'a ClarityName),
@@ -151,11 +160,81 @@ impl<'a> ASTDependencyDetector<'a> {
             Vec<QualifiedContractIdentifier>,
         ),
     > {
+        Self::new(contract_asts, preloaded).into_dependencies()
+    }
+
+    fn into_dependencies(
+        self,
+    ) -> Result<
+        HashMap<QualifiedContractIdentifier, DependencySet>,
+        (
+            HashMap<QualifiedContractIdentifier, DependencySet>,
+            Vec<QualifiedContractIdentifier>,
+        ),
+    > {
+        // Anything remaining in the pending_ maps indicates an unresolved dependency
+        let mut unresolved: Vec<QualifiedContractIdentifier> = self
+            .pending_function_checks
+            .into_keys()
+            .map(|(contract_id, name)| contract_id.clone())
+            .collect();
+        unresolved.append(
+            &mut self
+                .pending_trait_checks
+                .into_keys()
+                .map(|trait_id| trait_id.contract_identifier.clone())
+                .collect(),
+        );
+        if !unresolved.is_empty() {
+            Err((self.dependencies, unresolved))
+        } else {
+            Ok(self.dependencies)
+        }
+    }
+
+    /// Detect the dependencies like `detect_dependencies`, then check that the
+    /// contracts conform to the traits they declare with `impl-trait`, and
+    /// that the contracts passed as trait arguments conform to the expected
+    /// trait, when it can be resolved statically.
+    pub fn check_trait_conformance(
+        contract_asts: &'a HashMap<QualifiedContractIdentifier, ContractAST>,
+        preloaded: &'a BTreeMap<QualifiedContractIdentifier, ContractAST>,
+    ) -> (
+        Result<
+            HashMap<QualifiedContractIdentifier, DependencySet>,
+            (
+                HashMap<QualifiedContractIdentifier, DependencySet>,
+                Vec<QualifiedContractIdentifier>,
+            ),
+        >,
+        HashMap<QualifiedContractIdentifier, Vec<Diagnostic>>,
+    ) {
+        let detector = Self::new(contract_asts, preloaded);
+        let mut checker = TraitChecker {
+            detector: &detector,
+            current_contract: None,
+            params: None,
+            diagnostics: HashMap::new(),
+        };
+        for (contract_identifier, ast) in contract_asts {
+            checker.current_contract = Some(contract_identifier);
+            traverse(&mut checker, &ast.expressions);
+        }
+        let diagnostics = checker.diagnostics;
+        (detector.into_dependencies(), diagnostics)
+    }
+
+    fn new(
+        contract_asts: &'a HashMap<QualifiedContractIdentifier, ContractAST>,
+        preloaded: &'a BTreeMap<QualifiedContractIdentifier, ContractAST>,
+    ) -> Self {
         let mut detector = Self {
             dependencies: HashMap::new(),
             current_contract: None,
             defined_functions: HashMap::new(),
             defined_traits: HashMap::new(),
+            function_exprs: HashMap::new(),
+            trait_exprs: HashMap::new(),
             pending_function_checks: HashMap::new(),
             pending_trait_checks: HashMap::new(),
             params: None,
@@ -180,25 +259,7 @@ impl<'a> ASTDependencyDetector<'a> {
             detector.current_contract = Some(contract_identifier);
             traverse(&mut detector, &ast.expressions);
         }
-
-        // Anything remaining in the pending_ maps indicates an unresolved dependency
-        let mut unresolved: Vec<QualifiedContractIdentifier> = detector
-            .pending_function_checks
-            .into_keys()
-            .map(|(contract_id, name)| contract_id.clone())
-            .collect();
-        unresolved.append(
-            &mut detector
-                .pending_trait_checks
-                .into_keys()
-                .map(|trait_id| trait_id.contract_identifier.clone())
-                .collect(),
-        );
-        if !unresolved.is_empty() {
-            Err((detector.dependencies, unresolved))
-        } else {
-            Ok(detector.dependencies)
-        }
+        detector
     }
 
     pub fn order_contracts(
@@ -283,8 +344,13 @@ impl<'a> ASTDependencyDetector<'a> {
         &mut self,
         contract_identifier: &'a QualifiedContractIdentifier,
         name: &'a ClarityName,
+        define_type: DefineFunctions,
+        expr: &'a SymbolicExpression,
         param_types: Vec<TypeSignature>,
     ) {
+        self.function_exprs
+            .insert((contract_identifier, name), (define_type, expr));
+
         if let Some(pending) = self
             .pending_function_checks
             .remove(&(contract_identifier, name))
@@ -318,8 +384,12 @@ impl<'a> ASTDependencyDetector<'a> {
         &mut self,
         contract_identifier: &'a QualifiedContractIdentifier,
         name: &'a ClarityName,
+        functions: &'a [SymbolicExpression],
         trait_definition: BTreeMap<ClarityName, FunctionSignature>,
     ) {
+        self.trait_exprs
+            .insert((contract_identifier, name), functions);
+
         if let Some(pending) = self.pending_trait_checks.remove(&TraitIdentifier {
             name: name.clone(),
             contract_identifier: contract_identifier.clone(),
@@ -386,28 +456,34 @@ impl<'a> ASTDependencyDetector<'a> {
         self.check_callee_type(&function_signature.args, args)
     }
 
-    // A trait can only come from a parameter (cannot be a let binding or a return value), so
-    // find the corresponding parameter and return it.
     fn get_param_trait(&self, name: &ClarityName) -> Option<&'a TraitIdentifier> {
-        let params = match &self.params {
-            None => return None,
-            Some(params) => params,
-        };
-        for param in params {
-            if param.name == name {
-                if let SymbolicExpressionType::TraitReference(_, trait_def) = &param.type_expr.expr
-                {
-                    return match trait_def {
-                        TraitDefinition::Defined(identifier) => Some(identifier),
-                        TraitDefinition::Imported(identifier) => Some(identifier),
-                    };
-                } else {
-                    return None;
-                }
+        get_param_trait(&self.params, name)
+    }
+}
+
+// A trait can only come from a parameter (cannot be a let binding or a return value), so
+// find the corresponding parameter and return it.
+fn get_param_trait<'a>(
+    params: &Option<Vec<TypedVar<'a>>>,
+    name: &ClarityName,
+) -> Option<&'a TraitIdentifier> {
+    let params = match params {
+        None => return None,
+        Some(params) => params,
+    };
+    for param in params {
+        if param.name == name {
+            if let SymbolicExpressionType::TraitReference(_, trait_def) = &param.type_expr.expr {
+                return match trait_def {
+                    TraitDefinition::Defined(identifier) => Some(identifier),
+                    TraitDefinition::Imported(identifier) => Some(identifier),
+                };
+            } else {
+                return None;
             }
         }
-        None
     }
+    None
 }
 
 impl<'a> ASTVisitor<'a> for ASTDependencyDetector<'a> {
@@ -448,7 +524,13 @@ impl<'a> ASTVisitor<'a> for ASTDependencyDetector<'a> {
             None => Vec::new(),
         };
 
-        self.add_defined_function(self.current_contract.unwrap(), name, param_types);
+        self.add_defined_function(
+            self.current_contract.unwrap(),
+            name,
+            DefineFunctions::PrivateFunction,
+            expr,
+            param_types,
+        );
         true
     }
 
@@ -486,7 +568,13 @@ impl<'a> ASTVisitor<'a> for ASTDependencyDetector<'a> {
             None => Vec::new(),
         };
 
-        self.add_defined_function(self.current_contract.unwrap(), name, param_types);
+        self.add_defined_function(
+            self.current_contract.unwrap(),
+            name,
+            DefineFunctions::ReadOnlyFunction,
+            expr,
+            param_types,
+        );
         true
     }
 
@@ -524,7 +612,13 @@ impl<'a> ASTVisitor<'a> for ASTDependencyDetector<'a> {
             None => Vec::new(),
         };
 
-        self.add_defined_function(self.current_contract.unwrap(), name, param_types);
+        self.add_defined_function(
+            self.current_contract.unwrap(),
+            name,
+            DefineFunctions::PublicFunction,
+            expr,
+            param_types,
+        );
         true
     }
 
@@ -535,7 +629,12 @@ impl<'a> ASTVisitor<'a> for ASTDependencyDetector<'a> {
         functions: &'a [SymbolicExpression],
     ) -> bool {
         if let Ok(trait_definition) = TypeSignature::parse_trait_type_repr(functions, &mut ()) {
-            self.add_defined_trait(self.current_contract.unwrap(), name, trait_definition);
+            self.add_defined_trait(
+                self.current_contract.unwrap(),
+                name,
+                functions,
+                trait_definition,
+            );
         }
         true
     }
@@ -681,8 +780,13 @@ impl<'a, 'b> ASTVisitor<'a> for PreloadedVisitor<'a, 'b> {
             None => Vec::new(),
         };
 
-        self.detector
-            .add_defined_function(self.current_contract.unwrap(), name, param_types);
+        self.detector.add_defined_function(
+            self.current_contract.unwrap(),
+            name,
+            DefineFunctions::ReadOnlyFunction,
+            expr,
+            param_types,
+        );
         true
     }
 
@@ -704,8 +808,13 @@ impl<'a, 'b> ASTVisitor<'a> for PreloadedVisitor<'a, 'b> {
             None => Vec::new(),
         };
 
-        self.detector
-            .add_defined_function(self.current_contract.unwrap(), name, param_types);
+        self.detector.add_defined_function(
+            self.current_contract.unwrap(),
+            name,
+            DefineFunctions::PublicFunction,
+            expr,
+            param_types,
+        );
         true
     }
 
@@ -716,13 +825,501 @@ impl<'a, 'b> ASTVisitor<'a> for PreloadedVisitor<'a, 'b> {
         functions: &'a [SymbolicExpression],
     ) -> bool {
         if let Ok(trait_definition) = TypeSignature::parse_trait_type_repr(functions, &mut ()) {
-            self.detector
-                .add_defined_trait(self.current_contract.unwrap(), name, trait_definition);
+            self.detector.add_defined_trait(
+                self.current_contract.unwrap(),
+                name,
+                functions,
+                trait_definition,
+            );
         }
         true
     }
 }
 
+// How a contract fails to implement a function of a trait
+enum Mismatch<'a> {
+    Missing,
+    Private(&'a SymbolicExpression),
+    Arguments(&'a SymbolicExpression, Vec<TypeSignature>),
+    Returns(&'a SymbolicExpression, ReturnType),
+}
+
+impl Mismatch<'_> {
+    // The return types are only inferred from the syntax of the functions, and
+    // can be wrong, so they are not reported as errors.
+    fn level(&self) -> Level {
+        match self {
+            Mismatch::Returns(..) => Level::Warning,
+            _ => Level::Error,
+        }
+    }
+}
+
+// Once all contracts have been traversed by the dependency detector, checks
+// the `impl-trait` declarations and the contracts passed as trait arguments.
+struct TraitChecker<'a, 'b> {
+    detector: &'b ASTDependencyDetector<'a>,
+    current_contract: Option<&'a QualifiedContractIdentifier>,
+    params: Option<Vec<TypedVar<'a>>>,
+    diagnostics: HashMap<QualifiedContractIdentifier, Vec<Diagnostic>>,
+}
+
+impl<'a, 'b> TraitChecker<'a, 'b> {
+    fn find_mismatches(
+        &self,
+        contract_identifier: &QualifiedContractIdentifier,
+        trait_definition: &'b BTreeMap<ClarityName, FunctionSignature>,
+    ) -> Vec<(&'b ClarityName, &'b FunctionSignature, Mismatch<'a>)> {
+        let mut mismatches = vec![];
+        for (name, signature) in trait_definition {
+            let key = (contract_identifier, name);
+            let (define_type, expr) = match self.detector.function_exprs.get(&key) {
+                Some(definition) => definition,
+                None => {
+                    mismatches.push((name, signature, Mismatch::Missing));
+                    continue;
+                }
+            };
+            if matches!(define_type, DefineFunctions::PrivateFunction) {
+                mismatches.push((name, signature, Mismatch::Private(*expr)));
+                continue;
+            }
+            if let Some(arg_types) = self.detector.defined_functions.get(&key) {
+                if *arg_types != signature.args {
+                    mismatches.push((
+                        name,
+                        signature,
+                        Mismatch::Arguments(*expr, arg_types.clone()),
+                    ));
+                    continue;
+                }
+            }
+            if let Some(body) = expr.match_list().and_then(|list| list.get(2)) {
+                let return_type = ReturnType::infer(body);
+                if return_type.conflicts_with(&signature.returns) {
+                    mismatches.push((name, signature, Mismatch::Returns(*expr, return_type)));
+                }
+            }
+        }
+        mismatches
+    }
+
+    fn is_known_contract(&self, contract_identifier: &QualifiedContractIdentifier) -> bool {
+        self.detector.dependencies.contains_key(contract_identifier)
+            || self.detector.preloaded.contains_key(contract_identifier)
+    }
+
+    // Span of the name of a function in the declaration of a trait
+    fn trait_function_span(
+        &self,
+        trait_identifier: &TraitIdentifier,
+        name: &ClarityName,
+    ) -> Option<Span> {
+        let functions = self.detector.trait_exprs.get(&(
+            &trait_identifier.contract_identifier,
+            &trait_identifier.name,
+        ))?;
+        functions.iter().find_map(|function| {
+            let name_expr = function.match_list()?.first()?;
+            match name_expr.match_atom() {
+                Some(function_name) if function_name == name => Some(name_expr.span.clone()),
+                _ => None,
+            }
+        })
+    }
+
+    fn add_diagnostic(&mut self, level: Level, message: String, spans: Vec<Span>) {
+        self.diagnostics
+            .entry(self.current_contract.unwrap().clone())
+            .or_insert_with(Vec::new)
+            .push(Diagnostic {
+                level,
+                message,
+                spans,
+                suggestion: None,
+            });
+    }
+
+    // Notes can point to other contracts than the one being checked, they
+    // are reported with the diagnostics of the contract they point to.
+    fn add_note(
+        &mut self,
+        contract_identifier: &QualifiedContractIdentifier,
+        message: String,
+        span: Span,
+    ) {
+        self.diagnostics
+            .entry(contract_identifier.clone())
+            .or_insert_with(Vec::new)
+            .push(Diagnostic {
+                level: Level::Note,
+                message,
+                spans: vec![span],
+                suggestion: None,
+            });
+    }
+
+    fn add_trait_note(&mut self, trait_identifier: &TraitIdentifier, name: &ClarityName) {
+        if let Some(span) = self.trait_function_span(trait_identifier, name) {
+            self.add_note(
+                &trait_identifier.contract_identifier,
+                format!(
+                    "'{}' is declared here, in trait '{}' required by {}",
+                    name,
+                    trait_identifier.name,
+                    self.current_contract.unwrap()
+                ),
+                span,
+            );
+        }
+    }
+
+    // Check that the contracts passed as literals for trait parameters
+    // conform to these traits.
+    fn check_trait_args(&mut self, arg_types: &[TypeSignature], args: &'a [SymbolicExpression]) {
+        for (arg_type, arg) in arg_types.iter().zip(args) {
+            let trait_identifier = match arg_type {
+                TypeSignature::TraitReferenceType(trait_identifier) => trait_identifier,
+                _ => continue,
+            };
+            let contract_identifier = match arg.match_literal_value() {
+                Some(Value::Principal(PrincipalData::Contract(contract))) => contract,
+                _ => continue,
+            };
+            let trait_definition = match self.detector.defined_traits.get(&(
+                &trait_identifier.contract_identifier,
+                &trait_identifier.name,
+            )) {
+                Some(trait_definition) => trait_definition,
+                None => continue,
+            };
+            if !self.is_known_contract(contract_identifier) {
+                continue;
+            }
+            for (name, signature, mismatch) in
+                self.find_mismatches(contract_identifier, trait_definition)
+            {
+                let level = mismatch.level();
+                let (reason, definition) = match mismatch {
+                    Mismatch::Missing => (format!("function '{}' is missing", name), None),
+                    Mismatch::Private(expr) => {
+                        (format!("function '{}' is private", name), Some(expr))
+                    }
+                    Mismatch::Arguments(expr, arg_types) => (
+                        format!(
+                            "function '{}' expects arguments ({}), the trait requires ({})",
+                            name,
+                            format_types(arg_types),
+                            format_types(&signature.args)
+                        ),
+                        Some(expr),
+                    ),
+                    Mismatch::Returns(expr, return_type) => (
+                        format!(
+                            "function '{}' returns {}, the trait requires {}",
+                            name, return_type, signature.returns
+                        ),
+                        Some(expr),
+                    ),
+                };
+                self.add_diagnostic(
+                    level,
+                    format!(
+                        "contract '{}' does not conform to trait '{}': {}",
+                        contract_identifier, trait_identifier.name, reason
+                    ),
+                    vec![arg.span.clone()],
+                );
+                if let Some(expr) = definition {
+                    self.add_note(
+                        contract_identifier,
+                        format!(
+                            "'{}' is defined here, and does not conform to trait '{}' required by {}",
+                            name,
+                            trait_identifier.name,
+                            self.current_contract.unwrap()
+                        ),
+                        definition_name_span(expr),
+                    );
+                }
+                self.add_trait_note(trait_identifier, name);
+            }
+        }
+    }
+}
+
+impl<'a, 'b> ASTVisitor<'a> for TraitChecker<'a, 'b> {
+    fn traverse_define_private(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        parameters: Option<Vec<TypedVar<'a>>>,
+        body: &'a SymbolicExpression,
+    ) -> bool {
+        self.params = parameters;
+        let res = self.traverse_expr(body);
+        self.params = None;
+        res
+    }
+
+    fn traverse_define_read_only(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        parameters: Option<Vec<TypedVar<'a>>>,
+        body: &'a SymbolicExpression,
+    ) -> bool {
+        self.params = parameters;
+        let res = self.traverse_expr(body);
+        self.params = None;
+        res
+    }
+
+    fn traverse_define_public(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        parameters: Option<Vec<TypedVar<'a>>>,
+        body: &'a SymbolicExpression,
+    ) -> bool {
+        self.params = parameters;
+        let res = self.traverse_expr(body);
+        self.params = None;
+        res
+    }
+
+    fn visit_impl_trait(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        trait_identifier: &TraitIdentifier,
+    ) -> bool {
+        let trait_definition = match self.detector.defined_traits.get(&(
+            &trait_identifier.contract_identifier,
+            &trait_identifier.name,
+        )) {
+            Some(trait_definition) => trait_definition,
+            None => return true,
+        };
+        let contract_identifier = self.current_contract.unwrap();
+        for (name, signature, mismatch) in
+            self.find_mismatches(contract_identifier, trait_definition)
+        {
+            match mismatch {
+                Mismatch::Missing => self.add_diagnostic(
+                    Level::Error,
+                    format!(
+                        "missing function '{}' required by trait '{}'",
+                        name, trait_identifier.name
+                    ),
+                    vec![expr.span.clone()],
+                ),
+                Mismatch::Private(definition) => self.add_diagnostic(
+                    Level::Error,
+                    format!(
+                        "function '{}' required by trait '{}' must be public or read-only",
+                        name, trait_identifier.name
+                    ),
+                    vec![definition_name_span(definition)],
+                ),
+                Mismatch::Arguments(definition, arg_types) => self.add_diagnostic(
+                    Level::Error,
+                    format!(
+                        "function '{}' does not match trait '{}': expected arguments ({}), found ({})",
+                        name,
+                        trait_identifier.name,
+                        format_types(&signature.args),
+                        format_types(arg_types)
+                    ),
+                    vec![definition_name_span(definition)],
+                ),
+                Mismatch::Returns(definition, return_type) => self.add_diagnostic(
+                    Level::Warning,
+                    format!(
+                        "function '{}' does not match trait '{}': expected to return {}, found {}",
+                        name, trait_identifier.name, signature.returns, return_type
+                    ),
+                    vec![definition_name_span(definition)],
+                ),
+            }
+            self.add_trait_note(trait_identifier, name);
+        }
+        true
+    }
+
+    fn visit_static_contract_call(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        contract_identifier: &'a QualifiedContractIdentifier,
+        function_name: &'a ClarityName,
+        args: &'a [SymbolicExpression],
+    ) -> bool {
+        if let Some(arg_types) = self
+            .detector
+            .defined_functions
+            .get(&(contract_identifier, function_name))
+        {
+            self.check_trait_args(arg_types, args);
+        }
+        true
+    }
+
+    fn visit_dynamic_contract_call(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        trait_ref: &'a SymbolicExpression,
+        function_name: &'a ClarityName,
+        args: &'a [SymbolicExpression],
+    ) -> bool {
+        let trait_instance = trait_ref.match_atom().unwrap_or(&DEFAULT_NAME);
+        if let Some(trait_identifier) = get_param_trait(&self.params, trait_instance) {
+            if let Some(signature) = self
+                .detector
+                .defined_traits
+                .get(&(
+                    &trait_identifier.contract_identifier,
+                    &trait_identifier.name,
+                ))
+                .and_then(|trait_definition| trait_definition.get(function_name))
+            {
+                self.check_trait_args(&signature.args, args);
+            }
+        }
+        true
+    }
+
+    fn visit_call_user_defined(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        args: &'a [SymbolicExpression],
+    ) -> bool {
+        if let Some(arg_types) = self
+            .detector
+            .defined_functions
+            .get(&(self.current_contract.unwrap(), name))
+        {
+            self.check_trait_args(arg_types, args);
+        }
+        true
+    }
+}
+
+fn format_types(types: &[TypeSignature]) -> String {
+    types
+        .iter()
+        .map(|t| t.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// Return type of a function, as far as it can be inferred from the syntax of
+// its body, without type checking. Only the parts which can be inferred are
+// compared with the signatures of the traits.
+#[derive(Debug, Clone, PartialEq)]
+enum ReturnType {
+    Unknown,
+    // Only `int`, `uint`, `bool` and `principal`
+    Known(TypeSignature),
+    Optional(Box<ReturnType>),
+    Response(Box<ReturnType>, Box<ReturnType>),
+}
+
+impl ReturnType {
+    fn infer(expr: &SymbolicExpression) -> ReturnType {
+        if let Some(value) = expr
+            .match_literal_value()
+            .or_else(|| expr.match_atom_value())
+        {
+            return match value {
+                Value::Int(_) => ReturnType::Known(TypeSignature::IntType),
+                Value::UInt(_) => ReturnType::Known(TypeSignature::UIntType),
+                Value::Bool(_) => ReturnType::Known(TypeSignature::BoolType),
+                Value::Principal(PrincipalData::Standard(_)) => {
+                    ReturnType::Known(TypeSignature::PrincipalType)
+                }
+                _ => ReturnType::Unknown,
+            };
+        }
+        if let Some(atom) = expr.match_atom() {
+            return match atom.as_str() {
+                "true" | "false" => ReturnType::Known(TypeSignature::BoolType),
+                _ => ReturnType::Unknown,
+            };
+        }
+        let list = match expr.match_list() {
+            Some(list) => list,
+            None => return ReturnType::Unknown,
+        };
+        let function = match list.first().and_then(|function| function.match_atom()) {
+            Some(function) => function.as_str(),
+            None => return ReturnType::Unknown,
+        };
+        match (function, list.len()) {
+            ("ok", 2) => ReturnType::Response(
+                Box::new(ReturnType::infer(&list[1])),
+                Box::new(ReturnType::Unknown),
+            ),
+            ("err", 2) => ReturnType::Response(
+                Box::new(ReturnType::Unknown),
+                Box::new(ReturnType::infer(&list[1])),
+            ),
+            ("some", 2) => ReturnType::Optional(Box::new(ReturnType::infer(&list[1]))),
+            ("begin", len) | ("let", len) if len > 1 => ReturnType::infer(&list[len - 1]),
+            ("if", 4) => ReturnType::infer(&list[2]).merge(ReturnType::infer(&list[3])),
+            ("not", _)
+            | ("and", _)
+            | ("or", _)
+            | ("is-eq", _)
+            | ("<", _)
+            | (">", _)
+            | ("<=", _)
+            | (">=", _) => ReturnType::Known(TypeSignature::BoolType),
+            _ => ReturnType::Unknown,
+        }
+    }
+
+    // Combine the types of the branches of an expression, which must be the same
+    fn merge(self, other: ReturnType) -> ReturnType {
+        match (self, other) {
+            (ReturnType::Unknown, other) => other,
+            (known, ReturnType::Unknown) => known,
+            (ReturnType::Known(a), ReturnType::Known(b)) if a == b => ReturnType::Known(a),
+            (ReturnType::Optional(a), ReturnType::Optional(b)) => {
+                ReturnType::Optional(Box::new(a.merge(*b)))
+            }
+            (ReturnType::Response(a_ok, a_err), ReturnType::Response(b_ok, b_err)) => {
+                ReturnType::Response(Box::new(a_ok.merge(*b_ok)), Box::new(a_err.merge(*b_err)))
+            }
+            _ => ReturnType::Unknown,
+        }
+    }
+
+    fn conflicts_with(&self, expected: &TypeSignature) -> bool {
+        match (self, expected) {
+            (ReturnType::Unknown, _) => false,
+            (ReturnType::Known(found), expected) => found != expected,
+            (ReturnType::Optional(found), TypeSignature::OptionalType(expected)) => {
+                found.conflicts_with(expected)
+            }
+            (ReturnType::Response(ok, err), TypeSignature::ResponseType(expected)) => {
+                ok.conflicts_with(&expected.0) || err.conflicts_with(&expected.1)
+            }
+            _ => true,
+        }
+    }
+}
+
+impl std::fmt::Display for ReturnType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReturnType::Unknown => write!(f, "_"),
+            ReturnType::Known(type_signature) => write!(f, "{}", type_signature),
+            ReturnType::Optional(inner) => write!(f, "(optional {})", inner),
+            ReturnType::Response(ok, err) => write!(f, "(response {} {})", ok, err),
+        }
+    }
+}
+
 struct Graph {
     pub adjacency_list: Vec<Vec<usize>>,
 }
@@ -1257,4 +1854,247 @@ mod tests {
         assert_eq!(dependencies[&test_identifier].len(), 1);
         assert!(dependencies[&test_identifier].has_dependency(&foo).unwrap());
     }
+
+    #[test]
+    fn trait_conformance() {
+        let session = Session::new(SessionSettings::default());
+        let mut contracts = HashMap::new();
+        let snippet1 = "
+(define-trait something
+    ((hello (int) (response uint uint))
+     (goodbye () (response uint uint)))
+)"
+        .to_string();
+        match build_ast(&session, &snippet1, Some("other")) {
+            Ok((contract_identifier, ast, _)) => {
+                contracts.insert(contract_identifier, ast);
+            }
+            Err(_) => panic!("expected success"),
+        };
+
+        let snippet = "
+(impl-trait .other.something)
+(define-public (hello (a int))
+    (ok u0)
+)
+(define-read-only (goodbye)
+    (ok u0)
+)
+"
+        .to_string();
+        match build_ast(&session, &snippet, Some("test")) {
+            Ok((contract_identifier, ast, _)) => {
+                contracts.insert(contract_identifier, ast);
+            }
+            Err(_) => panic!("expected success"),
+        };
+
+        let diagnostics =
+            ASTDependencyDetector::check_trait_conformance(&contracts, &BTreeMap::new()).1;
+        assert!(diagnostics.is_empty());
+    }
+
+    #[test]
+    fn trait_conformance_mismatch() {
+        let session = Session::new(SessionSettings::default());
+        let mut contracts = HashMap::new();
+        let snippet1 = "
+(define-trait something
+    ((hello (int) (response uint uint))
+     (goodbye () (response uint uint)))
+)"
+        .to_string();
+        let other_identifier = match build_ast(&session, &snippet1, Some("other")) {
+            Ok((contract_identifier, ast, _)) => {
+                contracts.insert(contract_identifier.clone(), ast);
+                contract_identifier
+            }
+            Err(_) => panic!("expected success"),
+        };
+
+        let snippet = "
+(impl-trait .other.something)
+(define-public (hello (a uint))
+    (ok u0)
+)
+"
+        .to_string();
+        let test_identifier = match build_ast(&session, &snippet, Some("test")) {
+            Ok((contract_identifier, ast, _)) => {
+                contracts.insert(contract_identifier.clone(), ast);
+                contract_identifier
+            }
+            Err(_) => panic!("expected success"),
+        };
+
+        let diagnostics =
+            ASTDependencyDetector::check_trait_conformance(&contracts, &BTreeMap::new()).1;
+        let errors = &diagnostics[&test_identifier];
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].level, Level::Error);
+        assert_eq!(
+            errors[0].message,
+            "missing function 'goodbye' required by trait 'something'"
+        );
+        assert_eq!(errors[0].spans[0].start_line, 2);
+        assert_eq!(
+            errors[1].message,
+            "function 'hello' does not match trait 'something': expected arguments (int), found (uint)"
+        );
+        assert_eq!(errors[1].spans[0].start_line, 3);
+        assert_eq!(errors[1].spans[0].start_column, 17);
+
+        // The notes point to the declarations in the trait
+        let notes = &diagnostics[&other_identifier];
+        assert_eq!(notes.len(), 2);
+        assert_eq!(notes[0].level, Level::Note);
+        assert!(notes[0]
+            .message
+            .starts_with("'goodbye' is declared here, in trait 'something' required by"));
+        assert_eq!(notes[0].spans[0].start_line, 4);
+        assert_eq!(notes[0].spans[0].start_column, 7);
+        assert_eq!(notes[1].spans[0].start_line, 3);
+    }
+
+    #[test]
+    fn trait_argument_mismatch() {
+        let session = Session::new(SessionSettings::default());
+        let mut contracts = HashMap::new();
+        let snippet1 = "
+(define-trait something
+    ((hello (int) (response uint uint)))
+)
+(define-public (call-hello (t <something>))
+    (contract-call? t hello 1)
+)"
+        .to_string();
+        match build_ast(&session, &snippet1, Some("other")) {
+            Ok((contract_identifier, ast, _)) => {
+                contracts.insert(contract_identifier, ast);
+            }
+            Err(_) => panic!("expected success"),
+        };
+
+        let snippet2 = "
+(define-private (hello (a int))
+    (ok u0)
+)"
+        .to_string();
+        let foo_identifier = match build_ast(&session, &snippet2, Some("foo")) {
+            Ok((contract_identifier, ast, _)) => {
+                contracts.insert(contract_identifier.clone(), ast);
+                contract_identifier
+            }
+            Err(_) => panic!("expected success"),
+        };
+
+        let snippet = "(contract-call? .other call-hello .foo)".to_string();
+        let test_identifier = match build_ast(&session, &snippet, Some("test")) {
+            Ok((contract_identifier, ast, _)) => {
+                contracts.insert(contract_identifier.clone(), ast);
+                contract_identifier
+            }
+            Err(_) => panic!("expected success"),
+        };
+
+        let diagnostics =
+            ASTDependencyDetector::check_trait_conformance(&contracts, &BTreeMap::new()).1;
+        let errors = &diagnostics[&test_identifier];
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].level, Level::Error);
+        assert!(errors[0]
+            .message
+            .ends_with("does not conform to trait 'something': function 'hello' is private"));
+        assert_eq!(errors[0].spans[0].start_column, 35);
+
+        // The notes point to the definitions in the other contracts
+        let notes = &diagnostics[&foo_identifier];
+        assert_eq!(notes[0].level, Level::Note);
+        assert!(notes[0].message.starts_with(
+            "'hello' is defined here, and does not conform to trait 'something' required by"
+        ));
+        assert_eq!(notes[0].spans[0].start_line, 2);
+        assert_eq!(notes[0].spans[0].start_column, 18);
+    }
+
+    #[test]
+    fn trait_return_mismatch() {
+        let session = Session::new(SessionSettings::default());
+        let mut contracts = HashMap::new();
+        let snippet1 = "
+(define-trait something
+    ((hello (int) (response uint uint))
+     (goodbye () (response uint uint)))
+)
+(define-public (call-hello (t <something>))
+    (contract-call? t hello 1)
+)"
+        .to_string();
+        match build_ast(&session, &snippet1, Some("other")) {
+            Ok((contract_identifier, ast, _)) => {
+                contracts.insert(contract_identifier, ast);
+            }
+            Err(_) => panic!("expected success"),
+        };
+
+        let snippet2 = "
+(impl-trait .other.something)
+(define-public (hello (a int))
+    (if (> a 0)
+        (ok true)
+        (err u1)
+    )
+)
+(define-public (goodbye)
+    (begin
+        (print \"goodbye\")
+        (ok u0)
+    )
+)"
+        .to_string();
+        let foo_identifier = match build_ast(&session, &snippet2, Some("foo")) {
+            Ok((contract_identifier, ast, _)) => {
+                contracts.insert(contract_identifier.clone(), ast);
+                contract_identifier
+            }
+            Err(_) => panic!("expected success"),
+        };
+
+        let snippet = "(contract-call? .other call-hello .foo)".to_string();
+        let test_identifier = match build_ast(&session, &snippet, Some("test")) {
+            Ok((contract_identifier, ast, _)) => {
+                contracts.insert(contract_identifier.clone(), ast);
+                contract_identifier
+            }
+            Err(_) => panic!("expected success"),
+        };
+
+        let diagnostics =
+            ASTDependencyDetector::check_trait_conformance(&contracts, &BTreeMap::new()).1;
+        // The inferred return types are only reported as warnings
+        let foo_warnings: Vec<&Diagnostic> = diagnostics[&foo_identifier]
+            .iter()
+            .filter(|diagnostic| diagnostic.level != Level::Note)
+            .collect();
+        assert_eq!(foo_warnings.len(), 1);
+        assert_eq!(foo_warnings[0].level, Level::Warning);
+        assert_eq!(
+            foo_warnings[0].message,
+            "function 'hello' does not match trait 'something': expected to return (response uint uint), found (response bool uint)"
+        );
+        assert_eq!(foo_warnings[0].spans[0].start_line, 3);
+
+        let test_diagnostics = &diagnostics[&test_identifier];
+        assert_eq!(test_diagnostics.len(), 1);
+        assert_eq!(test_diagnostics[0].level, Level::Warning);
+        assert!(test_diagnostics[0].message.ends_with(
+            "function 'hello' returns (response bool uint), the trait requires (response uint uint)"
+        ));
+
+        // `goodbye` conforms, only `hello` is reported
+        assert!(!diagnostics
+            .values()
+            .flatten()
+            .any(|diagnostic| diagnostic.message.contains("'goodbye'")));
+    }
 }
@@ -2440,6 +2440,22 @@ pub fn traverse<'a>(visitor: &mut impl ASTVisitor<'a>, exprs: &'a [SymbolicExpre
     true
 }
 
+/// Returns the span of the name in a define expression, e.g. `foo` in
+/// `(define-constant foo u1)` or `(define-private (foo) u1)`.
+pub fn definition_name_span(expr: &SymbolicExpression) -> Span {
+    let name_expr = match expr.match_list().and_then(|list| list.get(1)) {
+        Some(name_expr) => name_expr,
+        None => return expr.span.clone(),
+    };
+    match name_expr.match_list() {
+        Some(signature) => signature
+            .get(0)
+            .map(|name| name.span.clone())
+            .unwrap_or_else(|| name_expr.span.clone()),
+        None => name_expr.span.clone(),
+    }
+}
+
 fn match_tuple(
     expr: &SymbolicExpression,
 ) -> Option<HashMap<Option<&ClarityName>, &SymbolicExpression>> {
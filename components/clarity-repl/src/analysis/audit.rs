@@ -0,0 +1,682 @@
+use crate::analysis::access_checker::Location;
+use crate::analysis::ast_visitor::{definition_name_span, traverse, ASTVisitor, TypedVar};
+use clarity::vm::analysis::types::ContractAnalysis;
+use clarity::vm::functions::NativeFunctions;
+use clarity::vm::representations::SymbolicExpression;
+use clarity::vm::types::QualifiedContractIdentifier;
+use clarity::vm::{ClarityName, ClarityVersion};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Visibility {
+    Public,
+    ReadOnly,
+    Private,
+}
+
+impl fmt::Display for Visibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Visibility::Public => write!(f, "public"),
+            Visibility::ReadOnly => write!(f, "read-only"),
+            Visibility::Private => write!(f, "private"),
+        }
+    }
+}
+
+/// An operation moving, minting or burning assets.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct AssetOperation {
+    pub operation: String,
+    /// Name of the token, `None` for STX
+    pub asset: Option<String>,
+}
+
+impl fmt::Display for AssetOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.asset {
+            Some(asset) => write!(f, "{} {}", self.operation, asset),
+            None => write!(f, "{}", self.operation),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FunctionAudit {
+    pub function: String,
+    pub visibility: Visibility,
+    pub location: Location,
+    /// Maps and data vars read by the function
+    pub reads: BTreeSet<String>,
+    /// Maps and data vars written by the function
+    pub writes: BTreeSet<String>,
+    /// Functions of the contract called by the function
+    pub calls: BTreeSet<String>,
+    /// Functions of other contracts called by the function, as
+    /// `contract.function`, or `<trait>.function` for dynamic calls
+    pub contract_calls: BTreeSet<String>,
+    /// Asset operations performed in the body of the function
+    pub asset_operations: BTreeSet<AssetOperation>,
+    /// Asset operations performed by the function, or by the functions it
+    /// calls transitively. Only computed for public functions.
+    pub reachable_asset_operations: BTreeSet<AssetOperation>,
+}
+
+/// Model of the data flow and of the calls between the functions of a contract.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ContractAudit {
+    pub functions: Vec<FunctionAudit>,
+}
+
+impl ContractAudit {
+    pub fn get_function(&self, name: &str) -> Option<&FunctionAudit> {
+        self.functions
+            .iter()
+            .find(|function| function.function == name)
+    }
+}
+
+struct AuditBuilder {
+    functions: Vec<FunctionAudit>,
+    current: Option<FunctionAudit>,
+}
+
+impl AuditBuilder {
+    fn new() -> AuditBuilder {
+        AuditBuilder {
+            functions: Vec::new(),
+            current: None,
+        }
+    }
+
+    fn run(mut self, expressions: &[SymbolicExpression]) -> ContractAudit {
+        traverse(&mut self, expressions);
+
+        // Resolve the asset operations reachable from the public functions
+        let summaries: HashMap<String, (BTreeSet<String>, BTreeSet<AssetOperation>)> = self
+            .functions
+            .iter()
+            .map(|function| {
+                (
+                    function.function.clone(),
+                    (function.calls.clone(), function.asset_operations.clone()),
+                )
+            })
+            .collect();
+        for function in self.functions.iter_mut() {
+            if function.visibility != Visibility::Public {
+                continue;
+            }
+            let mut visited = BTreeSet::new();
+            let mut queue = vec![function.function.clone()];
+            while let Some(name) = queue.pop() {
+                if !visited.insert(name.clone()) {
+                    continue;
+                }
+                if let Some((calls, operations)) = summaries.get(&name) {
+                    function
+                        .reachable_asset_operations
+                        .extend(operations.iter().cloned());
+                    queue.extend(calls.iter().cloned());
+                }
+            }
+        }
+
+        ContractAudit {
+            functions: self.functions,
+        }
+    }
+
+    fn traverse_function<'a>(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        visibility: Visibility,
+        body: &'a SymbolicExpression,
+    ) -> bool {
+        self.current = Some(FunctionAudit {
+            function: name.to_string(),
+            visibility,
+            location: Location::from(&definition_name_span(expr)),
+            reads: BTreeSet::new(),
+            writes: BTreeSet::new(),
+            calls: BTreeSet::new(),
+            contract_calls: BTreeSet::new(),
+            asset_operations: BTreeSet::new(),
+            reachable_asset_operations: BTreeSet::new(),
+        });
+        let rv = self.traverse_expr(body);
+        if let Some(function) = self.current.take() {
+            self.functions.push(function);
+        }
+        rv
+    }
+
+    fn record_read(&mut self, name: &ClarityName) {
+        if let Some(function) = self.current.as_mut() {
+            function.reads.insert(name.to_string());
+        }
+    }
+
+    fn record_write(&mut self, name: &ClarityName) {
+        if let Some(function) = self.current.as_mut() {
+            function.writes.insert(name.to_string());
+        }
+    }
+
+    fn record_call(&mut self, name: &ClarityName) {
+        if let Some(function) = self.current.as_mut() {
+            function.calls.insert(name.to_string());
+        }
+    }
+
+    /// Record a function passed to `map`, `fold` or `filter`, which can also
+    /// be a native function like `+`.
+    fn record_applied_function(&mut self, name: &ClarityName) {
+        if NativeFunctions::lookup_by_name_at_version(name, &ClarityVersion::latest()).is_none() {
+            self.record_call(name);
+        }
+    }
+
+    fn record_asset_operation(&mut self, operation: &str, asset: Option<&ClarityName>) {
+        if let Some(function) = self.current.as_mut() {
+            function.asset_operations.insert(AssetOperation {
+                operation: operation.to_string(),
+                asset: asset.map(|name| name.to_string()),
+            });
+        }
+    }
+}
+
+impl<'a> ASTVisitor<'a> for AuditBuilder {
+    fn traverse_define_private(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        _parameters: Option<Vec<TypedVar<'a>>>,
+        body: &'a SymbolicExpression,
+    ) -> bool {
+        self.traverse_function(expr, name, Visibility::Private, body)
+    }
+
+    fn traverse_define_read_only(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        _parameters: Option<Vec<TypedVar<'a>>>,
+        body: &'a SymbolicExpression,
+    ) -> bool {
+        self.traverse_function(expr, name, Visibility::ReadOnly, body)
+    }
+
+    fn traverse_define_public(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        _parameters: Option<Vec<TypedVar<'a>>>,
+        body: &'a SymbolicExpression,
+    ) -> bool {
+        self.traverse_function(expr, name, Visibility::Public, body)
+    }
+
+    fn visit_var_get(&mut self, _expr: &'a SymbolicExpression, name: &'a ClarityName) -> bool {
+        self.record_read(name);
+        true
+    }
+
+    fn visit_var_set(
+        &mut self,
+        _expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        _value: &'a SymbolicExpression,
+    ) -> bool {
+        self.record_write(name);
+        true
+    }
+
+    fn visit_map_get(
+        &mut self,
+        _expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        _key: &HashMap<Option<&'a ClarityName>, &'a SymbolicExpression>,
+    ) -> bool {
+        self.record_read(name);
+        true
+    }
+
+    fn visit_map_set(
+        &mut self,
+        _expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        _key: &HashMap<Option<&'a ClarityName>, &'a SymbolicExpression>,
+        _value: &HashMap<Option<&'a ClarityName>, &'a SymbolicExpression>,
+    ) -> bool {
+        self.record_write(name);
+        true
+    }
+
+    fn visit_map_insert(
+        &mut self,
+        _expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        _key: &HashMap<Option<&'a ClarityName>, &'a SymbolicExpression>,
+        _value: &HashMap<Option<&'a ClarityName>, &'a SymbolicExpression>,
+    ) -> bool {
+        self.record_write(name);
+        true
+    }
+
+    fn visit_map_delete(
+        &mut self,
+        _expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        _key: &HashMap<Option<&'a ClarityName>, &'a SymbolicExpression>,
+    ) -> bool {
+        self.record_write(name);
+        true
+    }
+
+    fn visit_call_user_defined(
+        &mut self,
+        _expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        _args: &'a [SymbolicExpression],
+    ) -> bool {
+        self.record_call(name);
+        true
+    }
+
+    fn visit_map(
+        &mut self,
+        _expr: &'a SymbolicExpression,
+        func: &'a ClarityName,
+        _sequences: &'a [SymbolicExpression],
+    ) -> bool {
+        self.record_applied_function(func);
+        true
+    }
+
+    fn visit_fold(
+        &mut self,
+        _expr: &'a SymbolicExpression,
+        func: &'a ClarityName,
+        _sequence: &'a SymbolicExpression,
+        _initial: &'a SymbolicExpression,
+    ) -> bool {
+        self.record_applied_function(func);
+        true
+    }
+
+    fn visit_filter(
+        &mut self,
+        _expr: &'a SymbolicExpression,
+        func: &'a ClarityName,
+        _sequence: &'a SymbolicExpression,
+    ) -> bool {
+        self.record_applied_function(func);
+        true
+    }
+
+    fn visit_static_contract_call(
+        &mut self,
+        _expr: &'a SymbolicExpression,
+        contract_identifier: &'a QualifiedContractIdentifier,
+        function_name: &'a ClarityName,
+        _args: &'a [SymbolicExpression],
+    ) -> bool {
+        if let Some(function) = self.current.as_mut() {
+            function
+                .contract_calls
+                .insert(format!("{}.{}", contract_identifier, function_name));
+        }
+        true
+    }
+
+    fn visit_dynamic_contract_call(
+        &mut self,
+        _expr: &'a SymbolicExpression,
+        trait_ref: &'a SymbolicExpression,
+        function_name: &'a ClarityName,
+        _args: &'a [SymbolicExpression],
+    ) -> bool {
+        if let (Some(function), Some(trait_ref)) = (self.current.as_mut(), trait_ref.match_atom()) {
+            function
+                .contract_calls
+                .insert(format!("<{}>.{}", trait_ref, function_name));
+        }
+        true
+    }
+
+    fn visit_stx_burn(
+        &mut self,
+        _expr: &'a SymbolicExpression,
+        _amount: &'a SymbolicExpression,
+        _sender: &'a SymbolicExpression,
+    ) -> bool {
+        self.record_asset_operation("stx-burn?", None);
+        true
+    }
+
+    fn visit_stx_transfer(
+        &mut self,
+        _expr: &'a SymbolicExpression,
+        _amount: &'a SymbolicExpression,
+        _sender: &'a SymbolicExpression,
+        _recipient: &'a SymbolicExpression,
+        _memo: Option<&'a SymbolicExpression>,
+    ) -> bool {
+        self.record_asset_operation("stx-transfer?", None);
+        true
+    }
+
+    fn visit_ft_burn(
+        &mut self,
+        _expr: &'a SymbolicExpression,
+        token: &'a ClarityName,
+        _amount: &'a SymbolicExpression,
+        _sender: &'a SymbolicExpression,
+    ) -> bool {
+        self.record_asset_operation("ft-burn?", Some(token));
+        true
+    }
+
+    fn visit_ft_transfer(
+        &mut self,
+        _expr: &'a SymbolicExpression,
+        token: &'a ClarityName,
+        _amount: &'a SymbolicExpression,
+        _sender: &'a SymbolicExpression,
+        _recipient: &'a SymbolicExpression,
+    ) -> bool {
+        self.record_asset_operation("ft-transfer?", Some(token));
+        true
+    }
+
+    fn visit_ft_mint(
+        &mut self,
+        _expr: &'a SymbolicExpression,
+        token: &'a ClarityName,
+        _amount: &'a SymbolicExpression,
+        _recipient: &'a SymbolicExpression,
+    ) -> bool {
+        self.record_asset_operation("ft-mint?", Some(token));
+        true
+    }
+
+    fn visit_nft_burn(
+        &mut self,
+        _expr: &'a SymbolicExpression,
+        token: &'a ClarityName,
+        _identifier: &'a SymbolicExpression,
+        _sender: &'a SymbolicExpression,
+    ) -> bool {
+        self.record_asset_operation("nft-burn?", Some(token));
+        true
+    }
+
+    fn visit_nft_transfer(
+        &mut self,
+        _expr: &'a SymbolicExpression,
+        token: &'a ClarityName,
+        _identifier: &'a SymbolicExpression,
+        _sender: &'a SymbolicExpression,
+        _recipient: &'a SymbolicExpression,
+    ) -> bool {
+        self.record_asset_operation("nft-transfer?", Some(token));
+        true
+    }
+
+    fn visit_nft_mint(
+        &mut self,
+        _expr: &'a SymbolicExpression,
+        token: &'a ClarityName,
+        _identifier: &'a SymbolicExpression,
+        _recipient: &'a SymbolicExpression,
+    ) -> bool {
+        self.record_asset_operation("nft-mint?", Some(token));
+        true
+    }
+}
+
+/// Build the audit model of a contract from its expressions.
+pub fn build_audit(expressions: &[SymbolicExpression]) -> ContractAudit {
+    AuditBuilder::new().run(expressions)
+}
+
+pub fn audit_report(contract_analysis: &ContractAnalysis) -> ContractAudit {
+    build_audit(&contract_analysis.expressions)
+}
+
+fn format_set<T: fmt::Display>(set: &BTreeSet<T>) -> String {
+    if set.is_empty() {
+        return "-".to_string();
+    }
+    set.iter()
+        .map(|item| format!("`{}`", item))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Render the audit reports of several contracts, indexed by contract id, as
+/// a markdown document.
+pub fn audit_reports_to_markdown(reports: &BTreeMap<String, ContractAudit>) -> String {
+    let mut markdown = String::from("# Audit report\n");
+    for (contract_id, report) in reports.iter() {
+        markdown.push_str(&format!("\n## {}\n", contract_id));
+
+        markdown.push_str("\n### Data access\n\n");
+        markdown.push_str("| Function | Visibility | Reads | Writes |\n");
+        markdown.push_str("| --- | --- | --- | --- |\n");
+        for function in report.functions.iter() {
+            markdown.push_str(&format!(
+                "| `{}` | {} | {} | {} |\n",
+                function.function,
+                function.visibility,
+                format_set(&function.reads),
+                format_set(&function.writes)
+            ));
+        }
+
+        markdown.push_str("\n### Calls\n\n");
+        markdown.push_str("| Function | Calls | Contract calls |\n");
+        markdown.push_str("| --- | --- | --- |\n");
+        for function in report.functions.iter() {
+            markdown.push_str(&format!(
+                "| `{}` | {} | {} |\n",
+                function.function,
+                format_set(&function.calls),
+                format_set(&function.contract_calls)
+            ));
+        }
+
+        markdown.push_str("\n### Asset operations\n\n");
+        markdown.push_str("| Public function | Reachable asset operations |\n");
+        markdown.push_str("| --- | --- |\n");
+        for function in report
+            .functions
+            .iter()
+            .filter(|function| function.visibility == Visibility::Public)
+        {
+            markdown.push_str(&format!(
+                "| `{}` | {} |\n",
+                function.function,
+                format_set(&function.reachable_asset_operations)
+            ));
+        }
+    }
+    markdown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repl::session::Session;
+    use crate::repl::SessionSettings;
+    use clarity::vm::EvaluationResult;
+
+    fn audit_snippet(snippet: &str) -> ContractAudit {
+        let mut session = Session::new(SessionSettings::default());
+        match session.formatted_interpretation(
+            snippet.to_string(),
+            Some("checker".to_string()),
+            false,
+            None,
+            None,
+        ) {
+            Ok((_, result)) => match result.result {
+                EvaluationResult::Contract(contract_result) => {
+                    audit_report(&contract_result.contract.analysis)
+                }
+                _ => panic!("Expected contract result"),
+            },
+            _ => panic!("Expected successful interpretation"),
+        }
+    }
+
+    #[test]
+    fn data_access_and_calls() {
+        let audit = audit_snippet(
+            "
+(define-data-var counter uint u0)
+(define-map balances principal uint)
+(define-private (increment)
+    (var-set counter (+ (var-get counter) u1))
+)
+(define-read-only (get-balance (who principal))
+    (default-to u0 (map-get? balances who))
+)
+(define-public (deposit (amount uint))
+    (begin
+        (increment)
+        (map-set balances tx-sender (+ (get-balance tx-sender) amount))
+        (ok true)
+    )
+)
+",
+        );
+        assert_eq!(audit.functions.len(), 3);
+
+        let increment = audit.get_function("increment").unwrap();
+        assert_eq!(increment.visibility, Visibility::Private);
+        assert!(increment.reads.contains("counter"));
+        assert!(increment.writes.contains("counter"));
+
+        let deposit = audit.get_function("deposit").unwrap();
+        assert!(deposit.reads.is_empty());
+        assert_eq!(
+            deposit.writes.iter().cloned().collect::<Vec<_>>(),
+            vec!["balances".to_string()]
+        );
+        assert_eq!(
+            deposit.calls.iter().cloned().collect::<Vec<_>>(),
+            vec!["get-balance".to_string(), "increment".to_string()]
+        );
+        assert_eq!(
+            deposit.location,
+            Location {
+                line: 10,
+                column: 17
+            }
+        );
+    }
+
+    #[test]
+    fn reachable_asset_operations() {
+        let audit = audit_snippet(
+            "
+(define-fungible-token token)
+(define-private (pay (amount uint) (recipient principal))
+    (begin
+        (try! (ft-transfer? token amount tx-sender recipient))
+        (stx-transfer? amount tx-sender recipient)
+    )
+)
+(define-private (reward (recipient principal))
+    (pay u10 recipient)
+)
+(define-public (claim)
+    (reward tx-sender)
+)
+(define-public (mint (amount uint))
+    (ft-mint? token amount tx-sender)
+)
+",
+        );
+        let claim = audit.get_function("claim").unwrap();
+        assert!(claim.asset_operations.is_empty());
+        assert_eq!(
+            claim
+                .reachable_asset_operations
+                .iter()
+                .map(|op| op.to_string())
+                .collect::<Vec<_>>(),
+            vec!["ft-transfer? token", "stx-transfer?"]
+        );
+
+        let mint = audit.get_function("mint").unwrap();
+        assert_eq!(
+            mint.reachable_asset_operations
+                .iter()
+                .map(|op| op.to_string())
+                .collect::<Vec<_>>(),
+            vec!["ft-mint? token"]
+        );
+
+        // Only public functions are entrypoints
+        let reward = audit.get_function("reward").unwrap();
+        assert!(reward.reachable_asset_operations.is_empty());
+
+        let mut reports = BTreeMap::new();
+        reports.insert("checker".to_string(), audit.clone());
+        let markdown = audit_reports_to_markdown(&reports);
+        assert!(markdown.contains("| `claim` | `ft-transfer? token`, `stx-transfer?` |"));
+    }
+
+    #[test]
+    fn functions_applied_to_sequences() {
+        let audit = audit_snippet(
+            "
+(define-private (transfer-one (recipient principal) (result (response bool uint)))
+    (begin
+        (try! result)
+        (stx-transfer? u10 tx-sender recipient)
+    )
+)
+(define-private (is-valid (recipient principal))
+    (not (is-eq recipient tx-sender))
+)
+(define-private (double (amount uint))
+    (* amount u2)
+)
+(define-public (send-many (recipients (list 10 principal)))
+    (fold transfer-one (filter is-valid recipients) (ok true))
+)
+(define-read-only (total (amounts (list 10 uint)))
+    (fold + (map double amounts) u0)
+)
+",
+        );
+        let send_many = audit.get_function("send-many").unwrap();
+        assert_eq!(
+            send_many.calls.iter().cloned().collect::<Vec<_>>(),
+            vec!["is-valid".to_string(), "transfer-one".to_string()]
+        );
+        assert_eq!(
+            send_many
+                .reachable_asset_operations
+                .iter()
+                .map(|op| op.to_string())
+                .collect::<Vec<_>>(),
+            vec!["stx-transfer?"]
+        );
+
+        // Native functions are not recorded as calls
+        let total = audit.get_function("total").unwrap();
+        assert_eq!(
+            total.calls.iter().cloned().collect::<Vec<_>>(),
+            vec!["double".to_string()]
+        );
+    }
+}
@@ -0,0 +1,227 @@
+use clarity::util::hash::Sha256Sum;
+use clarity::vm::diagnostic::{Diagnostic, Level};
+use clarity::vm::representations::Span;
+use clarity::vm::types::QualifiedContractIdentifier;
+use std::collections::HashMap;
+
+pub const BASELINE_FILE_NAME: &str = ".clarinet-baseline.json";
+
+/// Diagnostics that were present when the baseline was written, and should
+/// not be reported again.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Baseline {
+    pub findings: Vec<Finding>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Finding {
+    pub contract: String,
+    pub message: String,
+    pub fingerprint: String,
+}
+
+impl Baseline {
+    pub fn new() -> Baseline {
+        Baseline::default()
+    }
+
+    pub fn from_json(content: &str) -> Result<Baseline, String> {
+        serde_json::from_str(content).map_err(|e| format!("unable to parse baseline: {}", e))
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("unable to serialize baseline")
+    }
+
+    /// Record the diagnostics of a contract in the baseline, whatever their
+    /// level, since a lint level can promote or demote them.
+    pub fn add_diagnostics(
+        &mut self,
+        contract_id: &QualifiedContractIdentifier,
+        diagnostics: &[Diagnostic],
+        source: &str,
+    ) {
+        for (diagnostic, _) in attach_notes(diagnostics) {
+            self.findings.push(Finding {
+                contract: contract_id.name.to_string(),
+                message: diagnostic.message.clone(),
+                fingerprint: fingerprint(diagnostic, source),
+            });
+        }
+    }
+
+    /// Remove the diagnostics recorded in the baseline, along with their
+    /// notes, from the diagnostics of a contract.
+    pub fn filter_diagnostics(
+        &self,
+        contract_id: &QualifiedContractIdentifier,
+        diagnostics: Vec<Diagnostic>,
+        source: &str,
+    ) -> Vec<Diagnostic> {
+        // A finding can occur several times in the same contract, each entry
+        // of the baseline suppresses one occurrence.
+        let mut remaining: HashMap<&str, usize> = HashMap::new();
+        for finding in &self.findings {
+            if finding.contract == contract_id.name.as_str() {
+                *remaining.entry(&finding.fingerprint).or_insert(0) += 1;
+            }
+        }
+
+        let mut filtered = vec![];
+        for (diagnostic, notes) in attach_notes(&diagnostics) {
+            match remaining.get_mut(fingerprint(diagnostic, source).as_str()) {
+                Some(count) if *count > 0 => *count -= 1,
+                _ => {
+                    filtered.push(diagnostic.clone());
+                    filtered.extend_from_slice(notes);
+                }
+            }
+        }
+        filtered
+    }
+}
+
+/// Group the diagnostics with the notes attached to them. The passes report
+/// each diagnostic followed by its notes, so a note is attached to the closest
+/// diagnostic before it. A note with no diagnostic before it stands alone.
+fn attach_notes(diagnostics: &[Diagnostic]) -> Vec<(&Diagnostic, &[Diagnostic])> {
+    let mut groups = vec![];
+    let mut start = 0;
+    while start < diagnostics.len() {
+        let end = diagnostics[start + 1..]
+            .iter()
+            .position(|diagnostic| diagnostic.level != Level::Note)
+            .map_or(diagnostics.len(), |i| start + 1 + i);
+        groups.push((&diagnostics[start], &diagnostics[start + 1..end]));
+        start = end;
+    }
+    groups
+}
+
+/// Compute a fingerprint of a diagnostic from its message and the code it
+/// points to, ignoring whitespace, so that it does not change when code is
+/// added or moved around it.
+pub fn fingerprint(diagnostic: &Diagnostic, source: &str) -> String {
+    let mut data = diagnostic.message.clone();
+    if let Some(span) = diagnostic.spans.first() {
+        data.push('\n');
+        data.push_str(&span_text(span, source));
+    }
+    Sha256Sum::from_data(data.as_bytes()).to_hex()
+}
+
+// Extract the code covered by `span`, with whitespace normalized.
+fn span_text(span: &Span, source: &str) -> String {
+    if span.start_line == 0 {
+        return String::new();
+    }
+    let mut text = vec![];
+    for (i, line) in source.lines().enumerate() {
+        let line_number = i as u32 + 1;
+        if line_number < span.start_line || line_number > span.end_line {
+            continue;
+        }
+        let start = if line_number == span.start_line {
+            span.start_column.saturating_sub(1) as usize
+        } else {
+            0
+        };
+        let end = if line_number == span.end_line {
+            span.end_column as usize
+        } else {
+            usize::MAX
+        };
+        let part: String = line
+            .chars()
+            .skip(start)
+            .take(end.saturating_sub(start))
+            .collect();
+        text.push(part);
+    }
+    text.join(" ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn warning(message: &str, line: u32, start_column: u32, end_column: u32) -> Diagnostic {
+        Diagnostic {
+            level: Level::Warning,
+            message: message.to_string(),
+            spans: vec![Span {
+                start_line: line,
+                start_column,
+                end_line: line,
+                end_column,
+            }],
+            suggestion: None,
+        }
+    }
+
+    #[test]
+    fn fingerprint_ignores_line_numbers() {
+        let source = "(define-data-var x uint u0)\n(var-set x (- u0 u1))";
+        let moved = "\n\n(define-data-var x uint u0)\n  (var-set x (- u0 u1))";
+        assert_eq!(
+            fingerprint(&warning("subtraction may underflow", 2, 12, 20), source),
+            fingerprint(&warning("subtraction may underflow", 4, 14, 22), moved)
+        );
+    }
+
+    #[test]
+    fn filter_baselined_warnings() {
+        let contract_id = QualifiedContractIdentifier::transient();
+        let source = "(- a b)\n(- a b)\n(/ a b)";
+        let old = vec![warning("subtraction may underflow", 1, 1, 7)];
+        let mut baseline = Baseline::new();
+        baseline.add_diagnostics(&contract_id, &old, source);
+
+        let mut note = warning("note", 3, 1, 7);
+        note.level = Level::Note;
+        let new = vec![
+            warning("subtraction may underflow", 1, 1, 7),
+            note,
+            warning("subtraction may underflow", 2, 1, 7),
+            warning("possible division by zero", 3, 1, 7),
+        ];
+        let filtered = baseline.filter_diagnostics(&contract_id, new, source);
+        assert_eq!(filtered.len(), 2);
+        assert_eq!(filtered[0].spans[0].start_line, 2);
+        assert_eq!(filtered[1].message, "possible division by zero");
+    }
+
+    #[test]
+    fn filter_baselined_lints_whatever_their_level() {
+        let contract_id = QualifiedContractIdentifier::transient();
+        let source = "(- a b)\n(/ a b)";
+        let mut promoted = warning("subtraction may underflow", 1, 1, 7);
+        promoted.level = Level::Error;
+        let mut demoted = warning("possible division by zero", 2, 1, 7);
+        demoted.level = Level::Note;
+        let mut baseline = Baseline::new();
+        baseline.add_diagnostics(&contract_id, &[promoted.clone(), demoted.clone()], source);
+        assert_eq!(baseline.findings.len(), 1);
+
+        // The note following the error is attached to it, and suppressed with it
+        let filtered = baseline.filter_diagnostics(&contract_id, vec![promoted, demoted], source);
+        assert!(filtered.is_empty());
+
+        // A note with no diagnostic before it is a finding of its own
+        let mut note = warning("possible division by zero", 2, 1, 7);
+        note.level = Level::Note;
+        let mut baseline = Baseline::new();
+        baseline.add_diagnostics(&contract_id, &[note.clone()], source);
+        assert_eq!(baseline.findings.len(), 1);
+        let filtered = baseline.filter_diagnostics(
+            &contract_id,
+            vec![note, warning("subtraction may underflow", 1, 1, 7)],
+            source,
+        );
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].message, "subtraction may underflow");
+    }
+}
@@ -1,6 +1,6 @@
-use crate::analysis::annotation::Annotation;
+use crate::analysis::annotation::{Annotation, WarningKind};
 use crate::analysis::ast_visitor::{traverse, ASTVisitor, TypedVar};
-use crate::analysis::{AnalysisPass, AnalysisResult, Settings};
+use crate::analysis::{AnalysisPass, AnalysisResult, PassResult, Settings};
 use clarity::vm::analysis::analysis_db::AnalysisDatabase;
 pub use clarity::vm::analysis::types::ContractAnalysis;
 use clarity::vm::ast::ContractAST;
@@ -29,14 +29,31 @@ impl<'a> CallChecker<'a> {
         }
     }
 
-    fn run(mut self, contract_analysis: &'a ContractAnalysis) -> AnalysisResult {
+    fn run(
+        mut self,
+        contract_analysis: &'a ContractAnalysis,
+        annotations: &Vec<Annotation>,
+        settings: &Settings,
+    ) -> AnalysisResult {
         traverse(&mut self, &contract_analysis.expressions);
         self.check_user_calls();
 
-        if self.diagnostics.len() > 0 {
-            Err(self.diagnostics)
+        let diagnostics: Vec<Diagnostic> = self
+            .diagnostics
+            .into_iter()
+            .flat_map(|diagnostic| {
+                settings.apply_lint_level(
+                    contract_analysis,
+                    annotations,
+                    WarningKind::IncorrectArgumentCount,
+                    vec![diagnostic],
+                )
+            })
+            .collect();
+        if diagnostics.iter().any(|d| d.level == Level::Error) {
+            Err(diagnostics)
         } else {
-            Ok(vec![])
+            Ok(diagnostics)
         }
     }
 
@@ -196,15 +213,17 @@ impl AnalysisPass for CallChecker<'_> {
         analysis_db: &mut AnalysisDatabase,
         annotations: &Vec<Annotation>,
         settings: &Settings,
-    ) -> AnalysisResult {
+    ) -> PassResult {
         let tc = CallChecker::new();
-        tc.run(contract_analysis)
+        tc.run(contract_analysis, annotations, settings)
+            .map(|diagnostics| (diagnostics, vec![]))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::analysis::{LintLevel, LintSettings};
     use crate::repl::session::Session;
     use crate::repl::SessionSettings;
 
@@ -316,6 +335,47 @@ mod tests {
         };
     }
 
+    #[test]
+    fn lint_level_warning() {
+        let mut settings = SessionSettings::default();
+        settings.repl_settings.analysis.lints.insert(
+            "incorrect_argument_count".to_string(),
+            LintSettings {
+                level: LintLevel::Warning,
+            },
+        );
+        let mut session = Session::new(settings);
+        let snippet = "
+(define-private (foo (amount uint))
+    (ok amount)
+)
+
+(define-public (main)
+    (ok (foo u1 u2))
+)
+"
+        .to_string();
+        match session.formatted_interpretation(
+            snippet,
+            Some("checker".to_string()),
+            false,
+            None,
+            None,
+        ) {
+            Ok((output, result)) => {
+                assert_eq!(result.diagnostics.len(), 1);
+                assert_eq!(
+                    output[0],
+                    format!(
+                        "checker:7:9: {}: incorrect number of arguments in call to 'foo' (expected 1 got 2)",
+                        yellow!("warning")
+                    )
+                );
+            }
+            _ => panic!("Expected successful interpretation"),
+        };
+    }
+
     #[test]
     fn correct_call() {
         let mut session = Session::new(SessionSettings::default());
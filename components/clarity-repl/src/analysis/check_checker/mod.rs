@@ -1,6 +1,7 @@
 use crate::analysis::annotation::{Annotation, AnnotationKind, WarningKind};
 use crate::analysis::ast_visitor::{traverse, ASTVisitor, TypedVar};
-use crate::analysis::{self, AnalysisPass, AnalysisResult};
+use crate::analysis::fix::{DiagnosticFix, Fix};
+use crate::analysis::{self, AnalysisPass, PassResult};
 use clarity::vm::analysis::analysis_db::AnalysisDatabase;
 use clarity::vm::analysis::types::ContractAnalysis;
 use clarity::vm::diagnostic::{DiagnosableError, Diagnostic, Level};
@@ -24,6 +25,8 @@ pub struct Settings {
     trusted_caller: bool,
     // Allow filters in callee to filter caller
     callee_filter: bool,
+    // Report unchecked data reaching transfers inside `as-contract` separately
+    unchecked_as_contract: bool,
 }
 
 #[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
@@ -36,6 +39,8 @@ pub struct SettingsFile {
     trusted_caller: Option<bool>,
     // Allow filters in callee to filter caller
     callee_filter: Option<bool>,
+    // Report unchecked data reaching transfers inside `as-contract` separately
+    unchecked_as_contract: Option<bool>,
 }
 
 impl Default for Settings {
@@ -45,6 +50,7 @@ impl Default for Settings {
             trusted_sender: false,
             trusted_caller: false,
             callee_filter: false,
+            unchecked_as_contract: false,
         }
     }
 }
@@ -57,6 +63,7 @@ impl From<SettingsFile> for Settings {
                 trusted_sender: false,
                 trusted_caller: false,
                 callee_filter: false,
+                unchecked_as_contract: from_file.unchecked_as_contract.unwrap_or(false),
             }
         } else {
             Settings {
@@ -64,6 +71,7 @@ impl From<SettingsFile> for Settings {
                 trusted_sender: from_file.trusted_sender.unwrap_or(false),
                 trusted_caller: from_file.trusted_caller.unwrap_or(false),
                 callee_filter: from_file.callee_filter.unwrap_or(false),
+                unchecked_as_contract: from_file.unchecked_as_contract.unwrap_or(false),
             }
         }
     }
@@ -112,8 +120,8 @@ pub struct CheckChecker<'a, 'b> {
     settings: Settings,
     taint_sources: HashMap<Node<'a>, TaintSource<'a>>,
     tainted_nodes: HashMap<Node<'a>, TaintedNode<'a>>,
-    // Map expression ID to a generated diagnostic
-    diagnostics: HashMap<u64, Vec<Diagnostic>>,
+    // Map expression ID to a generated diagnostic, and the kind of warning
+    diagnostics: HashMap<u64, (WarningKind, Vec<Diagnostic>)>,
     annotations: &'a Vec<Annotation>,
     active_annotation: Option<usize>,
     // Record all public functions defined
@@ -123,6 +131,9 @@ pub struct CheckChecker<'a, 'b> {
     user_funcs: HashMap<&'a ClarityName, FunctionInfo>,
     // True if currently traversing within an `as-contract` node
     in_as_contract: bool,
+    // True if the current `as-contract` node is annotated with
+    // `allow(unchecked_as_contract)`
+    allow_as_contract: bool,
 }
 
 impl<'a, 'b> CheckChecker<'a, 'b> {
@@ -142,19 +153,41 @@ impl<'a, 'b> CheckChecker<'a, 'b> {
             public_funcs: HashSet::new(),
             user_funcs: HashMap::new(),
             in_as_contract: false,
+            allow_as_contract: false,
         }
     }
 
-    fn run(mut self, contract_analysis: &'a ContractAnalysis) -> AnalysisResult {
+    fn run(
+        mut self,
+        contract_analysis: &'a ContractAnalysis,
+        settings: &analysis::Settings,
+    ) -> PassResult {
         // First traverse the entire AST
         traverse(&mut self, &contract_analysis.expressions);
 
         // Collect all of the vecs of diagnostics into a vector
-        let mut diagnostics: Vec<Vec<Diagnostic>> = self.diagnostics.into_values().collect();
+        let mut diagnostics: Vec<(WarningKind, Vec<Diagnostic>)> =
+            self.diagnostics.into_values().collect();
         // Order the sets by the span of the error (the first diagnostic)
-        diagnostics.sort_by(|a, b| a[0].spans[0].cmp(&b[0].spans[0]));
-        // Then flatten into one vector
-        Ok(diagnostics.into_iter().flatten().collect())
+        diagnostics.sort_by(|(_, a), (_, b)| a[0].spans[0].cmp(&b[0].spans[0]));
+        // Then apply the configured lint level and flatten into one vector,
+        // offering to allow each remaining warning
+        let annotations = self.annotations;
+        let mut fixes = vec![];
+        let diagnostics = diagnostics
+            .into_iter()
+            .flat_map(|(kind, group)| {
+                let group = settings.apply_lint_level(contract_analysis, annotations, kind, group);
+                if let Some(diagnostic) = group.first() {
+                    fixes.push(DiagnosticFix {
+                        diagnostic: diagnostic.clone(),
+                        fix: Fix::allow_annotation(kind, &diagnostic.spans[0]),
+                    });
+                }
+                group
+            })
+            .collect();
+        Ok((diagnostics, fixes))
     }
 
     fn add_taint_source(&mut self, node: Node<'a>, span: Span) {
@@ -200,8 +233,24 @@ impl<'a, 'b> CheckChecker<'a, 'b> {
     // If this expression is tainted, add a diagnostic
     fn taint_check(&mut self, expr: &'a SymbolicExpression) {
         if self.tainted_nodes.contains_key(&Node::Expr(expr.id)) {
+            let kind = WarningKind::UncheckedData;
+            self.diagnostics
+                .insert(expr.id, (kind, self.generate_diagnostics(expr, kind)));
+        }
+    }
+
+    // If this argument of a transfer is tainted, add a diagnostic, reporting
+    // separately the transfers of the contract's assets inside `as-contract`
+    fn transfer_taint_check(&mut self, expr: &'a SymbolicExpression) {
+        if !(self.settings.unchecked_as_contract && self.in_as_contract) {
+            self.taint_check(expr);
+        } else if self.tainted_nodes.contains_key(&Node::Expr(expr.id))
+            && !self.allow_as_contract
+            && !self.allow_unchecked_as_contract()
+        {
+            let kind = WarningKind::UncheckedAsContract;
             self.diagnostics
-                .insert(expr.id, self.generate_diagnostics(expr));
+                .insert(expr.id, (kind, self.generate_diagnostics(expr, kind)));
         }
     }
 
@@ -275,6 +324,18 @@ impl<'a, 'b> CheckChecker<'a, 'b> {
         false
     }
 
+    // Check if the expression is annotated with `allow(unchecked_as_contract)`
+    fn allow_unchecked_as_contract(&self) -> bool {
+        if let Some(idx) = self.active_annotation {
+            let annotation = &self.annotations[idx];
+            return match annotation.kind {
+                AnnotationKind::Allow(WarningKind::UncheckedAsContract) => true,
+                _ => false,
+            };
+        }
+        false
+    }
+
     // Check if the expression is annotated with `allow(unchecked_params)`
     fn allow_unchecked_params(&self) -> bool {
         if let Some(idx) = self.active_annotation {
@@ -304,18 +365,28 @@ impl<'a, 'b> CheckChecker<'a, 'b> {
         }
     }
 
-    fn generate_diagnostics(&self, expr: &SymbolicExpression) -> Vec<Diagnostic> {
+    fn generate_diagnostics(
+        &self,
+        expr: &SymbolicExpression,
+        kind: WarningKind,
+    ) -> Vec<Diagnostic> {
         let mut diagnostics: Vec<Diagnostic> = Vec::new();
+        let message = match kind {
+            WarningKind::UncheckedAsContract => {
+                "use of potentially unchecked data in a transfer of the contract's assets (inside `as-contract`)"
+            }
+            _ => "use of potentially unchecked data",
+        };
         let diagnostic = Diagnostic {
             level: Level::Warning,
-            message: "use of potentially unchecked data".to_string(),
+            message: message.to_string(),
             spans: vec![expr.span.clone()],
             suggestion: None,
         };
         diagnostics.push(diagnostic);
 
-        let tainted = &self.tainted_nodes[&Node::Expr(expr.id)];
         // Add a note for each source, ordered by span
+        let tainted = &self.tainted_nodes[&Node::Expr(expr.id)];
         let mut source_spans = vec![];
         for source in &tainted.sources {
             let span = self.taint_sources[source].span.clone();
@@ -533,9 +604,12 @@ impl<'a> ASTVisitor<'a> for CheckChecker<'a, '_> {
         expr: &'a SymbolicExpression,
         inner: &'a SymbolicExpression,
     ) -> bool {
+        let allow_as_contract = self.allow_as_contract;
+        self.allow_as_contract = allow_as_contract || self.allow_unchecked_as_contract();
         self.in_as_contract = true;
         let res = self.traverse_expr(inner) && self.visit_as_contract(expr, inner);
         self.in_as_contract = false;
+        self.allow_as_contract = allow_as_contract;
         res
     }
 
@@ -622,9 +696,9 @@ impl<'a> ASTVisitor<'a> for CheckChecker<'a, '_> {
         if match_tx_sender(sender) && !self.in_as_contract {
             return true;
         }
-        self.taint_check(amount);
-        self.taint_check(sender);
-        self.taint_check(recipient);
+        self.transfer_taint_check(amount);
+        self.transfer_taint_check(sender);
+        self.transfer_taint_check(recipient);
         true
     }
 
@@ -658,9 +732,9 @@ impl<'a> ASTVisitor<'a> for CheckChecker<'a, '_> {
         if match_tx_sender(sender) && !self.in_as_contract {
             return true;
         }
-        self.taint_check(amount);
-        self.taint_check(sender);
-        self.taint_check(recipient);
+        self.transfer_taint_check(amount);
+        self.transfer_taint_check(sender);
+        self.transfer_taint_check(recipient);
         true
     }
 
@@ -862,9 +936,9 @@ impl AnalysisPass for CheckChecker<'_, '_> {
         analysis_db: &mut AnalysisDatabase,
         annotations: &Vec<Annotation>,
         settings: &analysis::Settings,
-    ) -> AnalysisResult {
+    ) -> PassResult {
         let checker = CheckChecker::new(analysis_db, annotations, settings.check_checker);
-        checker.run(contract_analysis)
+        checker.run(contract_analysis, settings)
     }
 }
 
@@ -939,6 +1013,39 @@ mod tests {
         };
     }
 
+    #[test]
+    fn unchecked_param_fixes() {
+        let mut settings = SessionSettings::default();
+        settings.repl_settings.analysis.passes = vec![Pass::CheckChecker];
+        let mut session = Session::new(settings);
+        let snippet = "
+(define-public (tainted (amount uint))
+    (stx-transfer? amount (as-contract tx-sender) tx-sender)
+)
+"
+        .to_string();
+        match session.formatted_interpretation(
+            snippet,
+            Some("checker".to_string()),
+            false,
+            None,
+            None,
+        ) {
+            Ok((_, result)) => {
+                // The fixes are returned apart from the diagnostics
+                assert_eq!(result.diagnostics[0].suggestion, None);
+                let fixes = session.interpreter.fixes.values().next().unwrap();
+                let titles: Vec<&str> =
+                    crate::analysis::fix::fixes_for(fixes, &result.diagnostics[0])
+                        .iter()
+                        .map(|fix| fix.title.as_str())
+                        .collect();
+                assert_eq!(titles, vec!["add `#[allow(unchecked_data)]` annotation"]);
+            }
+            _ => panic!("Expected successful interpretation"),
+        };
+    }
+
     #[test]
     fn expr_tainted() {
         let mut settings = SessionSettings::default();
@@ -3816,4 +3923,93 @@ mod tests {
             _ => panic!("Expected successful interpretation"),
         };
     }
+
+    #[test]
+    fn unchecked_as_contract() {
+        let mut settings = SessionSettings::default();
+        settings.repl_settings.analysis.passes = vec![Pass::CheckChecker];
+        settings
+            .repl_settings
+            .analysis
+            .check_checker
+            .unchecked_as_contract = true;
+        let mut session = Session::new(settings);
+        let snippet = "
+(define-public (withdraw (amount uint) (recipient principal))
+    (as-contract (stx-transfer? amount tx-sender recipient))
+)
+"
+        .to_string();
+        match session.formatted_interpretation(
+            snippet,
+            Some("checker".to_string()),
+            false,
+            None,
+            None,
+        ) {
+            Ok((output, _)) => {
+                assert_eq!(output.len(), 12);
+                assert_eq!(
+                    output[0],
+                    format!(
+                        "checker:3:33: {}: use of potentially unchecked data in a transfer of the contract's assets (inside `as-contract`)",
+                        yellow!("warning")
+                    )
+                );
+                assert_eq!(
+                    output[3],
+                    format!(
+                        "checker:2:27: {}: source of untrusted input here",
+                        blue!("note")
+                    )
+                );
+                assert_eq!(
+                    output[6],
+                    format!(
+                        "checker:3:50: {}: use of potentially unchecked data in a transfer of the contract's assets (inside `as-contract`)",
+                        yellow!("warning")
+                    )
+                );
+                assert_eq!(
+                    output[9],
+                    format!(
+                        "checker:2:41: {}: source of untrusted input here",
+                        blue!("note")
+                    )
+                );
+            }
+            _ => panic!("Expected successful interpretation"),
+        };
+    }
+
+    #[test]
+    fn allow_unchecked_as_contract() {
+        let mut settings = SessionSettings::default();
+        settings.repl_settings.analysis.passes = vec![Pass::CheckChecker];
+        settings
+            .repl_settings
+            .analysis
+            .check_checker
+            .unchecked_as_contract = true;
+        let mut session = Session::new(settings);
+        let snippet = "
+(define-public (withdraw (amount uint) (recipient principal))
+    ;; #[allow(unchecked_as_contract)]
+    (as-contract (stx-transfer? amount tx-sender recipient))
+)
+"
+        .to_string();
+        match session.formatted_interpretation(
+            snippet,
+            Some("checker".to_string()),
+            false,
+            None,
+            None,
+        ) {
+            Ok((_, result)) => {
+                assert_eq!(result.diagnostics.len(), 0);
+            }
+            _ => panic!("Expected successful interpretation"),
+        };
+    }
 }
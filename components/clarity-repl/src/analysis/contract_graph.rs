@@ -0,0 +1,501 @@
+use crate::analysis::ast_visitor::{traverse, ASTVisitor};
+use clarity::vm::ast::ContractAST;
+use clarity::vm::types::{QualifiedContractIdentifier, TraitIdentifier};
+use clarity::vm::{ClarityName, SymbolicExpression};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum EdgeKind {
+    ContractCall,
+    UseTrait,
+    ImplTrait,
+}
+
+impl fmt::Display for EdgeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EdgeKind::ContractCall => write!(f, "contract-call"),
+            EdgeKind::UseTrait => write!(f, "use-trait"),
+            EdgeKind::ImplTrait => write!(f, "impl-trait"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum NodeKind {
+    /// Contract of the project
+    Contract,
+    /// Contract listed in, or discovered from, the requirements
+    Requirement,
+    /// Contract referenced, but whose source is not available
+    External,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Node {
+    pub id: String,
+    pub name: String,
+    pub kind: NodeKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct Location {
+    pub line: u32,
+    pub column: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Edge {
+    pub from: String,
+    pub to: String,
+    pub kind: EdgeKind,
+    /// Locations, in the source of `from`, of the expressions creating the edge
+    pub locations: Vec<Location>,
+    /// Whether this edge is part of a circular dependency
+    pub cyclic: bool,
+}
+
+/// Graph of the dependencies between the contracts of a project.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ContractGraph {
+    pub nodes: Vec<Node>,
+    pub edges: Vec<Edge>,
+    /// Groups of contracts depending on each other
+    pub cycles: Vec<Vec<String>>,
+}
+
+impl ContractGraph {
+    pub fn build(
+        contracts: &HashMap<QualifiedContractIdentifier, ContractAST>,
+        requirements: &BTreeMap<QualifiedContractIdentifier, ContractAST>,
+    ) -> ContractGraph {
+        let mut collector = EdgeCollector {
+            current_contract: None,
+            edges: BTreeMap::new(),
+        };
+        for (contract_identifier, ast) in contracts.iter() {
+            collector.current_contract = Some(contract_identifier);
+            traverse(&mut collector, &ast.expressions);
+        }
+        for (contract_identifier, ast) in requirements.iter() {
+            collector.current_contract = Some(contract_identifier);
+            traverse(&mut collector, &ast.expressions);
+        }
+
+        let mut nodes = BTreeMap::new();
+        for contract_identifier in contracts.keys() {
+            nodes.insert(
+                contract_identifier.to_string(),
+                Node {
+                    id: contract_identifier.to_string(),
+                    name: contract_identifier.name.to_string(),
+                    kind: NodeKind::Contract,
+                },
+            );
+        }
+        for contract_identifier in requirements.keys() {
+            nodes.insert(
+                contract_identifier.to_string(),
+                Node {
+                    id: contract_identifier.to_string(),
+                    name: contract_identifier.name.to_string(),
+                    kind: NodeKind::Requirement,
+                },
+            );
+        }
+        for (_, to, _) in collector.edges.keys() {
+            nodes.entry(to.to_string()).or_insert_with(|| Node {
+                id: to.to_string(),
+                name: to.name.to_string(),
+                kind: NodeKind::External,
+            });
+        }
+
+        let mut edges: Vec<Edge> = collector
+            .edges
+            .into_iter()
+            .map(|((from, to, kind), locations)| Edge {
+                from: from.to_string(),
+                to: to.to_string(),
+                kind,
+                locations,
+                cyclic: false,
+            })
+            .collect();
+
+        let cycles = find_cycles(&nodes, &edges);
+        for edge in edges.iter_mut() {
+            edge.cyclic = edge.from == edge.to
+                || cycles
+                    .iter()
+                    .any(|cycle| cycle.contains(&edge.from) && cycle.contains(&edge.to));
+        }
+
+        ContractGraph {
+            nodes: nodes.into_values().collect(),
+            edges,
+            cycles,
+        }
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph contracts {\n    rankdir=LR;\n");
+        for node in self.nodes.iter() {
+            let style = match node.kind {
+                NodeKind::Contract => "solid",
+                NodeKind::Requirement => "dashed",
+                NodeKind::External => "dotted",
+            };
+            dot.push_str(&format!(
+                "    \"{}\" [label=\"{}\", shape=box, style={}];\n",
+                node.id, node.name, style
+            ));
+        }
+        for edge in self.edges.iter() {
+            let color = if edge.cyclic { ", color=red" } else { "" };
+            dot.push_str(&format!(
+                "    \"{}\" -> \"{}\" [label=\"{}\"{}];\n",
+                edge.from, edge.to, edge.kind, color
+            ));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    pub fn to_mermaid(&self) -> String {
+        let mut mermaid = String::from("graph LR\n");
+        let indices: HashMap<&String, usize> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (&node.id, i))
+            .collect();
+        for (i, node) in self.nodes.iter().enumerate() {
+            match node.kind {
+                NodeKind::Contract => mermaid.push_str(&format!("    n{}[\"{}\"]\n", i, node.name)),
+                NodeKind::Requirement => {
+                    mermaid.push_str(&format!("    n{}([\"{}\"])\n", i, node.name))
+                }
+                NodeKind::External => {
+                    mermaid.push_str(&format!("    n{}{{{{\"{}\"}}}}\n", i, node.name))
+                }
+            }
+        }
+        let mut cyclic_edges = vec![];
+        for (i, edge) in self.edges.iter().enumerate() {
+            mermaid.push_str(&format!(
+                "    n{} -->|{}| n{}\n",
+                indices[&edge.from], edge.kind, indices[&edge.to]
+            ));
+            if edge.cyclic {
+                cyclic_edges.push(i.to_string());
+            }
+        }
+        if !cyclic_edges.is_empty() {
+            mermaid.push_str(&format!(
+                "    linkStyle {} stroke:red\n",
+                cyclic_edges.join(",")
+            ));
+        }
+        mermaid
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("unable to serialize graph")
+    }
+}
+
+struct EdgeCollector<'a> {
+    current_contract: Option<&'a QualifiedContractIdentifier>,
+    edges: BTreeMap<
+        (
+            QualifiedContractIdentifier,
+            QualifiedContractIdentifier,
+            EdgeKind,
+        ),
+        Vec<Location>,
+    >,
+}
+
+impl<'a> EdgeCollector<'a> {
+    fn add_edge(
+        &mut self,
+        expr: &SymbolicExpression,
+        to: &QualifiedContractIdentifier,
+        kind: EdgeKind,
+    ) {
+        // Contracts with names starting with "__" are placeholders
+        if to.name.starts_with("__") {
+            return;
+        }
+        let from = self.current_contract.unwrap().clone();
+        self.edges
+            .entry((from, to.clone(), kind))
+            .or_insert_with(Vec::new)
+            .push(Location {
+                line: expr.span.start_line,
+                column: expr.span.start_column,
+            });
+    }
+}
+
+impl<'a> ASTVisitor<'a> for EdgeCollector<'a> {
+    fn visit_static_contract_call(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        contract_identifier: &'a QualifiedContractIdentifier,
+        _function_name: &'a ClarityName,
+        _args: &'a [SymbolicExpression],
+    ) -> bool {
+        self.add_edge(expr, contract_identifier, EdgeKind::ContractCall);
+        true
+    }
+
+    fn visit_use_trait(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        _name: &'a ClarityName,
+        trait_identifier: &TraitIdentifier,
+    ) -> bool {
+        self.add_edge(
+            expr,
+            &trait_identifier.contract_identifier,
+            EdgeKind::UseTrait,
+        );
+        true
+    }
+
+    fn visit_impl_trait(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        trait_identifier: &TraitIdentifier,
+    ) -> bool {
+        self.add_edge(
+            expr,
+            &trait_identifier.contract_identifier,
+            EdgeKind::ImplTrait,
+        );
+        true
+    }
+}
+
+// Strongly connected components with more than one node, or with a node
+// depending on itself (Tarjan's algorithm)
+fn find_cycles(nodes: &BTreeMap<String, Node>, edges: &[Edge]) -> Vec<Vec<String>> {
+    let mut successors: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    for edge in edges.iter() {
+        successors
+            .entry(edge.from.as_str())
+            .or_default()
+            .insert(edge.to.as_str());
+    }
+
+    struct Tarjan<'a> {
+        successors: BTreeMap<&'a str, BTreeSet<&'a str>>,
+        index: usize,
+        indices: HashMap<&'a str, usize>,
+        lowlinks: HashMap<&'a str, usize>,
+        stack: Vec<&'a str>,
+        cycles: Vec<Vec<String>>,
+    }
+
+    impl<'a> Tarjan<'a> {
+        fn connect(&mut self, node: &'a str) {
+            self.indices.insert(node, self.index);
+            self.lowlinks.insert(node, self.index);
+            self.index += 1;
+            self.stack.push(node);
+
+            let successors: Vec<&'a str> = self
+                .successors
+                .get(node)
+                .map(|set| set.iter().cloned().collect())
+                .unwrap_or_default();
+            for successor in successors {
+                if !self.indices.contains_key(successor) {
+                    self.connect(successor);
+                    let lowlink = self.lowlinks[node].min(self.lowlinks[successor]);
+                    self.lowlinks.insert(node, lowlink);
+                } else if self.stack.contains(&successor) {
+                    let lowlink = self.lowlinks[node].min(self.indices[successor]);
+                    self.lowlinks.insert(node, lowlink);
+                }
+            }
+
+            if self.lowlinks[node] == self.indices[node] {
+                let mut component = vec![];
+                while let Some(member) = self.stack.pop() {
+                    component.push(member.to_string());
+                    if member == node {
+                        break;
+                    }
+                }
+                let self_loop = self
+                    .successors
+                    .get(node)
+                    .map_or(false, |successors| successors.contains(node));
+                if component.len() > 1 || self_loop {
+                    component.sort();
+                    self.cycles.push(component);
+                }
+            }
+        }
+    }
+
+    let mut tarjan = Tarjan {
+        successors,
+        index: 0,
+        indices: HashMap::new(),
+        lowlinks: HashMap::new(),
+        stack: vec![],
+        cycles: vec![],
+    };
+    for node in nodes.keys() {
+        if !tarjan.indices.contains_key(node.as_str()) {
+            tarjan.connect(node.as_str());
+        }
+    }
+    let mut cycles = tarjan.cycles;
+    cycles.sort();
+    cycles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repl::session::Session;
+    use crate::repl::{
+        ClarityCodeSource, ClarityContract, ContractDeployer, SessionSettings,
+        DEFAULT_CLARITY_VERSION, DEFAULT_EPOCH,
+    };
+
+    fn build_ast(
+        session: &Session,
+        snippet: &str,
+        name: &str,
+    ) -> (QualifiedContractIdentifier, ContractAST) {
+        let contract = ClarityContract {
+            code_source: ClarityCodeSource::ContractInMemory(snippet.to_string()),
+            name: name.to_string(),
+            deployer: ContractDeployer::Transient,
+            clarity_version: DEFAULT_CLARITY_VERSION,
+            epoch: DEFAULT_EPOCH,
+        };
+        let (ast, _, _) = session.interpreter.build_ast(&contract);
+        (contract.expect_resolved_contract_identifier(None), ast)
+    }
+
+    #[test]
+    fn edge_kinds() {
+        let session = Session::new(SessionSettings::default());
+        let trait_snippet = "
+(define-trait my-trait
+    ((hello (int) (response int int)))
+)
+";
+        let foo_snippet = "
+(impl-trait .traits.my-trait)
+(define-public (hello (a int))
+    (ok a)
+)
+";
+        let bar_snippet = "
+(use-trait my-trait .traits.my-trait)
+(define-public (call-foo)
+    (contract-call? .foo hello 4)
+)
+(define-public (call-trait (t <my-trait>))
+    (contract-call? t hello 4)
+)
+";
+        let mut contracts = HashMap::new();
+        let (foo_id, ast) = build_ast(&session, foo_snippet, "foo");
+        contracts.insert(foo_id.clone(), ast);
+        let (bar_id, ast) = build_ast(&session, bar_snippet, "bar");
+        contracts.insert(bar_id.clone(), ast);
+        let mut requirements = BTreeMap::new();
+        let (traits_id, ast) = build_ast(&session, trait_snippet, "traits");
+        requirements.insert(traits_id.clone(), ast);
+
+        let graph = ContractGraph::build(&contracts, &requirements);
+        let traits_node = graph
+            .nodes
+            .iter()
+            .find(|node| node.id == traits_id.to_string())
+            .unwrap();
+        assert_eq!(traits_node.kind, NodeKind::Requirement);
+
+        let edges: Vec<(String, String, EdgeKind)> = graph
+            .edges
+            .iter()
+            .map(|edge| (edge.from.clone(), edge.to.clone(), edge.kind))
+            .collect();
+        assert_eq!(edges.len(), 3);
+        assert!(edges.contains(&(
+            foo_id.to_string(),
+            traits_id.to_string(),
+            EdgeKind::ImplTrait
+        )));
+        assert!(edges.contains(&(
+            bar_id.to_string(),
+            traits_id.to_string(),
+            EdgeKind::UseTrait
+        )));
+        assert!(edges.contains(&(
+            bar_id.to_string(),
+            foo_id.to_string(),
+            EdgeKind::ContractCall
+        )));
+        assert!(graph.cycles.is_empty());
+        assert!(graph.to_dot().contains("style=dashed"));
+    }
+
+    #[test]
+    fn cycles() {
+        let session = Session::new(SessionSettings::default());
+        let foo_snippet = "
+(define-public (hello)
+    (contract-call? .bar goodbye)
+)
+";
+        let bar_snippet = "
+(define-public (goodbye)
+    (ok u1)
+)
+(define-public (hello-again)
+    (contract-call? .foo hello)
+)
+";
+        let baz_snippet = "
+(define-public (call-bar)
+    (contract-call? .bar goodbye)
+)
+";
+        let mut contracts = HashMap::new();
+        let (foo_id, ast) = build_ast(&session, foo_snippet, "foo");
+        contracts.insert(foo_id.clone(), ast);
+        let (bar_id, ast) = build_ast(&session, bar_snippet, "bar");
+        contracts.insert(bar_id.clone(), ast);
+        let (baz_id, ast) = build_ast(&session, baz_snippet, "baz");
+        contracts.insert(baz_id.clone(), ast);
+
+        let graph = ContractGraph::build(&contracts, &BTreeMap::new());
+        assert_eq!(
+            graph.cycles,
+            vec![vec![bar_id.to_string(), foo_id.to_string()]]
+        );
+        for edge in graph.edges.iter() {
+            assert_eq!(edge.cyclic, edge.from != baz_id.to_string());
+        }
+        let edge = graph
+            .edges
+            .iter()
+            .find(|edge| edge.from == foo_id.to_string())
+            .unwrap();
+        assert_eq!(edge.locations, vec![Location { line: 3, column: 5 }]);
+        assert!(graph.to_mermaid().contains("linkStyle"));
+    }
+}
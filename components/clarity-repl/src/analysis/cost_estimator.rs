@@ -0,0 +1,799 @@
+use crate::analysis::ast_visitor::definition_name_span;
+use crate::repl::interpreter::BLOCK_LIMIT_MAINNET;
+use clarity::vm::analysis::types::{ContractAnalysis, TypeMap};
+use clarity::vm::costs::cost_functions::ClarityCostFunction;
+use clarity::vm::costs::ExecutionCost;
+use clarity::vm::functions::define::DefineFunctions;
+use clarity::vm::functions::NativeFunctions;
+use clarity::vm::representations::{Span, SymbolicExpressionType};
+use clarity::vm::types::{SequenceSubtype, StringSubtype, TypeSignature};
+use clarity::vm::{ClarityName, SymbolicExpression};
+use std::collections::{HashMap, HashSet};
+
+/// Worst-case execution cost of a function.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CostBound {
+    pub runtime: u64,
+    pub read_count: u64,
+    pub read_length: u64,
+    pub write_count: u64,
+    pub write_length: u64,
+}
+
+impl CostBound {
+    fn runtime(runtime: u64) -> CostBound {
+        CostBound {
+            runtime,
+            ..CostBound::default()
+        }
+    }
+
+    fn add(&mut self, other: &CostBound) {
+        self.runtime = self.runtime.saturating_add(other.runtime);
+        self.read_count = self.read_count.saturating_add(other.read_count);
+        self.read_length = self.read_length.saturating_add(other.read_length);
+        self.write_count = self.write_count.saturating_add(other.write_count);
+        self.write_length = self.write_length.saturating_add(other.write_length);
+    }
+
+    fn max(&mut self, other: &CostBound) {
+        self.runtime = self.runtime.max(other.runtime);
+        self.read_count = self.read_count.max(other.read_count);
+        self.read_length = self.read_length.max(other.read_length);
+        self.write_count = self.write_count.max(other.write_count);
+        self.write_length = self.write_length.max(other.write_length);
+    }
+
+    fn multiply(&self, times: u64) -> CostBound {
+        CostBound {
+            runtime: self.runtime.saturating_mul(times),
+            read_count: self.read_count.saturating_mul(times),
+            read_length: self.read_length.saturating_mul(times),
+            write_count: self.write_count.saturating_mul(times),
+            write_length: self.write_length.saturating_mul(times),
+        }
+    }
+
+    /// The dimensions of `limit` that this bound exceeds.
+    pub fn exceeded_dimensions(&self, limit: &ExecutionCost) -> Vec<&'static str> {
+        let mut dimensions = vec![];
+        if self.runtime > limit.runtime {
+            dimensions.push("runtime");
+        }
+        if self.read_count > limit.read_count {
+            dimensions.push("read_count");
+        }
+        if self.read_length > limit.read_length {
+            dimensions.push("read_length");
+        }
+        if self.write_count > limit.write_count {
+            dimensions.push("write_count");
+        }
+        if self.write_length > limit.write_length {
+            dimensions.push("write_length");
+        }
+        dimensions
+    }
+}
+
+fn linear(n: u64, a: u64, b: u64) -> u64 {
+    a.saturating_mul(n).saturating_add(b)
+}
+
+fn log2(n: u64) -> u64 {
+    match n {
+        0 => 0,
+        n => 63 - n.leading_zeros() as u64,
+    }
+}
+
+fn nlogn(n: u64, a: u64, b: u64) -> u64 {
+    a.saturating_mul(n.saturating_mul(log2(n)))
+        .saturating_add(b)
+}
+
+/// Cost of `function` for an input of size `n`, as defined by the `costs-2`
+/// boot contract.
+fn costs_2(function: ClarityCostFunction, n: u64) -> CostBound {
+    use clarity::vm::costs::cost_functions::ClarityCostFunction::*;
+    let runtime = match function {
+        LookupVariableDepth => linear(n, 2, 14),
+        LookupVariableSize => linear(n, 2, 1),
+        LookupFunction => 16,
+        BindName => 256,
+        InnerTypeCheckCost => linear(n, 2, 9),
+        UserFunctionApplication => linear(n, 26, 140),
+        Let => linear(n, 146, 862),
+        If => 200,
+        Asserts => 170,
+        Map => linear(n, 1210, 3314),
+        Filter => 460,
+        Len => 486,
+        ElementAt => 619,
+        IndexOf => linear(n, 1, 243),
+        Fold => 483,
+        ListCons => linear(n, 14, 198),
+        TupleGet => nlogn(n, 4, 1780),
+        TupleMerge => linear(n, 4, 646),
+        TupleCons => nlogn(n, 11, 1101),
+        Add | Sub | Mul | Div => linear(n, 14, 157),
+        Geq | Leq | Le | Ge | IntCast | Mod | Pow | Sqrti | Log2 | Xor | Not => 170,
+        Eq => linear(n, 7, 172),
+        Begin => 202,
+        Hash160 => linear(n, 1, 201),
+        Sha256 => linear(n, 1, 100),
+        Sha512 => linear(n, 1, 176),
+        Sha512t256 => linear(n, 1, 188),
+        Keccak256 => linear(n, 1, 221),
+        Secp256k1recover => 14344,
+        Secp256k1verify => 13540,
+        Print => linear(n, 3, 1413),
+        SomeCons | OkCons | ErrCons => 230,
+        DefaultTo | IsOkay | IsNone | IsErr | IsSome | Unwrap | UnwrapErr | TryRet | Match => 287,
+        UnwrapRet | UnwrapErrOrRet => 339,
+        Or | And => linear(n, 3, 149),
+        Append => linear(n, 71, 176),
+        Concat => linear(n, 75, 244),
+        AsMaxLen => 475,
+        ContractCall => 153,
+        ContractOf => 13400,
+        PrincipalOf => 999,
+        _ => return costs_2_storage(function, n),
+    };
+    CostBound::runtime(runtime)
+}
+
+/// Cost of the `costs-2` functions accessing the storage.
+fn costs_2_storage(function: ClarityCostFunction, n: u64) -> CostBound {
+    use clarity::vm::costs::cost_functions::ClarityCostFunction::*;
+    let (runtime, read_count, read_length, write_count, write_length) = match function {
+        AtBlock => (210, 1, 1, 0, 0),
+        LoadContract => (linear(n, 1, 157), 3, linear(n, 1, 1), 0, 0),
+        FetchEntry => (linear(n, 1, 1539), 1, linear(n, 1, 1), 0, 0),
+        SetEntry => (linear(n, 4, 2204), 1, 0, 1, linear(n, 1, 1)),
+        FetchVar => (linear(n, 1, 543), 1, linear(n, 1, 1), 0, 0),
+        SetVar => (linear(n, 5, 691), 1, 0, 1, linear(n, 1, 1)),
+        BlockInfo => (6321, 1, 1, 0, 0),
+        StxBalance => (1385, 1, 1, 0, 0),
+        StxTransfer => (1430, 1, 1, 1, 1),
+        FtMint => (1645, 2, 1, 2, 1),
+        FtTransfer | FtBurn => (612, 2, 1, 2, 1),
+        FtBalance => (547, 1, 1, 0, 0),
+        FtSupply => (483, 1, 1, 0, 0),
+        NftMint | NftTransfer | NftBurn => (linear(n, 9, 795), 1, 1, 1, 1),
+        NftOwner => (linear(n, 9, 795), 1, 1, 0, 0),
+        _ => (0, 0, 0, 0, 0),
+    };
+    CostBound {
+        runtime,
+        read_count,
+        read_length,
+        write_count,
+        write_length,
+    }
+}
+
+/// Static cost bound of a public or read-only function.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FunctionCostBound {
+    pub name: String,
+    pub access: String,
+    pub cost: CostBound,
+    /// Number of `contract-call?`, the cost of the callee is not included
+    pub contract_calls: u32,
+    /// A sequence of unknown length is iterated, its cost is not included
+    pub unknown_iterations: bool,
+    /// The dimensions of the block limit that can be exceeded
+    pub exceeds_block_limit: Vec<String>,
+    #[serde(skip)]
+    pub span: Span,
+}
+
+struct Function<'a> {
+    define_type: DefineFunctions,
+    name_span: Span,
+    body: &'a SymbolicExpression,
+}
+
+#[derive(Clone, Default)]
+struct Estimate {
+    cost: CostBound,
+    contract_calls: u32,
+    unknown_iterations: bool,
+}
+
+impl Estimate {
+    fn add(&mut self, other: &Estimate) {
+        self.cost.add(&other.cost);
+        self.contract_calls = self.contract_calls.saturating_add(other.contract_calls);
+        self.unknown_iterations |= other.unknown_iterations;
+    }
+
+    fn max(&mut self, other: &Estimate) {
+        self.cost.max(&other.cost);
+        self.contract_calls = self.contract_calls.max(other.contract_calls);
+        self.unknown_iterations |= other.unknown_iterations;
+    }
+
+    fn multiply(&self, times: u64) -> Estimate {
+        Estimate {
+            cost: self.cost.multiply(times),
+            contract_calls: self
+                .contract_calls
+                .saturating_mul(times.min(u32::MAX as u64) as u32),
+            unknown_iterations: self.unknown_iterations,
+        }
+    }
+}
+
+struct CostEstimator<'a> {
+    contract_analysis: &'a ContractAnalysis,
+    type_map: Option<&'a TypeMap>,
+    functions: HashMap<&'a ClarityName, Function<'a>>,
+    estimates: HashMap<&'a ClarityName, Estimate>,
+    // Functions being estimated, to avoid looping on invalid recursive calls
+    in_progress: HashSet<&'a ClarityName>,
+    // Number of contexts enclosing the current expression, each `let` body
+    // adding one, which is the depth of the variable lookups
+    depth: u64,
+}
+
+impl<'a> CostEstimator<'a> {
+    fn new(contract_analysis: &'a ContractAnalysis) -> CostEstimator<'a> {
+        let mut functions = HashMap::new();
+        for expr in contract_analysis.expressions.iter() {
+            let list = match expr.match_list() {
+                Some(list) => list,
+                None => continue,
+            };
+            let define_type = match list
+                .first()
+                .and_then(|name| name.match_atom())
+                .and_then(|name| DefineFunctions::lookup_by_name(name))
+            {
+                Some(
+                    define_type @ (DefineFunctions::PrivateFunction
+                    | DefineFunctions::ReadOnlyFunction
+                    | DefineFunctions::PublicFunction),
+                ) => define_type,
+                _ => continue,
+            };
+            let name = match list.get(1).and_then(|signature| signature.match_list()) {
+                Some(signature) => match signature.first() {
+                    Some(name) => name,
+                    None => continue,
+                },
+                None => continue,
+            };
+            if let (Some(function_name), Some(body)) = (name.match_atom(), list.get(2)) {
+                functions.insert(
+                    function_name,
+                    Function {
+                        define_type,
+                        name_span: definition_name_span(expr),
+                        body,
+                    },
+                );
+            }
+        }
+
+        Self {
+            contract_analysis,
+            type_map: contract_analysis.type_map.as_ref(),
+            functions,
+            estimates: HashMap::new(),
+            in_progress: HashSet::new(),
+            depth: 0,
+        }
+    }
+
+    fn run(mut self) -> Vec<FunctionCostBound> {
+        let mut names: Vec<_> = self
+            .functions
+            .iter()
+            .filter(|(_, function)| {
+                !matches!(function.define_type, DefineFunctions::PrivateFunction)
+            })
+            .map(|(name, function)| (*name, function.name_span.clone()))
+            .collect();
+        names.sort_by(|(_, a), (_, b)| a.cmp(b));
+
+        let mut bounds = vec![];
+        for (name, span) in names {
+            let estimate = self.estimate_function(name);
+            let access = match self.functions[name].define_type {
+                DefineFunctions::ReadOnlyFunction => "read-only",
+                _ => "public",
+            };
+            bounds.push(FunctionCostBound {
+                name: name.to_string(),
+                access: access.to_string(),
+                exceeds_block_limit: estimate
+                    .cost
+                    .exceeded_dimensions(&BLOCK_LIMIT_MAINNET)
+                    .into_iter()
+                    .map(|dimension| dimension.to_string())
+                    .collect(),
+                cost: estimate.cost,
+                contract_calls: estimate.contract_calls,
+                unknown_iterations: estimate.unknown_iterations,
+                span: span.clone(),
+            });
+        }
+        bounds
+    }
+
+    fn estimate_function(&mut self, name: &'a ClarityName) -> Estimate {
+        if let Some(estimate) = self.estimates.get(name) {
+            return estimate.clone();
+        }
+        let body = match self.functions.get(name) {
+            Some(function) => function.body,
+            None => return Estimate::default(),
+        };
+        if !self.in_progress.insert(name) {
+            return Estimate::default();
+        }
+        // The body of the function is evaluated in its own context
+        let depth = std::mem::replace(&mut self.depth, 0);
+        let estimate = self.estimate(body);
+        self.depth = depth;
+        self.in_progress.remove(name);
+        self.estimates.insert(name, estimate.clone());
+        estimate
+    }
+
+    fn estimate_all(&mut self, exprs: &'a [SymbolicExpression]) -> Estimate {
+        let mut estimate = Estimate::default();
+        for expr in exprs {
+            estimate.add(&self.estimate(expr));
+        }
+        estimate
+    }
+
+    fn estimate(&mut self, expr: &'a SymbolicExpression) -> Estimate {
+        let list = match &expr.expr {
+            SymbolicExpressionType::List(list) => list,
+            SymbolicExpressionType::Atom(_) => {
+                let mut cost = costs_2(ClarityCostFunction::LookupVariableDepth, self.depth);
+                cost.add(&costs_2(
+                    ClarityCostFunction::LookupVariableSize,
+                    self.type_size(expr),
+                ));
+                return Estimate {
+                    cost,
+                    ..Estimate::default()
+                };
+            }
+            _ => return Estimate::default(),
+        };
+        let (function_name, args) = match list.split_first() {
+            Some((function, args)) => match function.match_atom() {
+                Some(function_name) => (function_name, args),
+                None => return self.estimate_all(list),
+            },
+            None => return Estimate::default(),
+        };
+
+        let mut estimate = Estimate::default();
+        if self.functions.contains_key(function_name) {
+            estimate
+                .cost
+                .add(&costs_2(ClarityCostFunction::LookupFunction, 0));
+            estimate.cost.add(&self.user_call_cost(args));
+            estimate.add(&self.estimate_all(args));
+            estimate.add(&self.estimate_function(function_name));
+            return estimate;
+        }
+
+        let native_function = match NativeFunctions::lookup_by_name_at_version(
+            function_name,
+            &self.contract_analysis.clarity_version,
+        ) {
+            Some(native_function) => native_function,
+            // e.g. the entries of a tuple
+            None => return self.estimate_all(args),
+        };
+
+        use clarity::vm::functions::NativeFunctions::*;
+        estimate
+            .cost
+            .add(&costs_2(ClarityCostFunction::LookupFunction, 0));
+        estimate.cost.add(&self.native_cost(native_function, args));
+        match native_function {
+            If => {
+                estimate.add(&self.estimate_all(args.get(0..1).unwrap_or_default()));
+                let mut branch = Estimate::default();
+                for arg in args.iter().skip(1) {
+                    branch.max(&self.estimate(arg));
+                }
+                estimate.add(&branch);
+            }
+            Match => {
+                estimate.add(&self.estimate_all(args.get(0..1).unwrap_or_default()));
+                // The branches follow the names they bind, `(match opt some-name
+                // some-branch none-branch)` or `(match resp ok-name ok-branch
+                // err-name err-branch)`
+                let branches = match args.len() {
+                    5 => vec![&args[2], &args[4]],
+                    _ => args.iter().skip(2).collect(),
+                };
+                let mut branch = Estimate::default();
+                for arg in branches {
+                    branch.max(&self.estimate(arg));
+                }
+                estimate.add(&branch);
+            }
+            Let => {
+                if let Some(bindings) = args.get(0).and_then(|bindings| bindings.match_list()) {
+                    for binding in bindings {
+                        if let Some(value) = binding.match_list().and_then(|pair| pair.get(1)) {
+                            estimate.add(&self.estimate(value));
+                        }
+                    }
+                }
+                // The body is evaluated in a new context
+                self.depth += 1;
+                estimate.add(&self.estimate_all(args.get(1..).unwrap_or_default()));
+                self.depth -= 1;
+            }
+            Map | Filter | Fold => {
+                let sequences = match native_function {
+                    Map => args.get(1..).unwrap_or_default(),
+                    _ => args.get(1..2).unwrap_or_default(),
+                };
+                estimate.add(&self.estimate_all(args.get(1..).unwrap_or_default()));
+                let mut iterations = 0;
+                for sequence in sequences {
+                    match self.max_len(sequence) {
+                        Some(max_len) => iterations = iterations.max(max_len),
+                        None => estimate.unknown_iterations = true,
+                    }
+                }
+                // The function is applied to an element of each sequence, and
+                // to the accumulator for `fold`
+                let iteration_args = args.get(1..).unwrap_or_default();
+                let iteration = match args.get(0).and_then(|function| function.match_atom()) {
+                    Some(name) if self.functions.contains_key(name) => {
+                        let mut iteration = self.estimate_function(name);
+                        iteration.cost.add(&self.user_call_cost(iteration_args));
+                        iteration
+                    }
+                    Some(name) => match NativeFunctions::lookup_by_name_at_version(
+                        name,
+                        &self.contract_analysis.clarity_version,
+                    ) {
+                        Some(function) => Estimate {
+                            cost: self.native_cost(function, iteration_args),
+                            ..Estimate::default()
+                        },
+                        None => Estimate::default(),
+                    },
+                    None => Estimate::default(),
+                };
+                estimate.add(&iteration.multiply(iterations));
+            }
+            FetchVar | SetVar | FetchEntry | SetEntry | InsertEntry | DeleteEntry
+            | GetTokenBalance | GetTokenSupply | MintToken | BurnToken | TransferToken
+            | GetAssetOwner | MintAsset | BurnAsset | TransferAsset => {
+                // The first argument is the name of the variable, map or token
+                estimate.add(&self.estimate_all(args.get(1..).unwrap_or_default()));
+            }
+            ContractCall => {
+                // Loading the callee is counted, its execution is not known
+                estimate
+                    .cost
+                    .add(&costs_2(ClarityCostFunction::LoadContract, 0));
+                estimate.contract_calls += 1;
+                // The second argument is the name of the called function
+                estimate.add(&self.estimate_all(args.get(0..1).unwrap_or_default()));
+                estimate.add(&self.estimate_all(args.get(2..).unwrap_or_default()));
+            }
+            _ => estimate.add(&self.estimate_all(args)),
+        }
+        estimate
+    }
+
+    /// Cost of applying a user-defined function, whose arguments are
+    /// type-checked.
+    fn user_call_cost(&self, args: &[SymbolicExpression]) -> CostBound {
+        let mut cost = costs_2(
+            ClarityCostFunction::UserFunctionApplication,
+            args.len() as u64,
+        );
+        for arg in args {
+            cost.add(&costs_2(
+                ClarityCostFunction::InnerTypeCheckCost,
+                self.type_size(arg),
+            ));
+        }
+        cost
+    }
+
+    /// Cost of applying a native function, the input size being bounded by
+    /// the types of the arguments or of the accessed storage.
+    fn native_cost(
+        &self,
+        native_function: NativeFunctions,
+        args: &[SymbolicExpression],
+    ) -> CostBound {
+        use clarity::vm::costs::cost_functions::ClarityCostFunction as Cost;
+        use clarity::vm::functions::NativeFunctions::*;
+        let arg_count = args.len() as u64;
+        let first_size = args.get(0).map_or(0, |arg| self.type_size(arg));
+        let sizes = args
+            .iter()
+            .fold(0u64, |sum, arg| sum.saturating_add(self.type_size(arg)));
+        let (function, n) = match native_function {
+            Add => (Cost::Add, arg_count),
+            Subtract => (Cost::Sub, arg_count),
+            Multiply => (Cost::Mul, arg_count),
+            Divide => (Cost::Div, arg_count),
+            CmpGeq => (Cost::Geq, 0),
+            CmpLeq => (Cost::Leq, 0),
+            CmpLess => (Cost::Le, 0),
+            CmpGreater => (Cost::Ge, 0),
+            ToInt | ToUInt => (Cost::IntCast, 0),
+            Modulo => (Cost::Mod, 0),
+            Power => (Cost::Pow, 0),
+            Sqrti => (Cost::Sqrti, 0),
+            Log2 => (Cost::Log2, 0),
+            BitwiseXOR => (Cost::Xor, 0),
+            Not => (Cost::Not, 0),
+            And => (Cost::And, arg_count),
+            Or => (Cost::Or, arg_count),
+            Equals => (Cost::Eq, arg_count),
+            If => (Cost::If, 0),
+            Let => (
+                Cost::Let,
+                args.get(0)
+                    .and_then(|bindings| bindings.match_list())
+                    .map_or(0, |bindings| bindings.len() as u64),
+            ),
+            Asserts => (Cost::Asserts, 0),
+            Begin => (Cost::Begin, 0),
+            Map => (Cost::Map, arg_count.saturating_sub(1)),
+            Filter => (Cost::Filter, 0),
+            Fold => (Cost::Fold, 0),
+            Len => (Cost::Len, 0),
+            ElementAt => (Cost::ElementAt, 0),
+            IndexOf => (Cost::IndexOf, first_size),
+            ListCons => (Cost::ListCons, arg_count),
+            Append => (Cost::Append, sizes),
+            Concat => (Cost::Concat, sizes),
+            AsMaxLen => (Cost::AsMaxLen, 0),
+            TupleCons => (Cost::TupleCons, arg_count),
+            TupleGet => (
+                Cost::TupleGet,
+                args.get(1).map_or(0, |tuple| self.tuple_len(tuple)),
+            ),
+            TupleMerge => (
+                Cost::TupleMerge,
+                args.iter()
+                    .fold(0, |sum, tuple| sum.saturating_add(self.tuple_len(tuple))),
+            ),
+            Hash160 => (Cost::Hash160, first_size),
+            Sha256 => (Cost::Sha256, first_size),
+            Sha512 => (Cost::Sha512, first_size),
+            Sha512Trunc256 => (Cost::Sha512t256, first_size),
+            Keccak256 => (Cost::Keccak256, first_size),
+            Secp256k1Recover => (Cost::Secp256k1recover, 0),
+            Secp256k1Verify => (Cost::Secp256k1verify, 0),
+            Print => (Cost::Print, first_size),
+            ConsSome => (Cost::SomeCons, 0),
+            ConsOkay => (Cost::OkCons, 0),
+            ConsError => (Cost::ErrCons, 0),
+            DefaultTo => (Cost::DefaultTo, 0),
+            UnwrapRet => (Cost::UnwrapRet, 0),
+            UnwrapErrRet => (Cost::UnwrapErrOrRet, 0),
+            Unwrap => (Cost::Unwrap, 0),
+            UnwrapErr => (Cost::UnwrapErr, 0),
+            TryRet => (Cost::TryRet, 0),
+            Match => (Cost::Match, 0),
+            IsOkay => (Cost::IsOkay, 0),
+            IsNone => (Cost::IsNone, 0),
+            IsErr => (Cost::IsErr, 0),
+            IsSome => (Cost::IsSome, 0),
+            ContractCall => (Cost::ContractCall, 0),
+            ContractOf => (Cost::ContractOf, 0),
+            PrincipalOf => (Cost::PrincipalOf, 0),
+            AtBlock => (Cost::AtBlock, 0),
+            GetBlockInfo | GetBurnBlockInfo => (Cost::BlockInfo, 0),
+            FetchVar => (Cost::FetchVar, self.var_size(args)),
+            SetVar => (Cost::SetVar, self.var_size(args)),
+            FetchEntry => {
+                let (key_size, value_size) = self.map_sizes(args);
+                (Cost::FetchEntry, key_size.saturating_add(value_size))
+            }
+            SetEntry | InsertEntry => {
+                let (key_size, value_size) = self.map_sizes(args);
+                (Cost::SetEntry, key_size.saturating_add(value_size))
+            }
+            DeleteEntry => (Cost::SetEntry, self.map_sizes(args).0),
+            GetStxBalance | StxGetAccount => (Cost::StxBalance, 0),
+            StxTransfer | StxTransferMemo | StxBurn => (Cost::StxTransfer, 0),
+            GetTokenBalance => (Cost::FtBalance, 0),
+            GetTokenSupply => (Cost::FtSupply, 0),
+            MintToken => (Cost::FtMint, 0),
+            TransferToken => (Cost::FtTransfer, 0),
+            BurnToken => (Cost::FtBurn, 0),
+            GetAssetOwner => (Cost::NftOwner, self.asset_size(args)),
+            MintAsset => (Cost::NftMint, self.asset_size(args)),
+            TransferAsset => (Cost::NftTransfer, self.asset_size(args)),
+            BurnAsset => (Cost::NftBurn, self.asset_size(args)),
+            // Not priced by `costs-2`
+            _ => return CostBound::default(),
+        };
+        costs_2(function, n)
+    }
+
+    fn type_size(&self, expr: &SymbolicExpression) -> u64 {
+        self.type_map
+            .and_then(|type_map| type_map.get_type(expr))
+            .map(|type_signature| type_signature.size() as u64)
+            .unwrap_or_default()
+    }
+
+    fn tuple_len(&self, expr: &SymbolicExpression) -> u64 {
+        match self.type_map.and_then(|type_map| type_map.get_type(expr)) {
+            Some(TypeSignature::TupleType(tuple)) => tuple.get_type_map().len() as u64,
+            _ => 0,
+        }
+    }
+
+    fn max_len(&self, sequence: &SymbolicExpression) -> Option<u64> {
+        let max_len = match self.type_map?.get_type(sequence)? {
+            TypeSignature::SequenceType(SequenceSubtype::ListType(list)) => list.get_max_len(),
+            TypeSignature::SequenceType(SequenceSubtype::BufferType(length)) => u32::from(length),
+            TypeSignature::SequenceType(SequenceSubtype::StringType(StringSubtype::ASCII(
+                length,
+            ))) => u32::from(length),
+            TypeSignature::SequenceType(SequenceSubtype::StringType(StringSubtype::UTF8(
+                length,
+            ))) => u32::from(length),
+            _ => return None,
+        };
+        Some(max_len as u64)
+    }
+
+    fn var_size(&self, args: &[SymbolicExpression]) -> u64 {
+        args.get(0)
+            .and_then(|name| name.match_atom())
+            .and_then(|name| self.contract_analysis.persisted_variable_types.get(name))
+            .map(|type_signature| type_signature.size() as u64)
+            .unwrap_or_default()
+    }
+
+    fn map_sizes(&self, args: &[SymbolicExpression]) -> (u64, u64) {
+        args.get(0)
+            .and_then(|name| name.match_atom())
+            .and_then(|name| self.contract_analysis.map_types.get(name))
+            .map(|(key, value)| (key.size() as u64, value.size() as u64))
+            .unwrap_or_default()
+    }
+
+    fn asset_size(&self, args: &[SymbolicExpression]) -> u64 {
+        args.get(0)
+            .and_then(|name| name.match_atom())
+            .and_then(|name| self.contract_analysis.non_fungible_tokens.get(name))
+            .map(|type_signature| type_signature.size() as u64)
+            .unwrap_or_default()
+    }
+}
+
+/// Bound the worst-case execution cost of the public and read-only functions
+/// of a contract, with the `costs-2` cost functions. Iterations are bounded by
+/// the maximum length of the sequences, and the input sizes by their types.
+pub fn estimate_costs(contract_analysis: &ContractAnalysis) -> Vec<FunctionCostBound> {
+    CostEstimator::new(contract_analysis).run()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repl::session::Session;
+    use crate::repl::SessionSettings;
+    use clarity::vm::EvaluationResult;
+
+    fn estimate_snippet(snippet: &str) -> Vec<FunctionCostBound> {
+        let mut session = Session::new(SessionSettings::default());
+        match session.formatted_interpretation(
+            snippet.to_string(),
+            Some("checker".to_string()),
+            false,
+            None,
+            None,
+        ) {
+            Ok((_, result)) => match result.result {
+                EvaluationResult::Contract(contract_result) => {
+                    estimate_costs(&contract_result.contract.analysis)
+                }
+                _ => panic!("expected a contract"),
+            },
+            Err(e) => panic!("expected success: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn storage_accesses() {
+        let snippet = "
+(define-data-var counter uint u0)
+(define-map balances principal uint)
+(define-public (increment)
+    (begin
+        (var-set counter (+ (var-get counter) u1))
+        (ok (map-set balances tx-sender (var-get counter)))
+    )
+)
+(define-read-only (get-counter)
+    (var-get counter)
+)
+";
+        let bounds = estimate_snippet(snippet);
+        assert_eq!(bounds.len(), 2);
+        assert_eq!(bounds[0].name, "increment");
+        assert_eq!(bounds[0].access, "public");
+        // `var-set` and `map-set` also read the stored value
+        assert_eq!(bounds[0].cost.read_count, 4);
+        assert_eq!(bounds[0].cost.write_count, 2);
+        assert_eq!(bounds[1].name, "get-counter");
+        assert_eq!(bounds[1].access, "read-only");
+        assert_eq!(bounds[1].cost.read_count, 1);
+        assert_eq!(bounds[1].cost.write_count, 0);
+        assert!(bounds[1].exceeds_block_limit.is_empty());
+    }
+
+    #[test]
+    fn costs_2_formulas() {
+        let snippet = "
+(define-read-only (sum (a uint) (b uint))
+    (+ a b)
+)
+";
+        let bounds = estimate_snippet(snippet);
+        // cost_lookup_function, cost_add with 2 arguments, then
+        // cost_lookup_variable_depth and cost_lookup_variable_size for each
+        // of the 16 bytes arguments
+        assert_eq!(
+            bounds[0].cost.runtime,
+            16 + (2 * 14 + 157) + 2 * (14 + (16 * 2 + 1))
+        );
+        assert_eq!(bounds[0].cost.read_count, 0);
+    }
+
+    #[test]
+    fn iterations_use_max_length() {
+        let snippet = "
+(define-map balances principal uint)
+(define-private (read-balance (owner principal))
+    (default-to u0 (map-get? balances owner))
+)
+(define-read-only (get-balances (owners (list 200 principal)))
+    (map read-balance owners)
+)
+(define-read-only (get-balance (owner principal))
+    (read-balance owner)
+)
+";
+        let bounds = estimate_snippet(snippet);
+        assert_eq!(bounds[0].name, "get-balances");
+        assert_eq!(bounds[0].cost.read_count, 200);
+        assert_eq!(bounds[1].cost.read_count, 1);
+        assert_eq!(bounds[0].cost.read_length, 200 * bounds[1].cost.read_length);
+    }
+
+    #[test]
+    fn exceeds_block_limit() {
+        let snippet = "
+(define-map entries uint (buff 100000))
+(define-public (store (keys (list 1000 uint)))
+    (ok (map store-one keys))
+)
+(define-private (store-one (key uint))
+    (map-set entries key 0x00)
+)
+";
+        let bounds = estimate_snippet(snippet);
+        assert_eq!(bounds[0].exceeds_block_limit, vec!["write_length"]);
+    }
+}
@@ -0,0 +1,359 @@
+use crate::analysis::annotation::Annotation;
+use crate::analysis::{AnalysisPass, LintLevel, PassResult, Settings};
+use clarity::vm::analysis::analysis_db::AnalysisDatabase;
+use clarity::vm::analysis::types::ContractAnalysis;
+use clarity::vm::diagnostic::{Diagnostic, Level};
+use clarity::vm::functions::define::DefineFunctions;
+use clarity::vm::representations::SymbolicExpressionType::*;
+use clarity::vm::representations::{Span, SymbolicExpression};
+
+/// Kind of function a custom rule applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FunctionKind {
+    Public,
+    ReadOnly,
+    Private,
+}
+
+fn default_rule_level() -> LintLevel {
+    LintLevel::Warning
+}
+
+/// A project-specific rule, matching the expressions of the contracts
+/// against a pattern. Patterns are written like Clarity expressions, where
+/// `_` matches any expression, and `...` matches the remaining expressions
+/// of a list, e.g. `(unwrap-panic _)` or `(print ...)`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomRule {
+    pub name: String,
+    pub message: String,
+    #[serde(default = "default_rule_level")]
+    pub level: LintLevel,
+    /// Kinds of functions checked by the rule. When empty, `forbid` rules
+    /// check all the expressions of the contract, and `require` rules check
+    /// all the functions.
+    #[serde(default)]
+    pub functions: Vec<FunctionKind>,
+    /// Report each expression matching this pattern
+    pub forbid: Option<String>,
+    /// Report each function without an expression matching this pattern
+    pub require: Option<String>,
+}
+
+impl CustomRule {
+    pub fn validate(&self) -> Result<(), String> {
+        match (&self.forbid, &self.require) {
+            (Some(pattern), None) | (None, Some(pattern)) => {
+                Pattern::parse(pattern)
+                    .map_err(|e| format!("invalid pattern in rule '{}': {}", self.name, e))?;
+                Ok(())
+            }
+            _ => Err(format!(
+                "rule '{}' must specify exactly one of 'forbid' or 'require'",
+                self.name
+            )),
+        }
+    }
+
+    fn applies_to(&self, kind: FunctionKind) -> bool {
+        self.functions.is_empty() || self.functions.contains(&kind)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Pattern {
+    // `_`
+    Any,
+    // `...`, only valid as the last item of a list
+    Rest,
+    Atom(String),
+    List(Vec<Pattern>),
+}
+
+impl Pattern {
+    fn parse(source: &str) -> Result<Pattern, String> {
+        let tokens = tokenize(source)?;
+        let mut position = 0;
+        let pattern = Pattern::parse_tokens(&tokens, &mut position)?;
+        if position != tokens.len() {
+            return Err(format!("unexpected '{}'", tokens[position]));
+        }
+        if pattern == Pattern::Rest {
+            return Err("'...' can only end a list".to_string());
+        }
+        Ok(pattern)
+    }
+
+    fn parse_tokens(tokens: &[String], position: &mut usize) -> Result<Pattern, String> {
+        let token = tokens
+            .get(*position)
+            .ok_or_else(|| "unexpected end of pattern".to_string())?;
+        *position += 1;
+        match token.as_str() {
+            "(" => {
+                let mut items = vec![];
+                loop {
+                    match tokens.get(*position).map(|token| token.as_str()) {
+                        Some(")") => {
+                            *position += 1;
+                            break;
+                        }
+                        Some(_) => {
+                            if items.last() == Some(&Pattern::Rest) {
+                                return Err("'...' can only end a list".to_string());
+                            }
+                            items.push(Pattern::parse_tokens(tokens, position)?);
+                        }
+                        None => return Err("missing ')'".to_string()),
+                    }
+                }
+                Ok(Pattern::List(items))
+            }
+            ")" => Err("unexpected ')'".to_string()),
+            "_" => Ok(Pattern::Any),
+            "..." => Ok(Pattern::Rest),
+            atom => Ok(Pattern::Atom(atom.to_string())),
+        }
+    }
+
+    fn matches(&self, expr: &SymbolicExpression) -> bool {
+        match (self, &expr.expr) {
+            (Pattern::Any, _) => true,
+            (Pattern::Rest, _) => false,
+            (Pattern::Atom(name), Atom(atom)) => atom.as_str() == name,
+            (Pattern::Atom(value), AtomValue(v)) | (Pattern::Atom(value), LiteralValue(v)) => {
+                v.to_string() == *value
+            }
+            (Pattern::Atom(_), _) => false,
+            (Pattern::List(patterns), List(exprs)) => {
+                let rest = patterns.last() == Some(&Pattern::Rest);
+                let fixed = if rest {
+                    &patterns[..patterns.len() - 1]
+                } else {
+                    &patterns[..]
+                };
+                if exprs.len() < fixed.len() || (!rest && exprs.len() != fixed.len()) {
+                    return false;
+                }
+                fixed
+                    .iter()
+                    .zip(exprs.iter())
+                    .all(|(pattern, expr)| pattern.matches(expr))
+            }
+            (Pattern::List(_), _) => false,
+        }
+    }
+
+    // Returns the spans of the expressions matching the pattern in `expr`,
+    // including `expr` itself.
+    fn find_matches(&self, expr: &SymbolicExpression, matches: &mut Vec<Span>) {
+        if self.matches(expr) {
+            matches.push(expr.span.clone());
+        }
+        if let Some(list) = expr.match_list() {
+            for item in list {
+                self.find_matches(item, matches);
+            }
+        }
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<String>, String> {
+    let mut tokens = vec![];
+    let mut chars = source.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '(' | ')' => tokens.push(c.to_string()),
+            c if c.is_whitespace() => (),
+            '"' => {
+                let mut token = String::from("\"");
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => token.push(c),
+                        None => return Err("unterminated string".to_string()),
+                    }
+                }
+                token.push('"');
+                tokens.push(token);
+            }
+            c => {
+                let mut token = c.to_string();
+                while let Some(&next) = chars.peek() {
+                    if next == '(' || next == ')' || next.is_whitespace() {
+                        break;
+                    }
+                    token.push(next);
+                    chars.next();
+                }
+                tokens.push(token);
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+// Returns the kind, the name and the body of a function definition.
+fn match_function(
+    expr: &SymbolicExpression,
+) -> Option<(FunctionKind, &SymbolicExpression, &SymbolicExpression)> {
+    let list = expr.match_list()?;
+    let kind = match DefineFunctions::lookup_by_name(list.get(0)?.match_atom()?)? {
+        DefineFunctions::PublicFunction => FunctionKind::Public,
+        DefineFunctions::ReadOnlyFunction => FunctionKind::ReadOnly,
+        DefineFunctions::PrivateFunction => FunctionKind::Private,
+        _ => return None,
+    };
+    let name = list.get(1)?.match_list()?.get(0)?;
+    Some((kind, name, list.get(2)?))
+}
+
+pub struct CustomRulesChecker;
+
+impl CustomRulesChecker {
+    fn check_rule(
+        contract_analysis: &ContractAnalysis,
+        rule: &CustomRule,
+        level: Level,
+    ) -> Vec<Diagnostic> {
+        let mut spans = vec![];
+        if let Some(pattern) = rule.forbid.as_ref().and_then(|p| Pattern::parse(p).ok()) {
+            for expr in contract_analysis.expressions.iter() {
+                match match_function(expr) {
+                    Some((kind, _, _)) if !rule.applies_to(kind) => (),
+                    None if !rule.functions.is_empty() => (),
+                    _ => pattern.find_matches(expr, &mut spans),
+                }
+            }
+        } else if let Some(pattern) = rule.require.as_ref().and_then(|p| Pattern::parse(p).ok()) {
+            for expr in contract_analysis.expressions.iter() {
+                if let Some((kind, name, body)) = match_function(expr) {
+                    if !rule.applies_to(kind) {
+                        continue;
+                    }
+                    let mut matches = vec![];
+                    pattern.find_matches(body, &mut matches);
+                    if matches.is_empty() {
+                        spans.push(name.span.clone());
+                    }
+                }
+            }
+        }
+
+        spans
+            .into_iter()
+            .map(|span| Diagnostic {
+                level: level.clone(),
+                message: format!("{} (rule '{}')", rule.message, rule.name),
+                spans: vec![span],
+                suggestion: None,
+            })
+            .collect()
+    }
+}
+
+impl AnalysisPass for CustomRulesChecker {
+    fn run_pass(
+        contract_analysis: &mut ContractAnalysis,
+        _analysis_db: &mut AnalysisDatabase,
+        _annotations: &Vec<Annotation>,
+        settings: &Settings,
+    ) -> PassResult {
+        let mut diagnostics = vec![];
+        for rule in settings.rules.iter() {
+            let level = match rule.level {
+                LintLevel::Off => continue,
+                LintLevel::Note => Level::Note,
+                LintLevel::Warning => Level::Warning,
+                LintLevel::Error => Level::Error,
+            };
+            diagnostics.append(&mut Self::check_rule(contract_analysis, rule, level));
+        }
+        Ok((diagnostics, vec![]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repl::session::Session;
+    use crate::repl::SessionSettings;
+
+    fn rule(name: &str, forbid: Option<&str>, require: Option<&str>) -> CustomRule {
+        CustomRule {
+            name: name.to_string(),
+            message: format!("{} violated", name),
+            level: LintLevel::Warning,
+            functions: vec![],
+            forbid: forbid.map(|p| p.to_string()),
+            require: require.map(|p| p.to_string()),
+        }
+    }
+
+    #[test]
+    fn patterns() {
+        assert!(Pattern::parse("(unwrap-panic _)").is_ok());
+        assert!(Pattern::parse("(print ...)").is_ok());
+        assert!(Pattern::parse("(print ... _)").is_err());
+        assert!(Pattern::parse("(print _").is_err());
+        assert!(Pattern::parse("...").is_err());
+        assert!(rule("both", Some("_"), Some("_")).validate().is_err());
+        assert!(rule("none", None, None).validate().is_err());
+    }
+
+    #[test]
+    fn forbid_and_require() {
+        let mut settings = SessionSettings::default();
+        settings.repl_settings.analysis.rules = vec![
+            rule("no-unwrap-panic", Some("(unwrap-panic _)"), None),
+            CustomRule {
+                functions: vec![FunctionKind::Public],
+                ..rule("public-print", None, Some("(print ...)"))
+            },
+        ];
+        let mut session = Session::new(settings);
+        let snippet = "
+(define-map balances principal uint)
+(define-public (transfer (amount uint))
+    (begin
+        (print { event: \"transfer\", amount: amount })
+        (ok (unwrap-panic (map-get? balances tx-sender)))
+    )
+)
+(define-public (silent)
+    (ok true)
+)
+(define-private (helper)
+    true
+)
+"
+        .to_string();
+        match session.formatted_interpretation(
+            snippet,
+            Some("checker".to_string()),
+            false,
+            None,
+            None,
+        ) {
+            Ok((output, _)) => {
+                assert_eq!(output.len(), 6);
+                assert_eq!(
+                    output[0],
+                    format!(
+                        "checker:6:13: {}: no-unwrap-panic violated (rule 'no-unwrap-panic')",
+                        yellow!("warning")
+                    )
+                );
+                assert_eq!(
+                    output[3],
+                    format!(
+                        "checker:9:17: {}: public-print violated (rule 'public-print')",
+                        yellow!("warning")
+                    )
+                );
+            }
+            _ => panic!("Expected successful interpretation"),
+        };
+    }
+}
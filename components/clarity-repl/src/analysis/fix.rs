@@ -0,0 +1,189 @@
+use crate::analysis::annotation::WarningKind;
+use clarity::vm::diagnostic::Diagnostic;
+use clarity::vm::representations::Span;
+
+/// A machine-applicable fix for a diagnostic.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Fix {
+    pub title: String,
+    pub edits: Vec<Edit>,
+}
+
+/// Replace the text between `start` (inclusive) and `end` (exclusive) with
+/// `text`. Lines and columns start at 1, an edit with `start == end` is an
+/// insertion.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Edit {
+    pub start: Position,
+    pub end: Position,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Position {
+    pub line: u32,
+    pub column: u32,
+}
+
+/// A fix, along with the diagnostic it resolves. `Diagnostic` is defined in
+/// the clarity crate, with no room for fixes, so the passes return them next
+/// to their diagnostics.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiagnosticFix {
+    pub diagnostic: Diagnostic,
+    pub fix: Fix,
+}
+
+impl DiagnosticFix {
+    /// Check if this fix resolves `diagnostic`. The level is ignored, since it
+    /// can be changed by a lint level after the analysis.
+    pub fn resolves(&self, diagnostic: &Diagnostic) -> bool {
+        self.diagnostic.message == diagnostic.message && self.diagnostic.spans == diagnostic.spans
+    }
+}
+
+impl Fix {
+    /// Add an `allow` annotation for `kind` on the line before `span`.
+    pub fn allow_annotation(kind: WarningKind, span: &Span) -> Fix {
+        let position = Position {
+            line: span.start_line,
+            column: 1,
+        };
+        let indentation = " ".repeat(span.start_column.saturating_sub(1) as usize);
+        Fix {
+            title: format!("add `#[allow({})]` annotation", kind),
+            edits: vec![Edit {
+                start: position,
+                end: position,
+                text: format!("{};; #[allow({})]\n", indentation, kind),
+            }],
+        }
+    }
+}
+
+/// Find the fixes resolving `diagnostic`.
+pub fn fixes_for<'a>(fixes: &'a [DiagnosticFix], diagnostic: &'a Diagnostic) -> Vec<&'a Fix> {
+    fixes
+        .iter()
+        .filter(|fix| fix.resolves(diagnostic))
+        .map(|fix| &fix.fix)
+        .collect()
+}
+
+/// Apply `fixes` to `source`. An edit overlapping with another edit is
+/// skipped, and identical edits are only applied once. Returns the new source
+/// and the number of fixes applied.
+pub fn apply_fixes(source: &str, fixes: &[Fix]) -> (String, usize) {
+    let mut line_offsets = vec![0];
+    for (i, c) in source.char_indices() {
+        if c == '\n' {
+            line_offsets.push(i + 1);
+        }
+    }
+    let offset = |position: &Position| -> Option<usize> {
+        let line_start = *line_offsets.get(position.line.checked_sub(1)? as usize)?;
+        let line = source[line_start..].split('\n').next().unwrap_or("");
+        let column = position.column.checked_sub(1)? as usize;
+        match line.char_indices().nth(column) {
+            Some((i, _)) => Some(line_start + i),
+            None if column == line.chars().count() => Some(line_start + line.len()),
+            None => None,
+        }
+    };
+
+    let mut applied = 0;
+    let mut edits: Vec<(usize, usize, &str)> = vec![];
+    for fix in fixes {
+        let mut fix_edits = vec![];
+        for edit in &fix.edits {
+            match (offset(&edit.start), offset(&edit.end)) {
+                (Some(start), Some(end)) if start <= end => {
+                    fix_edits.push((start, end, edit.text.as_str()))
+                }
+                _ => break,
+            }
+        }
+        if fix_edits.len() != fix.edits.len() {
+            continue;
+        }
+        let duplicate = fix_edits.iter().all(|edit| edits.contains(edit));
+        let overlaps = fix_edits.iter().any(|(start, end, _)| {
+            edits
+                .iter()
+                .any(|(s, e, _)| (start < e && s < end) || (start == end && start == s))
+        });
+        if duplicate {
+            applied += 1;
+        } else if !overlaps {
+            edits.append(&mut fix_edits);
+            applied += 1;
+        }
+    }
+
+    edits.sort_by(|a, b| b.0.cmp(&a.0).then(b.1.cmp(&a.1)));
+    let mut fixed = source.to_string();
+    for (start, end, text) in edits {
+        fixed.replace_range(start..end, text);
+    }
+    (fixed, applied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(line: u32, column: u32) -> Span {
+        Span {
+            start_line: line,
+            start_column: column,
+            end_line: line,
+            end_column: column + 5,
+        }
+    }
+
+    #[test]
+    fn find_fixes_for_diagnostic() {
+        let diagnostic = Diagnostic {
+            level: clarity::vm::diagnostic::Level::Warning,
+            message: "use of potentially unchecked data".to_string(),
+            spans: vec![span(2, 5)],
+            suggestion: None,
+        };
+        let fix = Fix::allow_annotation(WarningKind::UncheckedData, &span(2, 5));
+        let fixes = vec![
+            DiagnosticFix {
+                diagnostic: diagnostic.clone(),
+                fix: fix.clone(),
+            },
+            DiagnosticFix {
+                diagnostic: Diagnostic {
+                    spans: vec![span(3, 5)],
+                    ..diagnostic.clone()
+                },
+                fix: Fix::allow_annotation(WarningKind::UncheckedData, &span(3, 5)),
+            },
+        ];
+        // The level can be changed by a lint level after the analysis
+        let promoted = Diagnostic {
+            level: clarity::vm::diagnostic::Level::Error,
+            ..diagnostic
+        };
+        assert_eq!(fixes_for(&fixes, &promoted), vec![&fix]);
+    }
+
+    #[test]
+    fn apply_allow_annotations() {
+        let source = "(define-public (foo (a uint))\n    (ok a)\n)\n";
+        let fixes = vec![
+            Fix::allow_annotation(WarningKind::UncheckedData, &span(2, 5)),
+            Fix::allow_annotation(WarningKind::UncheckedData, &span(2, 5)),
+            Fix::allow_annotation(WarningKind::Unused, &span(1, 1)),
+        ];
+        let (fixed, applied) = apply_fixes(source, &fixes);
+        assert_eq!(applied, 3);
+        assert_eq!(
+            fixed,
+            ";; #[allow(unused)]\n(define-public (foo (a uint))\n    ;; #[allow(unchecked_data)]\n    (ok a)\n)\n"
+        );
+    }
+}
@@ -0,0 +1,404 @@
+use crate::analysis::annotation::{Annotation, WarningKind};
+use crate::analysis::ast_visitor::{traverse, ASTVisitor, TypedVar};
+use crate::analysis::{AnalysisPass, AnalysisResult, PassResult, Settings};
+use clarity::vm::analysis::analysis_db::AnalysisDatabase;
+use clarity::vm::analysis::types::ContractAnalysis;
+use clarity::vm::diagnostic::{Diagnostic, Level};
+use clarity::vm::representations::{Span, SymbolicExpression};
+use clarity::vm::ClarityName;
+use std::collections::{BTreeMap, HashMap};
+
+struct FunctionInfo {
+    // Span of the first call to an untrusted contract made by this function
+    // (directly, or through another private function), if any.
+    interaction: Option<Span>,
+    // True if this function modifies a data-var or a map.
+    modifies_state: bool,
+}
+
+pub struct InteractionChecker<'a> {
+    // Map expression ID to a generated diagnostic
+    diagnostics: BTreeMap<u64, Vec<Diagnostic>>,
+    // For each private function, record its interactions and effects
+    user_funcs: HashMap<&'a ClarityName, FunctionInfo>,
+    // Span of the first untrusted contract call in the current function
+    interaction: Option<Span>,
+    // True if the current function modifies a data-var or a map
+    modifies_state: bool,
+}
+
+impl<'a> InteractionChecker<'a> {
+    fn new() -> InteractionChecker<'a> {
+        Self {
+            diagnostics: BTreeMap::new(),
+            user_funcs: HashMap::new(),
+            interaction: None,
+            modifies_state: false,
+        }
+    }
+
+    fn run(
+        mut self,
+        contract_analysis: &'a ContractAnalysis,
+        annotations: &Vec<Annotation>,
+        settings: &Settings,
+    ) -> AnalysisResult {
+        traverse(&mut self, &contract_analysis.expressions);
+
+        // Order the sets by the span of the warning (the first diagnostic)
+        let mut diagnostics: Vec<Vec<Diagnostic>> = self.diagnostics.into_values().collect();
+        diagnostics.sort_by(|a, b| a[0].spans[0].cmp(&b[0].spans[0]));
+        Ok(diagnostics
+            .into_iter()
+            .flat_map(|group| {
+                settings.apply_lint_level(
+                    contract_analysis,
+                    annotations,
+                    WarningKind::StateAfterCall,
+                    group,
+                )
+            })
+            .collect())
+    }
+
+    fn enter_function(&mut self) {
+        self.interaction = None;
+        self.modifies_state = false;
+    }
+
+    fn record_interaction(&mut self, span: &Span) {
+        if self.interaction.is_none() {
+            self.interaction = Some(span.clone());
+        }
+    }
+
+    // Record a write to a data-var or map and, if an untrusted contract call
+    // was made earlier in this function, add a diagnostic.
+    fn record_effect(&mut self, expr: &SymbolicExpression) {
+        self.modifies_state = true;
+        if let Some(interaction) = &self.interaction {
+            let diagnostics = generate_diagnostics(expr, interaction);
+            self.diagnostics.insert(expr.id, diagnostics);
+        }
+    }
+}
+
+fn generate_diagnostics(expr: &SymbolicExpression, interaction: &Span) -> Vec<Diagnostic> {
+    vec![
+        Diagnostic {
+            level: Level::Warning,
+            message: "state modified after call to untrusted contract".to_string(),
+            spans: vec![expr.span.clone()],
+            suggestion: None,
+        },
+        Diagnostic {
+            level: Level::Note,
+            message: "call to untrusted contract here".to_string(),
+            spans: vec![interaction.clone()],
+            suggestion: None,
+        },
+    ]
+}
+
+impl<'a> ASTVisitor<'a> for InteractionChecker<'a> {
+    fn traverse_define_public(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        parameters: Option<Vec<TypedVar<'a>>>,
+        body: &'a SymbolicExpression,
+    ) -> bool {
+        self.enter_function();
+        self.traverse_expr(body)
+    }
+
+    fn traverse_define_private(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        parameters: Option<Vec<TypedVar<'a>>>,
+        body: &'a SymbolicExpression,
+    ) -> bool {
+        self.enter_function();
+        self.traverse_expr(body);
+        self.user_funcs.insert(
+            name,
+            FunctionInfo {
+                interaction: self.interaction.take(),
+                modifies_state: self.modifies_state,
+            },
+        );
+        true
+    }
+
+    fn traverse_define_read_only(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        parameters: Option<Vec<TypedVar<'a>>>,
+        body: &'a SymbolicExpression,
+    ) -> bool {
+        // Read-only functions cannot modify state, so there is nothing to check.
+        true
+    }
+
+    fn visit_dynamic_contract_call(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        trait_ref: &'a SymbolicExpression,
+        function_name: &'a ClarityName,
+        args: &'a [SymbolicExpression],
+    ) -> bool {
+        // The callee is a trait reference or a principal supplied by the
+        // caller, so it could be any contract.
+        self.record_interaction(&expr.span);
+        true
+    }
+
+    fn visit_call_user_defined(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        args: &'a [SymbolicExpression],
+    ) -> bool {
+        let (interaction, modifies_state) = match self.user_funcs.get(name) {
+            Some(info) => (info.interaction.is_some(), info.modifies_state),
+            None => return true,
+        };
+        if modifies_state {
+            self.record_effect(expr);
+        }
+        if interaction {
+            self.record_interaction(&expr.span);
+        }
+        true
+    }
+
+    fn visit_var_set(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        value: &'a SymbolicExpression,
+    ) -> bool {
+        self.record_effect(expr);
+        true
+    }
+
+    fn visit_map_set(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        key: &HashMap<Option<&'a ClarityName>, &'a SymbolicExpression>,
+        value: &HashMap<Option<&'a ClarityName>, &'a SymbolicExpression>,
+    ) -> bool {
+        self.record_effect(expr);
+        true
+    }
+
+    fn visit_map_insert(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        key: &HashMap<Option<&'a ClarityName>, &'a SymbolicExpression>,
+        value: &HashMap<Option<&'a ClarityName>, &'a SymbolicExpression>,
+    ) -> bool {
+        self.record_effect(expr);
+        true
+    }
+
+    fn visit_map_delete(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        key: &HashMap<Option<&'a ClarityName>, &'a SymbolicExpression>,
+    ) -> bool {
+        self.record_effect(expr);
+        true
+    }
+}
+
+impl AnalysisPass for InteractionChecker<'_> {
+    fn run_pass(
+        contract_analysis: &mut ContractAnalysis,
+        analysis_db: &mut AnalysisDatabase,
+        annotations: &Vec<Annotation>,
+        settings: &Settings,
+    ) -> PassResult {
+        let checker = InteractionChecker::new();
+        checker
+            .run(contract_analysis, annotations, settings)
+            .map(|diagnostics| (diagnostics, vec![]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::Pass;
+    use crate::repl::session::Session;
+    use crate::repl::SessionSettings;
+
+    #[test]
+    fn var_set_after_call() {
+        let mut settings = SessionSettings::default();
+        settings.repl_settings.analysis.passes = vec![Pass::InteractionChecker];
+        let mut session = Session::new(settings);
+        let snippet = "
+(define-trait token-trait ((transfer (uint principal) (response bool uint))))
+(define-data-var counter uint u0)
+(define-public (unsafe (token <token-trait>))
+    (begin
+        (try! (contract-call? token transfer u10 tx-sender))
+        (var-set counter (+ (var-get counter) u1))
+        (ok true)
+    )
+)
+"
+        .to_string();
+        match session.formatted_interpretation(
+            snippet,
+            Some("checker".to_string()),
+            false,
+            None,
+            None,
+        ) {
+            Ok((output, _)) => {
+                assert_eq!(output.len(), 6);
+                assert_eq!(
+                    output[0],
+                    format!(
+                        "checker:7:9: {}: state modified after call to untrusted contract",
+                        yellow!("warning")
+                    )
+                );
+                assert_eq!(
+                    output[1],
+                    "        (var-set counter (+ (var-get counter) u1))"
+                );
+                assert_eq!(
+                    output[2],
+                    "        ^~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~"
+                );
+                assert_eq!(
+                    output[3],
+                    format!(
+                        "checker:6:15: {}: call to untrusted contract here",
+                        blue!("note")
+                    )
+                );
+                assert_eq!(
+                    output[4],
+                    "        (try! (contract-call? token transfer u10 tx-sender))"
+                );
+                assert_eq!(
+                    output[5],
+                    "              ^~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~"
+                );
+            }
+            _ => panic!("Expected successful interpretation"),
+        };
+    }
+
+    #[test]
+    fn var_set_before_call() {
+        let mut settings = SessionSettings::default();
+        settings.repl_settings.analysis.passes = vec![Pass::InteractionChecker];
+        let mut session = Session::new(settings);
+        let snippet = "
+(define-trait token-trait ((transfer (uint principal) (response bool uint))))
+(define-data-var counter uint u0)
+(define-public (safe (token <token-trait>))
+    (begin
+        (var-set counter (+ (var-get counter) u1))
+        (contract-call? token transfer u10 tx-sender)
+    )
+)
+"
+        .to_string();
+        match session.formatted_interpretation(
+            snippet,
+            Some("checker".to_string()),
+            false,
+            None,
+            None,
+        ) {
+            Ok((_, result)) => {
+                assert_eq!(result.diagnostics.len(), 0);
+            }
+            _ => panic!("Expected successful interpretation"),
+        };
+    }
+
+    #[test]
+    fn map_set_in_private_after_call() {
+        let mut settings = SessionSettings::default();
+        settings.repl_settings.analysis.passes = vec![Pass::InteractionChecker];
+        let mut session = Session::new(settings);
+        let snippet = "
+(define-trait token-trait ((transfer (uint principal) (response bool uint))))
+(define-map balances principal uint)
+(define-private (credit (amount uint))
+    (map-set balances tx-sender amount)
+)
+(define-public (deposit (token <token-trait>) (amount uint))
+    (begin
+        (try! (contract-call? token transfer amount tx-sender))
+        (ok (credit amount))
+    )
+)
+"
+        .to_string();
+        match session.formatted_interpretation(
+            snippet,
+            Some("checker".to_string()),
+            false,
+            None,
+            None,
+        ) {
+            Ok((output, result)) => {
+                assert_eq!(result.diagnostics.len(), 2);
+                assert_eq!(
+                    output[0],
+                    format!(
+                        "checker:10:13: {}: state modified after call to untrusted contract",
+                        yellow!("warning")
+                    )
+                );
+            }
+            _ => panic!("Expected successful interpretation"),
+        };
+    }
+
+    #[test]
+    fn static_contract_call() {
+        let mut settings = SessionSettings::default();
+        settings.repl_settings.analysis.passes = vec![Pass::InteractionChecker];
+        let mut session = Session::new(settings);
+        let snippet = "
+(define-data-var counter uint u0)
+(define-read-only (get-counter)
+    (ok (var-get counter))
+)
+(define-public (incr)
+    (begin
+        (try! (contract-call? .contract-0 get-counter))
+        (var-set counter (+ (var-get counter) u1))
+        (ok true)
+    )
+)
+"
+        .to_string();
+        match session.formatted_interpretation(
+            snippet,
+            Some("checker".to_string()),
+            false,
+            None,
+            None,
+        ) {
+            Ok((_, result)) => {
+                assert_eq!(result.diagnostics.len(), 0);
+            }
+            _ => panic!("Expected successful interpretation"),
+        };
+    }
+}
@@ -1,35 +1,90 @@
+pub mod access_checker;
 pub mod annotation;
+pub mod arithmetic_checker;
 pub mod ast_dependency_detector;
 pub mod ast_visitor;
+pub mod audit;
+pub mod baseline;
 pub mod call_checker;
 pub mod check_checker;
+pub mod contract_graph;
+pub mod cost_estimator;
 pub mod coverage;
+pub mod custom_rules;
+pub mod fix;
+pub mod interaction_checker;
+pub mod purity_checker;
+pub mod unused_checker;
+pub mod version_checker;
 
 use serde::de::Deserialize;
 use serde::Serialize;
 
-use crate::analysis::annotation::Annotation;
+use crate::analysis::annotation::{is_allowed, Annotation, WarningKind};
 use clarity::vm::analysis::analysis_db::AnalysisDatabase;
 use clarity::vm::analysis::types::ContractAnalysis;
-use clarity::vm::diagnostic::Diagnostic;
+use clarity::vm::diagnostic::{Diagnostic, Level};
+use std::collections::BTreeMap;
 
+use self::access_checker::AccessChecker;
+use self::arithmetic_checker::ArithmeticChecker;
 use self::ast_dependency_detector::ASTDependencyDetector;
 use self::call_checker::CallChecker;
 use self::check_checker::CheckChecker;
+use self::custom_rules::{CustomRule, CustomRulesChecker};
+use self::fix::DiagnosticFix;
+use self::interaction_checker::InteractionChecker;
+use self::purity_checker::PurityChecker;
+use self::unused_checker::UnusedChecker;
 
 pub type AnalysisResult = Result<Vec<Diagnostic>, Vec<Diagnostic>>;
+/// The result of a pass: its diagnostics with the fixes it offers for them, or
+/// its errors.
+pub type PassResult = Result<(Vec<Diagnostic>, Vec<DiagnosticFix>), Vec<Diagnostic>>;
 
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum Pass {
     All,
     CheckChecker,
+    InteractionChecker,
+    UnusedChecker,
+    AccessChecker,
+    ArithmeticChecker,
+    PurityChecker,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LintLevel {
+    Off,
+    Note,
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct LintSettings {
+    pub level: LintLevel,
 }
 
 #[derive(Debug, Default, Clone, Deserialize, Serialize)]
 pub struct Settings {
     passes: Vec<Pass>,
     check_checker: check_checker::Settings,
+    #[serde(default)]
+    lints: BTreeMap<String, LintSettings>,
+    // Rules defined by the project, in the manifest or in rules files
+    #[serde(default)]
+    rules: Vec<CustomRule>,
+    #[serde(default)]
+    rules_files: Vec<String>,
+    // Deno scripts run by `clarinet check`
+    #[serde(default)]
+    rule_scripts: Vec<String>,
+    // Overrides from the `[contracts.<name>.lints]` tables of the manifest
+    #[serde(skip)]
+    contract_lints: BTreeMap<String, BTreeMap<String, LintSettings>>,
 }
 
 impl Settings {
@@ -48,6 +103,94 @@ impl Settings {
             };
         }
     }
+
+    pub fn set_contract_lints(
+        &mut self,
+        contract_name: String,
+        lints: BTreeMap<String, LintSettings>,
+    ) -> Result<(), String> {
+        validate_lints(&lints)?;
+        self.contract_lints.insert(contract_name, lints);
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        validate_lints(&self.lints)?;
+        for rule in self.rules.iter() {
+            rule.validate()?;
+        }
+        Ok(())
+    }
+
+    /// Paths of the JSON files defining custom rules, relative to the
+    /// project root.
+    pub fn rules_files(&self) -> &[String] {
+        &self.rules_files
+    }
+
+    /// Paths of the Deno scripts defining custom rules, relative to the
+    /// project root.
+    pub fn rule_scripts(&self) -> &[String] {
+        &self.rule_scripts
+    }
+
+    /// Add the custom rules defined in a JSON file, as an array of rules.
+    pub fn add_rules_from_json(&mut self, path: &str, content: &str) -> Result<(), String> {
+        let rules: Vec<CustomRule> = serde_json::from_str(content)
+            .map_err(|e| format!("unable to parse rules file {}: {}", path, e))?;
+        for rule in rules.iter() {
+            rule.validate()?;
+        }
+        self.rules.extend(rules);
+        Ok(())
+    }
+
+    fn lint_level(&self, contract_name: &str, kind: WarningKind) -> Option<LintLevel> {
+        let name = kind.to_string();
+        self.contract_lints
+            .get(contract_name)
+            .and_then(|lints| lints.get(&name))
+            .or_else(|| self.lints.get(&name))
+            .map(|lint| lint.level)
+    }
+
+    /// Apply the configured level of `kind` to a set of diagnostics made of a
+    /// warning followed by its notes. The set is dropped when the lint is off,
+    /// or when the warning is allowed by an annotation.
+    pub fn apply_lint_level(
+        &self,
+        contract_analysis: &ContractAnalysis,
+        annotations: &[Annotation],
+        kind: WarningKind,
+        mut diagnostics: Vec<Diagnostic>,
+    ) -> Vec<Diagnostic> {
+        let allowed = diagnostics
+            .first()
+            .and_then(|diagnostic| diagnostic.spans.first())
+            .map_or(false, |span| is_allowed(annotations, kind, span));
+        if allowed {
+            return vec![];
+        }
+        let contract_name = contract_analysis.contract_identifier.name.as_str();
+        let level = match self.lint_level(contract_name, kind) {
+            Some(LintLevel::Off) => return vec![],
+            Some(LintLevel::Note) => Level::Note,
+            Some(LintLevel::Warning) => Level::Warning,
+            Some(LintLevel::Error) => Level::Error,
+            None => return diagnostics,
+        };
+        if let Some(diagnostic) = diagnostics.first_mut() {
+            diagnostic.level = level;
+        }
+        diagnostics
+    }
+}
+
+fn validate_lints(lints: &BTreeMap<String, LintSettings>) -> Result<(), String> {
+    for name in lints.keys() {
+        name.parse::<WarningKind>()?;
+    }
+    Ok(())
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -63,10 +206,21 @@ pub enum OneOrList<T> {
 pub struct SettingsFile {
     passes: Option<OneOrList<Pass>>,
     check_checker: Option<check_checker::SettingsFile>,
+    lints: Option<BTreeMap<String, LintSettings>>,
+    rules: Option<Vec<CustomRule>>,
+    rules_files: Option<Vec<String>>,
+    rule_scripts: Option<Vec<String>>,
 }
 
 // Each new pass should be included in this list
-static ALL_PASSES: [Pass; 1] = [Pass::CheckChecker];
+static ALL_PASSES: [Pass; 6] = [
+    Pass::CheckChecker,
+    Pass::InteractionChecker,
+    Pass::UnusedChecker,
+    Pass::AccessChecker,
+    Pass::ArithmeticChecker,
+    Pass::PurityChecker,
+];
 
 impl From<SettingsFile> for Settings {
     fn from(from_file: SettingsFile) -> Self {
@@ -98,6 +252,11 @@ impl From<SettingsFile> for Settings {
         Self {
             passes,
             check_checker: checker_settings,
+            lints: from_file.lints.unwrap_or_default(),
+            rules: from_file.rules.unwrap_or_default(),
+            rules_files: from_file.rules_files.unwrap_or_default(),
+            rule_scripts: from_file.rule_scripts.unwrap_or_default(),
+            contract_lints: BTreeMap::new(),
         }
     }
 }
@@ -108,15 +267,17 @@ pub trait AnalysisPass {
         analysis_db: &mut AnalysisDatabase,
         annotations: &Vec<Annotation>,
         settings: &Settings,
-    ) -> AnalysisResult;
+    ) -> PassResult;
 }
 
+/// Run the REPL-only analyses. The fixes offered by the passes are returned
+/// along with the diagnostics.
 pub fn run_analysis(
     contract_analysis: &mut ContractAnalysis,
     analysis_db: &mut AnalysisDatabase,
     annotations: &Vec<Annotation>,
     settings: &Settings,
-) -> AnalysisResult {
+) -> (AnalysisResult, Vec<DiagnosticFix>) {
     let mut errors: Vec<Diagnostic> = Vec::new();
     let mut passes: Vec<
         fn(
@@ -124,20 +285,33 @@ pub fn run_analysis(
             &mut AnalysisDatabase,
             &Vec<Annotation>,
             settings: &Settings,
-        ) -> AnalysisResult,
+        ) -> PassResult,
     > = vec![CallChecker::run_pass];
     for pass in &settings.passes {
         match pass {
             Pass::CheckChecker => passes.push(CheckChecker::run_pass),
+            Pass::InteractionChecker => passes.push(InteractionChecker::run_pass),
+            Pass::UnusedChecker => passes.push(UnusedChecker::run_pass),
+            Pass::AccessChecker => passes.push(AccessChecker::run_pass),
+            Pass::ArithmeticChecker => passes.push(ArithmeticChecker::run_pass),
+            Pass::PurityChecker => passes.push(PurityChecker::run_pass),
             Pass::All => panic!("unexpected All in list of passes"),
         }
     }
+    // Custom rules run whenever the project defines some
+    if !settings.rules.is_empty() {
+        passes.push(CustomRulesChecker::run_pass);
+    }
 
-    analysis_db.execute(|db| {
+    let mut fixes: Vec<DiagnosticFix> = Vec::new();
+    let result = analysis_db.execute(|db| {
         for pass in passes {
             // Collect warnings and continue, or if there is an error, return.
             match pass(contract_analysis, db, annotations, &settings) {
-                Ok(mut w) => errors.append(&mut w),
+                Ok((mut w, mut f)) => {
+                    errors.append(&mut w);
+                    fixes.append(&mut f);
+                }
                 Err(mut e) => {
                     errors.append(&mut e);
                     return Err(errors);
@@ -145,5 +319,6 @@ pub fn run_analysis(
             }
         }
         Ok(errors)
-    })
+    });
+    (result, fixes)
 }
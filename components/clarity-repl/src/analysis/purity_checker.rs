@@ -0,0 +1,451 @@
+use crate::analysis::annotation::{Annotation, WarningKind};
+use crate::analysis::ast_visitor::{traverse, ASTVisitor, TypedVar};
+use crate::analysis::{AnalysisPass, AnalysisResult, PassResult, Settings};
+use clarity::vm::analysis::analysis_db::AnalysisDatabase;
+use clarity::vm::analysis::types::ContractAnalysis;
+use clarity::vm::diagnostic::{Diagnostic, Level};
+use clarity::vm::functions::define::DefineFunctions;
+use clarity::vm::representations::SymbolicExpressionType::*;
+use clarity::vm::{ClarityName, SymbolicExpression};
+use std::collections::{HashMap, HashSet};
+
+#[derive(Clone, Copy, PartialEq)]
+enum FunctionKind {
+    ReadOnly,
+    Other,
+}
+
+// Calls made by a function, recorded while traversing its body.
+struct FunctionCalls<'a> {
+    kind: FunctionKind,
+    // `contract-call?` through a trait, which can target a public function
+    dynamic_calls: Vec<&'a SymbolicExpression>,
+    // Calls to functions of this contract, with the calling expression
+    user_calls: Vec<(&'a ClarityName, &'a SymbolicExpression)>,
+}
+
+pub struct PurityChecker<'a> {
+    constants: HashSet<&'a ClarityName>,
+    functions: HashMap<&'a ClarityName, FunctionCalls<'a>>,
+    current_function: Option<&'a ClarityName>,
+    diagnostics: Vec<(WarningKind, Diagnostic)>,
+}
+
+impl<'a> PurityChecker<'a> {
+    fn new() -> PurityChecker<'a> {
+        Self {
+            constants: HashSet::new(),
+            functions: HashMap::new(),
+            current_function: None,
+            diagnostics: Vec::new(),
+        }
+    }
+
+    fn run(
+        mut self,
+        contract_analysis: &'a ContractAnalysis,
+        annotations: &Vec<Annotation>,
+        settings: &Settings,
+    ) -> AnalysisResult {
+        // Constants can be referenced before their definition
+        for expr in contract_analysis.expressions.iter() {
+            if let Some((DefineFunctions::Constant, name)) = match_definition(expr) {
+                self.constants.insert(name);
+            }
+        }
+        traverse(&mut self, &contract_analysis.expressions);
+        self.check_read_only_calls();
+
+        self.diagnostics
+            .sort_by(|(_, a), (_, b)| a.spans[0].cmp(&b.spans[0]));
+        Ok(self
+            .diagnostics
+            .into_iter()
+            .flat_map(|(kind, diagnostic)| {
+                settings.apply_lint_level(contract_analysis, annotations, kind, vec![diagnostic])
+            })
+            .collect())
+    }
+
+    fn add_diagnostic(&mut self, kind: WarningKind, expr: &SymbolicExpression, message: String) {
+        let diagnostic = Diagnostic {
+            level: Level::Warning,
+            message,
+            spans: vec![expr.span.clone()],
+            suggestion: None,
+        };
+        self.diagnostics.push((kind, diagnostic));
+    }
+
+    fn traverse_function(
+        &mut self,
+        name: &'a ClarityName,
+        kind: FunctionKind,
+        body: &'a SymbolicExpression,
+    ) -> bool {
+        self.functions.insert(
+            name,
+            FunctionCalls {
+                kind,
+                dynamic_calls: vec![],
+                user_calls: vec![],
+            },
+        );
+        self.current_function = Some(name);
+        let rv = self.traverse_expr(body);
+        self.current_function = None;
+        rv
+    }
+
+    fn current_calls(&mut self) -> Option<&mut FunctionCalls<'a>> {
+        let name = self.current_function?;
+        self.functions.get_mut(name)
+    }
+
+    // Report the `contract-call?` through a trait reachable from read-only
+    // functions, directly or through the functions they call.
+    fn check_read_only_calls(&mut self) {
+        let read_only_functions: Vec<&'a ClarityName> = self
+            .functions
+            .iter()
+            .filter(|(_, calls)| calls.kind == FunctionKind::ReadOnly)
+            .map(|(name, _)| *name)
+            .collect();
+
+        let mut diagnostics = vec![];
+        for name in read_only_functions {
+            let calls = &self.functions[name];
+            for expr in calls.dynamic_calls.iter() {
+                diagnostics.push((
+                    *expr,
+                    format!(
+                        "contract-call? through a trait in read-only function '{}' fails at runtime if the callee is a public function",
+                        name
+                    ),
+                ));
+            }
+            for (callee, expr) in calls.user_calls.iter() {
+                let mut visited = HashSet::new();
+                if self.has_dynamic_call(*callee, &mut visited) {
+                    diagnostics.push((
+                        *expr,
+                        format!(
+                            "'{}' makes a contract-call? through a trait, which fails at runtime in read-only function '{}' if the callee is a public function",
+                            callee, name
+                        ),
+                    ));
+                }
+            }
+        }
+        for (expr, message) in diagnostics {
+            self.add_diagnostic(WarningKind::ReadOnlyContractCall, expr, message);
+        }
+    }
+
+    fn has_dynamic_call(
+        &self,
+        name: &'a ClarityName,
+        visited: &mut HashSet<&'a ClarityName>,
+    ) -> bool {
+        if !visited.insert(name) {
+            return false;
+        }
+        match self.functions.get(name) {
+            Some(calls) => {
+                !calls.dynamic_calls.is_empty()
+                    || calls
+                        .user_calls
+                        .iter()
+                        .any(|(callee, _)| self.has_dynamic_call(*callee, visited))
+            }
+            None => false,
+        }
+    }
+
+    fn is_constant(&self, expr: &SymbolicExpression) -> bool {
+        match &expr.expr {
+            LiteralValue(_) | AtomValue(_) => true,
+            Atom(name) => self.constants.contains(name),
+            _ => false,
+        }
+    }
+}
+
+fn match_definition(expr: &SymbolicExpression) -> Option<(DefineFunctions, &ClarityName)> {
+    let list = expr.match_list()?;
+    let define = DefineFunctions::lookup_by_name(list.get(0)?.match_atom()?)?;
+    Some((define, list.get(1)?.match_atom()?))
+}
+
+// Check if `expr` offsets a block height by a literal number of blocks, e.g.
+// `(- block-height u144)`.
+fn is_block_height_offset(expr: &SymbolicExpression) -> bool {
+    let list = match expr.match_list() {
+        Some(list) => list,
+        None => return false,
+    };
+    match list.split_first() {
+        Some((function, operands))
+            if matches!(
+                function.match_atom().map(|f| f.as_str()),
+                Some("+") | Some("-") | Some("*") | Some("/")
+            ) =>
+        {
+            operands.iter().any(|operand| {
+                matches!(
+                    operand.match_atom().map(|a| a.as_str()),
+                    Some("block-height") | Some("burn-block-height")
+                ) || is_block_height_offset(operand)
+            }) && operands
+                .iter()
+                .any(|operand| matches!(operand.expr, LiteralValue(_) | AtomValue(_)))
+        }
+        _ => false,
+    }
+}
+
+impl<'a> ASTVisitor<'a> for PurityChecker<'a> {
+    fn traverse_define_private(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        parameters: Option<Vec<TypedVar<'a>>>,
+        body: &'a SymbolicExpression,
+    ) -> bool {
+        self.traverse_function(name, FunctionKind::Other, body)
+    }
+
+    fn traverse_define_read_only(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        parameters: Option<Vec<TypedVar<'a>>>,
+        body: &'a SymbolicExpression,
+    ) -> bool {
+        self.traverse_function(name, FunctionKind::ReadOnly, body)
+    }
+
+    fn traverse_define_public(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        parameters: Option<Vec<TypedVar<'a>>>,
+        body: &'a SymbolicExpression,
+    ) -> bool {
+        self.traverse_function(name, FunctionKind::Other, body)
+    }
+
+    fn visit_dynamic_contract_call(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        trait_ref: &'a SymbolicExpression,
+        function_name: &'a ClarityName,
+        args: &'a [SymbolicExpression],
+    ) -> bool {
+        if let Some(calls) = self.current_calls() {
+            calls.dynamic_calls.push(expr);
+        }
+        true
+    }
+
+    fn visit_call_user_defined(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        args: &'a [SymbolicExpression],
+    ) -> bool {
+        if let Some(calls) = self.current_calls() {
+            calls.user_calls.push((name, expr));
+        }
+        true
+    }
+
+    fn visit_at_block(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        block: &'a SymbolicExpression,
+        inner: &'a SymbolicExpression,
+    ) -> bool {
+        if !self.is_constant(block) {
+            self.add_diagnostic(
+                WarningKind::DynamicAtBlock,
+                block,
+                "at-block with a block hash that is not a constant".to_string(),
+            );
+        }
+        true
+    }
+
+    fn visit_get_block_info(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        prop_name: &'a ClarityName,
+        block: &'a SymbolicExpression,
+    ) -> bool {
+        if prop_name.as_str() == "time" && is_block_height_offset(block) {
+            self.add_diagnostic(
+                WarningKind::FixedBlockTime,
+                expr,
+                "offsetting the block height by a number of blocks to get a time assumes a fixed block time".to_string(),
+            );
+        }
+        true
+    }
+}
+
+impl AnalysisPass for PurityChecker<'_> {
+    fn run_pass(
+        contract_analysis: &mut ContractAnalysis,
+        analysis_db: &mut AnalysisDatabase,
+        annotations: &Vec<Annotation>,
+        settings: &Settings,
+    ) -> PassResult {
+        let checker = PurityChecker::new();
+        checker
+            .run(contract_analysis, annotations, settings)
+            .map(|diagnostics| (diagnostics, vec![]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::Pass;
+    use crate::repl::session::Session;
+    use crate::repl::SessionSettings;
+
+    #[test]
+    fn read_only_contract_call() {
+        let mut settings = SessionSettings::default();
+        settings.repl_settings.analysis.passes = vec![Pass::PurityChecker];
+        let mut session = Session::new(settings);
+        let snippet = "
+(define-trait oracle ((get-price () (response uint uint))))
+(define-private (fetch (source <oracle>))
+    (contract-call? source get-price)
+)
+(define-read-only (price (source <oracle>))
+    (contract-call? source get-price)
+)
+(define-read-only (price-via-helper (source <oracle>))
+    (fetch source)
+)
+(define-public (update (source <oracle>))
+    (contract-call? source get-price)
+)
+"
+        .to_string();
+        match session.formatted_interpretation(
+            snippet,
+            Some("checker".to_string()),
+            false,
+            None,
+            None,
+        ) {
+            Ok((output, _)) => {
+                assert_eq!(output.len(), 6);
+                assert_eq!(
+                    output[0],
+                    format!(
+                        "checker:7:5: {}: contract-call? through a trait in read-only function 'price' fails at runtime if the callee is a public function",
+                        yellow!("warning")
+                    )
+                );
+                assert_eq!(
+                    output[3],
+                    format!(
+                        "checker:10:5: {}: 'fetch' makes a contract-call? through a trait, which fails at runtime in read-only function 'price-via-helper' if the callee is a public function",
+                        yellow!("warning")
+                    )
+                );
+            }
+            _ => panic!("Expected successful interpretation"),
+        };
+    }
+
+    #[test]
+    fn block_info() {
+        let mut settings = SessionSettings::default();
+        settings.repl_settings.analysis.passes = vec![Pass::PurityChecker];
+        let mut session = Session::new(settings);
+        let snippet = "
+(define-constant genesis 0x0000000000000000000000000000000000000000000000000000000000000000)
+(define-data-var checkpoint (buff 32) genesis)
+(define-read-only (at-genesis)
+    (at-block genesis block-height)
+)
+(define-read-only (at-checkpoint)
+    (at-block (var-get checkpoint) block-height)
+)
+(define-read-only (yesterday)
+    (get-block-info? time (- block-height u144))
+)
+(define-read-only (previous-block-time)
+    (get-block-info? time block-height)
+)
+"
+        .to_string();
+        match session.formatted_interpretation(
+            snippet,
+            Some("checker".to_string()),
+            false,
+            None,
+            None,
+        ) {
+            Ok((output, _)) => {
+                assert_eq!(output.len(), 6);
+                assert_eq!(
+                    output[0],
+                    format!(
+                        "checker:8:15: {}: at-block with a block hash that is not a constant",
+                        yellow!("warning")
+                    )
+                );
+                assert_eq!(
+                    output[3],
+                    format!(
+                        "checker:11:5: {}: offsetting the block height by a number of blocks to get a time assumes a fixed block time",
+                        yellow!("warning")
+                    )
+                );
+            }
+            _ => panic!("Expected successful interpretation"),
+        };
+    }
+
+    #[test]
+    fn allow_annotations() {
+        let mut settings = SessionSettings::default();
+        settings.repl_settings.analysis.passes = vec![Pass::PurityChecker];
+        let mut session = Session::new(settings);
+        let snippet = "
+(define-trait oracle ((get-price () (response uint uint))))
+(define-data-var checkpoint (buff 32) 0x00)
+(define-read-only (price (source <oracle>))
+    ;; #[allow(read_only_contract_call)]
+    (contract-call? source get-price)
+)
+(define-read-only (at-checkpoint)
+    ;; #[allow(dynamic_at_block)]
+    (at-block (var-get checkpoint) block-height)
+)
+(define-read-only (yesterday)
+    ;; #[allow(fixed_block_time)]
+    (get-block-info? time (- block-height u144))
+)
+"
+        .to_string();
+        match session.formatted_interpretation(
+            snippet,
+            Some("checker".to_string()),
+            false,
+            None,
+            None,
+        ) {
+            Ok((_, result)) => {
+                assert_eq!(result.diagnostics.len(), 0);
+            }
+            _ => panic!("Expected successful interpretation"),
+        };
+    }
+}
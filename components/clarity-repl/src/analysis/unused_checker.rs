@@ -0,0 +1,586 @@
+use crate::analysis::annotation::{Annotation, WarningKind};
+use crate::analysis::ast_visitor::{definition_name_span, traverse, ASTVisitor, TypedVar};
+use crate::analysis::fix::{DiagnosticFix, Fix};
+use crate::analysis::{AnalysisPass, PassResult, Settings};
+use clarity::vm::analysis::analysis_db::AnalysisDatabase;
+use clarity::vm::analysis::types::ContractAnalysis;
+use clarity::vm::diagnostic::{Diagnostic, Level};
+use clarity::vm::representations::{Span, SymbolicExpression, TraitDefinition};
+use clarity::vm::ClarityName;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Clone, Copy)]
+enum BindingKind {
+    Constant,
+    PrivateFunction,
+    Map,
+    DataVar,
+    Argument,
+    LetBinding,
+}
+
+impl BindingKind {
+    fn description(&self) -> &'static str {
+        match self {
+            BindingKind::Constant => "constant",
+            BindingKind::PrivateFunction => "private function",
+            BindingKind::Map => "map",
+            BindingKind::DataVar => "data-var",
+            BindingKind::Argument => "function argument",
+            BindingKind::LetBinding => "let binding",
+        }
+    }
+}
+
+struct Binding<'a> {
+    kind: BindingKind,
+    name: &'a ClarityName,
+    span: Span,
+    used: bool,
+}
+
+// A local scope, introduced by a function definition or a `let` expression.
+struct Scope<'a> {
+    // Span of the expression introducing this scope, where the fixes add their
+    // annotation.
+    span: Span,
+    bindings: Vec<Binding<'a>>,
+}
+
+pub struct UnusedChecker<'a> {
+    annotations: &'a Vec<Annotation>,
+    // Each diagnostic, with the fix allowing it
+    diagnostics: Vec<(Diagnostic, Fix)>,
+    // Top-level definitions, with the span of the enclosing define expression
+    definitions: Vec<(Binding<'a>, Span)>,
+    // Names of top-level definitions referenced anywhere in the contract
+    used: HashSet<&'a ClarityName>,
+    // Stack of local scopes, innermost last
+    scopes: Vec<Scope<'a>>,
+}
+
+impl<'a> UnusedChecker<'a> {
+    fn new(annotations: &'a Vec<Annotation>) -> UnusedChecker<'a> {
+        Self {
+            annotations,
+            diagnostics: Vec::new(),
+            definitions: Vec::new(),
+            used: HashSet::new(),
+            scopes: Vec::new(),
+        }
+    }
+
+    fn run(mut self, contract_analysis: &'a ContractAnalysis, settings: &Settings) -> PassResult {
+        traverse(&mut self, &contract_analysis.expressions);
+
+        // Definitions can be referenced before they are defined, so they can
+        // only be checked once the whole contract has been visited.
+        let definitions = std::mem::take(&mut self.definitions);
+        for (binding, define_span) in definitions {
+            if !self.used.contains(binding.name) {
+                self.report_unused(&binding, &define_span);
+            }
+        }
+
+        self.diagnostics
+            .sort_by(|(a, _), (b, _)| a.spans[0].cmp(&b.spans[0]));
+        let annotations = self.annotations;
+        let mut fixes = vec![];
+        let diagnostics = self
+            .diagnostics
+            .into_iter()
+            .flat_map(|(diagnostic, fix)| {
+                let group = settings.apply_lint_level(
+                    contract_analysis,
+                    annotations,
+                    WarningKind::Unused,
+                    vec![diagnostic],
+                );
+                if let Some(diagnostic) = group.first() {
+                    fixes.push(DiagnosticFix {
+                        diagnostic: diagnostic.clone(),
+                        fix,
+                    });
+                }
+                group
+            })
+            .collect();
+        Ok((diagnostics, fixes))
+    }
+
+    fn report_unused(&mut self, binding: &Binding<'a>, enclosing: &Span) {
+        let diagnostic = Diagnostic {
+            level: Level::Warning,
+            message: format!(
+                "{} '{}' is never used",
+                binding.kind.description(),
+                binding.name
+            ),
+            spans: vec![binding.span.clone()],
+            suggestion: None,
+        };
+        // Annotate the enclosing expression when the binding is on its first
+        // line, to keep the annotation aligned with it
+        let fix_span = if enclosing.start_line == binding.span.start_line {
+            enclosing
+        } else {
+            &binding.span
+        };
+        let fix = Fix::allow_annotation(WarningKind::Unused, fix_span);
+        self.diagnostics.push((diagnostic, fix));
+    }
+
+    fn add_definition(
+        &mut self,
+        kind: BindingKind,
+        name: &'a ClarityName,
+        expr: &SymbolicExpression,
+    ) {
+        let binding = Binding {
+            kind,
+            name,
+            span: definition_name_span(expr),
+            used: false,
+        };
+        self.definitions.push((binding, expr.span.clone()));
+    }
+
+    fn mark_used(&mut self, name: &'a ClarityName) {
+        for scope in self.scopes.iter_mut().rev() {
+            if let Some(binding) = scope.bindings.iter_mut().find(|b| b.name == name) {
+                binding.used = true;
+                return;
+            }
+        }
+        self.used.insert(name);
+    }
+
+    fn push_scope(&mut self, span: &Span, bindings: Vec<Binding<'a>>) {
+        self.scopes.push(Scope {
+            span: span.clone(),
+            bindings,
+        });
+    }
+
+    fn pop_scope(&mut self) {
+        if let Some(scope) = self.scopes.pop() {
+            for binding in scope.bindings.iter().filter(|b| !b.used) {
+                self.report_unused(binding, &scope.span);
+            }
+        }
+    }
+
+    fn traverse_function(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        parameters: Option<Vec<TypedVar<'a>>>,
+        body: &'a SymbolicExpression,
+    ) -> bool {
+        let bindings = parameters
+            .unwrap_or_default()
+            .into_iter()
+            .map(|param| Binding {
+                kind: BindingKind::Argument,
+                name: param.name,
+                span: param.decl_span,
+                used: false,
+            })
+            .collect();
+        self.push_scope(&expr.span, bindings);
+        let rv = self.traverse_expr(body);
+        self.pop_scope();
+        rv
+    }
+}
+
+// Returns the names declared in a `let` expression, with their spans.
+fn let_binding_names(expr: &SymbolicExpression) -> Vec<(&ClarityName, Span)> {
+    let mut names = Vec::new();
+    let bindings = match expr
+        .match_list()
+        .and_then(|list| list.get(1))
+        .and_then(|bindings| bindings.match_list())
+    {
+        Some(bindings) => bindings,
+        None => return names,
+    };
+    for pair in bindings {
+        if let Some(name_expr) = pair.match_list().and_then(|pair| pair.get(0)) {
+            if let Some(name) = name_expr.match_atom() {
+                names.push((name, name_expr.span.clone()));
+            }
+        }
+    }
+    names
+}
+
+impl<'a> ASTVisitor<'a> for UnusedChecker<'a> {
+    fn traverse_define_private(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        parameters: Option<Vec<TypedVar<'a>>>,
+        body: &'a SymbolicExpression,
+    ) -> bool {
+        self.add_definition(BindingKind::PrivateFunction, name, expr);
+        self.traverse_function(expr, parameters, body)
+    }
+
+    fn traverse_define_read_only(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        parameters: Option<Vec<TypedVar<'a>>>,
+        body: &'a SymbolicExpression,
+    ) -> bool {
+        self.traverse_function(expr, parameters, body)
+    }
+
+    fn traverse_define_public(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        parameters: Option<Vec<TypedVar<'a>>>,
+        body: &'a SymbolicExpression,
+    ) -> bool {
+        self.traverse_function(expr, parameters, body)
+    }
+
+    fn visit_define_constant(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        value: &'a SymbolicExpression,
+    ) -> bool {
+        self.add_definition(BindingKind::Constant, name, expr);
+        true
+    }
+
+    fn visit_define_map(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        key_type: &'a SymbolicExpression,
+        value_type: &'a SymbolicExpression,
+    ) -> bool {
+        self.add_definition(BindingKind::Map, name, expr);
+        true
+    }
+
+    fn visit_define_data_var(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        data_type: &'a SymbolicExpression,
+        initial: &'a SymbolicExpression,
+    ) -> bool {
+        self.add_definition(BindingKind::DataVar, name, expr);
+        true
+    }
+
+    fn traverse_let(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        bindings: &HashMap<&'a ClarityName, &'a SymbolicExpression>,
+        body: &'a [SymbolicExpression],
+    ) -> bool {
+        // Bindings are visible to the values of the bindings that follow
+        // them, so the scope is opened before traversing the values.
+        let scope = let_binding_names(expr)
+            .into_iter()
+            .map(|(name, span)| Binding {
+                kind: BindingKind::LetBinding,
+                name,
+                span,
+                used: false,
+            })
+            .collect();
+        self.push_scope(&expr.span, scope);
+        let mut rv = true;
+        for (_, val) in bindings {
+            rv = rv && self.traverse_expr(val);
+        }
+        for expr in body {
+            rv = rv && self.traverse_expr(expr);
+        }
+        self.pop_scope();
+        rv && self.visit_let(expr, bindings, body)
+    }
+
+    fn visit_atom(&mut self, expr: &'a SymbolicExpression, atom: &'a ClarityName) -> bool {
+        self.mark_used(atom);
+        true
+    }
+
+    fn visit_trait_reference(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        trait_def: &TraitDefinition,
+    ) -> bool {
+        self.mark_used(name);
+        true
+    }
+
+    fn visit_call_user_defined(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        args: &'a [SymbolicExpression],
+    ) -> bool {
+        self.used.insert(name);
+        true
+    }
+
+    fn visit_map(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        func: &'a ClarityName,
+        sequences: &'a [SymbolicExpression],
+    ) -> bool {
+        self.used.insert(func);
+        true
+    }
+
+    fn visit_fold(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        func: &'a ClarityName,
+        sequence: &'a SymbolicExpression,
+        initial: &'a SymbolicExpression,
+    ) -> bool {
+        self.used.insert(func);
+        true
+    }
+
+    fn visit_filter(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        func: &'a ClarityName,
+        sequence: &'a SymbolicExpression,
+    ) -> bool {
+        self.used.insert(func);
+        true
+    }
+
+    fn visit_var_get(&mut self, expr: &'a SymbolicExpression, name: &'a ClarityName) -> bool {
+        self.used.insert(name);
+        true
+    }
+
+    fn visit_var_set(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        value: &'a SymbolicExpression,
+    ) -> bool {
+        self.used.insert(name);
+        true
+    }
+
+    fn visit_map_get(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        key: &HashMap<Option<&'a ClarityName>, &'a SymbolicExpression>,
+    ) -> bool {
+        self.used.insert(name);
+        true
+    }
+
+    fn visit_map_set(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        key: &HashMap<Option<&'a ClarityName>, &'a SymbolicExpression>,
+        value: &HashMap<Option<&'a ClarityName>, &'a SymbolicExpression>,
+    ) -> bool {
+        self.used.insert(name);
+        true
+    }
+
+    fn visit_map_insert(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        key: &HashMap<Option<&'a ClarityName>, &'a SymbolicExpression>,
+        value: &HashMap<Option<&'a ClarityName>, &'a SymbolicExpression>,
+    ) -> bool {
+        self.used.insert(name);
+        true
+    }
+
+    fn visit_map_delete(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        key: &HashMap<Option<&'a ClarityName>, &'a SymbolicExpression>,
+    ) -> bool {
+        self.used.insert(name);
+        true
+    }
+}
+
+impl AnalysisPass for UnusedChecker<'_> {
+    fn run_pass(
+        contract_analysis: &mut ContractAnalysis,
+        analysis_db: &mut AnalysisDatabase,
+        annotations: &Vec<Annotation>,
+        settings: &Settings,
+    ) -> PassResult {
+        let checker = UnusedChecker::new(annotations);
+        checker.run(contract_analysis, settings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::Pass;
+    use crate::repl::session::Session;
+    use crate::repl::SessionSettings;
+
+    #[test]
+    fn unused_definitions() {
+        let mut settings = SessionSettings::default();
+        settings.repl_settings.analysis.passes = vec![Pass::UnusedChecker];
+        let mut session = Session::new(settings);
+        let snippet = "
+(define-constant ERR_UNUSED (err u1))
+(define-map balances principal uint)
+(define-data-var counter uint u0)
+(define-private (helper (x uint)) (ok x))
+(define-public (main (amount uint) (memo (buff 34)))
+    (let ((total (+ amount u1)) (extra u2))
+        (ok total)
+    )
+)
+"
+        .to_string();
+        match session.formatted_interpretation(
+            snippet,
+            Some("checker".to_string()),
+            false,
+            None,
+            None,
+        ) {
+            Ok((output, _)) => {
+                assert_eq!(output.len(), 18);
+                assert_eq!(
+                    output[0],
+                    format!(
+                        "checker:2:18: {}: constant 'ERR_UNUSED' is never used",
+                        yellow!("warning")
+                    )
+                );
+                assert_eq!(output[1], "(define-constant ERR_UNUSED (err u1))");
+                assert_eq!(output[2], "                 ^~~~~~~~~");
+                assert_eq!(
+                    output[3],
+                    format!(
+                        "checker:3:13: {}: map 'balances' is never used",
+                        yellow!("warning")
+                    )
+                );
+                assert_eq!(
+                    output[6],
+                    format!(
+                        "checker:4:18: {}: data-var 'counter' is never used",
+                        yellow!("warning")
+                    )
+                );
+                assert_eq!(
+                    output[9],
+                    format!(
+                        "checker:5:18: {}: private function 'helper' is never used",
+                        yellow!("warning")
+                    )
+                );
+                assert_eq!(
+                    output[12],
+                    format!(
+                        "checker:6:37: {}: function argument 'memo' is never used",
+                        yellow!("warning")
+                    )
+                );
+                assert_eq!(
+                    output[15],
+                    format!(
+                        "checker:7:34: {}: let binding 'extra' is never used",
+                        yellow!("warning")
+                    )
+                );
+            }
+            _ => panic!("Expected successful interpretation"),
+        };
+    }
+
+    #[test]
+    fn all_used() {
+        let mut settings = SessionSettings::default();
+        settings.repl_settings.analysis.passes = vec![Pass::UnusedChecker];
+        let mut session = Session::new(settings);
+        let snippet = "
+(define-constant ERR_ZERO (err u1))
+(define-map balances principal uint)
+(define-data-var total uint u0)
+(define-private (add-total (amount uint) (acc uint))
+    (+ amount acc)
+)
+(define-public (deposit (amounts (list 10 uint)))
+    (let ((sum (fold add-total amounts u0)) (current (var-get total)))
+        (asserts! (> sum u0) ERR_ZERO)
+        (map-set balances tx-sender sum)
+        (ok (var-set total (+ current sum)))
+    )
+)
+"
+        .to_string();
+        match session.formatted_interpretation(
+            snippet,
+            Some("checker".to_string()),
+            false,
+            None,
+            None,
+        ) {
+            Ok((_, result)) => {
+                assert_eq!(result.diagnostics.len(), 0);
+            }
+            _ => panic!("Expected successful interpretation"),
+        };
+    }
+
+    #[test]
+    fn allow_unused() {
+        let mut settings = SessionSettings::default();
+        settings.repl_settings.analysis.passes = vec![Pass::UnusedChecker];
+        let mut session = Session::new(settings);
+        let snippet = "
+;; #[allow(unused)]
+(define-constant ERR_UNUSED (err u1))
+;; #[allow(unused)]
+(define-public (transfer (amount uint) (memo (optional (buff 34))))
+    (ok amount)
+)
+(define-read-only (get-one)
+    ;; #[allow(unused)]
+    (let ((unused u2))
+        (ok u1)
+    )
+)
+"
+        .to_string();
+        match session.formatted_interpretation(
+            snippet,
+            Some("checker".to_string()),
+            false,
+            None,
+            None,
+        ) {
+            Ok((_, result)) => {
+                assert_eq!(result.diagnostics.len(), 0);
+            }
+            _ => panic!("Expected successful interpretation"),
+        };
+    }
+}
@@ -0,0 +1,344 @@
+use crate::analysis::ast_visitor::{traverse, ASTVisitor, TypedVar};
+use clarity::types::StacksEpochId;
+use clarity::vm::diagnostic::{Diagnostic, Level};
+use clarity::vm::functions::NativeFunctions;
+use clarity::vm::representations::{Span, SymbolicExpression};
+use clarity::vm::variables::NativeVariables;
+use clarity::vm::{ClarityName, ClarityVersion};
+use std::collections::{HashMap, HashSet};
+
+// Built-ins whose behavior differs before epoch 2.1
+const EPOCH_21_BEHAVIOR_CHANGES: &[(&str, &str)] = &[
+    (
+        "principal-of?",
+        "returns a testnet principal on every network before epoch 2.1",
+    ),
+    (
+        "stx-get-balance",
+        "excludes the STX locked in PoX-1 before epoch 2.1, which unlocks them",
+    ),
+    (
+        "stx-transfer?",
+        "cannot transfer the STX locked in PoX-1 before epoch 2.1, which unlocks them",
+    ),
+    (
+        "stx-burn?",
+        "cannot burn the STX locked in PoX-1 before epoch 2.1, which unlocks them",
+    ),
+];
+
+/// Checks that the built-ins used by a contract are available in the
+/// Clarity version it is deployed with, and warns about those whose
+/// behavior depends on the epoch.
+pub struct VersionChecker<'a> {
+    clarity_version: ClarityVersion,
+    epoch: StacksEpochId,
+    // Names defined by the contract, which can shadow built-ins of later versions
+    bound_names: HashSet<&'a ClarityName>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> VersionChecker<'a> {
+    pub fn run(
+        expressions: &'a [SymbolicExpression],
+        clarity_version: ClarityVersion,
+        epoch: StacksEpochId,
+    ) -> Result<Vec<Diagnostic>, Vec<Diagnostic>> {
+        // Names can be used before they are defined, collect them all first
+        let mut collector = BoundNamesCollector {
+            bound_names: HashSet::new(),
+        };
+        traverse(&mut collector, expressions);
+
+        let mut checker = VersionChecker {
+            clarity_version,
+            epoch,
+            bound_names: collector.bound_names,
+            diagnostics: vec![],
+        };
+        traverse(&mut checker, expressions);
+        if checker
+            .diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.level == Level::Error)
+        {
+            Err(checker.diagnostics)
+        } else {
+            Ok(checker.diagnostics)
+        }
+    }
+
+    fn check_function(&mut self, name: &ClarityName, span: &Span) {
+        if self.bound_names.contains(&name) {
+            return;
+        }
+        if NativeFunctions::lookup_by_name_at_version(name, &self.clarity_version).is_none() {
+            if NativeFunctions::lookup_by_name_at_version(name, &ClarityVersion::latest()).is_some()
+            {
+                self.unavailable(name, span);
+            }
+            return;
+        }
+        if self.epoch < StacksEpochId::Epoch21 {
+            for (function, change) in EPOCH_21_BEHAVIOR_CHANGES {
+                if name.as_str() == *function {
+                    self.diagnostics.push(Diagnostic {
+                        level: Level::Warning,
+                        message: format!("{} {} (contract epoch is {})", name, change, self.epoch),
+                        spans: vec![span.clone()],
+                        suggestion: None,
+                    });
+                }
+            }
+        }
+    }
+
+    fn check_variable(&mut self, name: &ClarityName, span: &Span) {
+        if self.bound_names.contains(&name) {
+            return;
+        }
+        if NativeVariables::lookup_by_name_at_version(name, &self.clarity_version).is_none()
+            && NativeVariables::lookup_by_name_at_version(name, &ClarityVersion::latest()).is_some()
+        {
+            self.unavailable(name, span);
+        }
+    }
+
+    fn unavailable(&mut self, name: &ClarityName, span: &Span) {
+        self.diagnostics.push(Diagnostic {
+            level: Level::Error,
+            message: format!(
+                "{} is not available in {}, the contract's Clarity version",
+                name, self.clarity_version
+            ),
+            spans: vec![span.clone()],
+            suggestion: Some(
+                "set clarity_version = 2 and epoch = \"2.1\" for this contract in Clarinet.toml"
+                    .to_string(),
+            ),
+        });
+    }
+}
+
+impl<'a> ASTVisitor<'a> for VersionChecker<'a> {
+    fn visit_list(&mut self, expr: &'a SymbolicExpression, list: &'a [SymbolicExpression]) -> bool {
+        if let Some(head) = list.first() {
+            if let Some(name) = head.match_atom() {
+                self.check_function(name, &head.span);
+            }
+        }
+        true
+    }
+
+    fn visit_atom(&mut self, expr: &'a SymbolicExpression, atom: &'a ClarityName) -> bool {
+        self.check_variable(atom, &expr.span);
+        true
+    }
+}
+
+// Collects the names defined by a contract: its definitions, the parameters
+// of its functions and its `let` bindings.
+struct BoundNamesCollector<'a> {
+    bound_names: HashSet<&'a ClarityName>,
+}
+
+impl<'a> BoundNamesCollector<'a> {
+    fn add_function(&mut self, name: &'a ClarityName, parameters: Option<Vec<TypedVar<'a>>>) {
+        self.bound_names.insert(name);
+        for param in parameters.unwrap_or_default() {
+            self.bound_names.insert(param.name);
+        }
+    }
+}
+
+impl<'a> ASTVisitor<'a> for BoundNamesCollector<'a> {
+    fn visit_define_constant(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        value: &'a SymbolicExpression,
+    ) -> bool {
+        self.bound_names.insert(name);
+        true
+    }
+
+    fn visit_define_private(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        parameters: Option<Vec<TypedVar<'a>>>,
+        body: &'a SymbolicExpression,
+    ) -> bool {
+        self.add_function(name, parameters);
+        true
+    }
+
+    fn visit_define_read_only(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        parameters: Option<Vec<TypedVar<'a>>>,
+        body: &'a SymbolicExpression,
+    ) -> bool {
+        self.add_function(name, parameters);
+        true
+    }
+
+    fn visit_define_public(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        parameters: Option<Vec<TypedVar<'a>>>,
+        body: &'a SymbolicExpression,
+    ) -> bool {
+        self.add_function(name, parameters);
+        true
+    }
+
+    fn visit_define_nft(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        nft_type: &'a SymbolicExpression,
+    ) -> bool {
+        self.bound_names.insert(name);
+        true
+    }
+
+    fn visit_define_ft(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        supply: Option<&'a SymbolicExpression>,
+    ) -> bool {
+        self.bound_names.insert(name);
+        true
+    }
+
+    fn visit_define_map(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        key_type: &'a SymbolicExpression,
+        value_type: &'a SymbolicExpression,
+    ) -> bool {
+        self.bound_names.insert(name);
+        true
+    }
+
+    fn visit_define_data_var(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        data_type: &'a SymbolicExpression,
+        initial: &'a SymbolicExpression,
+    ) -> bool {
+        self.bound_names.insert(name);
+        true
+    }
+
+    fn visit_define_trait(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        name: &'a ClarityName,
+        functions: &'a [SymbolicExpression],
+    ) -> bool {
+        self.bound_names.insert(name);
+        true
+    }
+
+    fn visit_let(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        bindings: &HashMap<&'a ClarityName, &'a SymbolicExpression>,
+        body: &'a [SymbolicExpression],
+    ) -> bool {
+        self.bound_names.extend(bindings.keys());
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clarity::vm::ast::build_ast_with_diagnostics;
+    use clarity::vm::types::QualifiedContractIdentifier;
+
+    fn check(
+        snippet: &str,
+        clarity_version: ClarityVersion,
+        epoch: StacksEpochId,
+    ) -> Result<Vec<Diagnostic>, Vec<Diagnostic>> {
+        let (contract_ast, _, _) = build_ast_with_diagnostics(
+            &QualifiedContractIdentifier::transient(),
+            snippet,
+            &mut (),
+            clarity_version,
+            epoch,
+        );
+        VersionChecker::run(&contract_ast.expressions, clarity_version, epoch)
+    }
+
+    #[test]
+    fn unavailable_builtins() {
+        let snippet = "
+(define-read-only (get-account (who principal))
+    (stx-account who)
+)
+(define-read-only (get-chain-id)
+    chain-id
+)
+";
+        match check(snippet, ClarityVersion::Clarity1, StacksEpochId::Epoch20) {
+            Err(diagnostics) => {
+                assert_eq!(diagnostics.len(), 2);
+                assert_eq!(diagnostics[0].spans[0].start_line, 3);
+                assert_eq!(diagnostics[1].spans[0].start_line, 6);
+            }
+            _ => panic!("Expected an error"),
+        }
+        assert!(
+            check(snippet, ClarityVersion::Clarity2, StacksEpochId::Epoch21)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn shadowed_builtins() {
+        let snippet = "
+(define-private (stx-account (chain-id uint))
+    (let ((tx-sponsor? chain-id)) tx-sponsor?)
+)
+(define-read-only (get-account)
+    (stx-account u1)
+)
+";
+        assert!(
+            check(snippet, ClarityVersion::Clarity1, StacksEpochId::Epoch20)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn epoch_behavior_changes() {
+        let snippet = "
+(principal-of? 0x03adb8de4bfb65db2cfd6120d55c6526ae9c52e675db7e47308636534ba7786110)
+(stx-get-balance tx-sender)
+";
+        match check(snippet, ClarityVersion::Clarity1, StacksEpochId::Epoch2_05) {
+            Ok(diagnostics) => {
+                assert_eq!(diagnostics.len(), 2);
+                assert_eq!(diagnostics[0].level, Level::Warning);
+                assert!(diagnostics[1].message.starts_with("stx-get-balance"));
+            }
+            _ => panic!("Expected a warning"),
+        }
+        assert!(
+            check(snippet, ClarityVersion::Clarity1, StacksEpochId::Epoch21)
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
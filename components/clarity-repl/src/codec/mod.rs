@@ -20,7 +20,7 @@ use clarity::util::secp256k1::{
 use clarity::vm::types::{
     PrincipalData, QualifiedContractIdentifier, StandardPrincipalData, Value,
 };
-use clarity::vm::{ClarityName, ContractName};
+use clarity::vm::{ClarityName, ClarityVersion, ContractName};
 use clarity::{
     impl_array_hexstring_fmt, impl_array_newtype, impl_byte_array_message_codec,
     impl_byte_array_serde,
@@ -1350,7 +1350,9 @@ impl_byte_array_serde!(TokenTransferMemo);
 pub enum TransactionPayload {
     TokenTransfer(PrincipalData, u64, TokenTransferMemo),
     ContractCall(TransactionContractCall),
-    SmartContract(TransactionSmartContract),
+    // The Clarity version is only encoded when specified, in a versioned
+    // smart contract payload, supported from epoch 2.1
+    SmartContract(TransactionSmartContract, Option<ClarityVersion>),
     PoisonMicroblock(StacksMicroblockHeader, StacksMicroblockHeader), // the previous epoch leader sent two microblocks with the same sequence, and this is proof
     Coinbase(CoinbasePayload, Option<PrincipalData>),
 }
@@ -1376,6 +1378,7 @@ pub enum TransactionPayloadID {
     PoisonMicroblock = 3,
     Coinbase = 4,
     CoinbaseToAltRecipient = 5,
+    VersionedSmartContract = 6,
 }
 
 /// Encoding of an asset type identifier
@@ -2393,10 +2396,21 @@ impl StacksMessageCodec for TransactionPayload {
                 write_next(fd, &(TransactionPayloadID::ContractCall as u8))?;
                 cc.consensus_serialize(fd)?;
             }
-            TransactionPayload::SmartContract(ref sc) => {
-                write_next(fd, &(TransactionPayloadID::SmartContract as u8))?;
-                sc.consensus_serialize(fd)?;
-            }
+            TransactionPayload::SmartContract(ref sc, ref version) => match version {
+                Some(version) => {
+                    let version: u8 = match version {
+                        ClarityVersion::Clarity1 => 1,
+                        ClarityVersion::Clarity2 => 2,
+                    };
+                    write_next(fd, &(TransactionPayloadID::VersionedSmartContract as u8))?;
+                    write_next(fd, &version)?;
+                    sc.consensus_serialize(fd)?;
+                }
+                None => {
+                    write_next(fd, &(TransactionPayloadID::SmartContract as u8))?;
+                    sc.consensus_serialize(fd)?;
+                }
+            },
             _ => {
                 unreachable!()
             }
@@ -2419,7 +2433,22 @@ impl StacksMessageCodec for TransactionPayload {
             }
             x if x == TransactionPayloadID::SmartContract as u8 => {
                 let payload: TransactionSmartContract = read_next(fd)?;
-                TransactionPayload::SmartContract(payload)
+                TransactionPayload::SmartContract(payload, None)
+            }
+            x if x == TransactionPayloadID::VersionedSmartContract as u8 => {
+                let version: u8 = read_next(fd)?;
+                let version = match version {
+                    1 => ClarityVersion::Clarity1,
+                    2 => ClarityVersion::Clarity2,
+                    _ => {
+                        return Err(CodecError::DeserializeError(format!(
+                            "Failed to parse transaction -- unknown Clarity version {}",
+                            version
+                        )))
+                    }
+                };
+                let payload: TransactionSmartContract = read_next(fd)?;
+                TransactionPayload::SmartContract(payload, Some(version))
             }
             x if x == TransactionPayloadID::PoisonMicroblock as u8 => {
                 let micrblock1: StacksMicroblockHeader = read_next(fd)?;
@@ -0,0 +1,113 @@
+use super::terminal::{complete_input, Input};
+use rustyline::completion::{Completer, Pair};
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::validate::{ValidationContext, ValidationResult, Validator};
+use rustyline::{Context, Helper, Result};
+use std::collections::BTreeSet;
+
+const COMMANDS: &[&str] = &[
+    "::help",
+    "::list_functions",
+    "::describe_function",
+    "::mint_stx",
+    "::set_tx_sender",
+    "::set_tx_sponsor",
+    "::create_multisig",
+    "::get_assets_maps",
+    "::get_costs",
+    "::get_audit_report",
+    "::get_contracts",
+    "::get_map",
+    "::get_var",
+    "::get_block_height",
+    "::advance_chain_tip",
+    "::set_burn_block_header_hash",
+    "::set_burn_block_height",
+    "::set_pox_payout_addrs",
+    "::snapshot",
+    "::restore",
+    "::save_session",
+    "::load_session",
+    "::expect",
+    "::toggle_costs",
+    "::encode",
+    "::decode",
+    "::debug",
+    "::trace",
+    "::read",
+];
+
+/// Completes the console commands, the Clarity keywords and the symbols of
+/// the contracts deployed in the session, and keeps the editor open until
+/// the brackets of the input are balanced.
+pub struct ConsoleHelper {
+    symbols: BTreeSet<String>,
+}
+
+impl ConsoleHelper {
+    pub fn new(keywords: Vec<String>) -> ConsoleHelper {
+        ConsoleHelper {
+            symbols: keywords.into_iter().collect(),
+        }
+    }
+
+    /// Adds symbols to complete, e.g. the contracts newly deployed.
+    pub fn add_symbols(&mut self, symbols: BTreeSet<String>) {
+        self.symbols.extend(symbols);
+    }
+}
+
+impl Completer for ConsoleHelper {
+    type Candidate = Pair;
+
+    fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> Result<(usize, Vec<Pair>)> {
+        let start = line[..pos]
+            .rfind(|c: char| c.is_whitespace() || c == '(' || c == ')')
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        let prefix = &line[start..pos];
+        if prefix.is_empty() {
+            return Ok((pos, vec![]));
+        }
+
+        let candidates: Vec<Pair> = if start == 0 && prefix.starts_with(':') {
+            COMMANDS
+                .iter()
+                .filter(|command| command.starts_with(prefix))
+                .map(|command| Pair {
+                    display: command.to_string(),
+                    replacement: format!("{} ", command),
+                })
+                .collect()
+        } else {
+            self.symbols
+                .iter()
+                .filter(|symbol| symbol.starts_with(prefix))
+                .map(|symbol| Pair {
+                    display: symbol.clone(),
+                    replacement: symbol.clone(),
+                })
+                .collect()
+        };
+        Ok((start, candidates))
+    }
+}
+
+impl Hinter for ConsoleHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ConsoleHelper {}
+
+impl Validator for ConsoleHelper {
+    fn validate(&self, ctx: &mut ValidationContext) -> Result<ValidationResult> {
+        // Malformed inputs are reported once submitted
+        match complete_input(ctx.input()) {
+            Ok(Input::Incomplete(_)) => Ok(ValidationResult::Incomplete),
+            _ => Ok(ValidationResult::Valid(None)),
+        }
+    }
+}
+
+impl Helper for ConsoleHelper {}
@@ -1,2 +1,3 @@
+mod helper;
 pub mod terminal;
 pub use terminal::Terminal;
@@ -1,19 +1,20 @@
+use super::helper::ConsoleHelper;
 use crate::repl::{settings::SessionSettings, Session};
 
 use ansi_term::{Colour, Style};
 use rustyline::error::ReadlineError;
-use rustyline::Editor;
+use rustyline::{CompletionType, Config, Editor};
 use std::io::{stdin, stdout, Write};
 
 const VERSION: Option<&'static str> = option_env!("CARGO_PKG_VERSION");
 const HISTORY_FILE: Option<&'static str> = option_env!("CLARITY_REPL_HISTORY_FILE");
 
-enum Input<'a> {
+pub(super) enum Input<'a> {
     Incomplete(char),
     Complete(Vec<&'a str>),
 }
 
-fn complete_input(str: &str) -> Result<Input, (char, char)> {
+pub(super) fn complete_input(str: &str) -> Result<Input, (char, char)> {
     let mut forms: Vec<&str> = vec![];
     let mut paren_count = 0;
     let mut last_pos = 0;
@@ -21,9 +22,15 @@ fn complete_input(str: &str) -> Result<Input, (char, char)> {
     let mut brackets = vec![];
     let mut skip_next = false;
     let mut in_string = false;
+    let mut in_comment = false;
 
     for (pos, character) in str.char_indices() {
+        if in_comment {
+            in_comment = character != '\n';
+            continue;
+        }
         match character {
+            ';' if !in_string => in_comment = true,
             '\\' => skip_next = true,
             '"' => {
                 if skip_next {
@@ -75,18 +82,30 @@ fn complete_input(str: &str) -> Result<Input, (char, char)> {
 
 pub struct Terminal {
     pub session: Session,
+    history_location: String,
 }
 
 impl Terminal {
     pub fn new(session_settings: SessionSettings) -> Terminal {
         let mut session = Session::new(session_settings);
         session.is_interactive = true;
-        Terminal { session }
+        Terminal {
+            session,
+            history_location: HISTORY_FILE.unwrap_or("history.txt").to_string(),
+        }
     }
 
     pub fn load(mut session: Session) -> Terminal {
         session.is_interactive = true;
-        Terminal { session }
+        Terminal {
+            session,
+            history_location: HISTORY_FILE.unwrap_or("history.txt").to_string(),
+        }
+    }
+
+    /// Sets the file the history of the inputs is loaded from and saved to.
+    pub fn set_history_location(&mut self, history_location: String) {
+        self.history_location = history_location;
     }
 
     pub fn start(&mut self) {
@@ -104,39 +123,37 @@ impl Terminal {
         };
         println!("{}", output);
         println!("command_line_end");
-        let mut editor = Editor::<()>::new();
+        let config = Config::builder()
+            .completion_type(CompletionType::List)
+            .build();
+        let mut editor = Editor::<ConsoleHelper>::with_config(config);
+        let mut helper = ConsoleHelper::new(self.session.get_api_reference_index());
+        helper.add_symbols(self.session.get_contracts_symbols());
+        editor.set_helper(Some(helper));
         let mut ctrl_c_acc = 0;
-        let mut input_buffer = vec![];
-        let mut prompt = String::from(">> ");
 
-        editor
-            .load_history(HISTORY_FILE.unwrap_or("history.txt"))
-            .ok();
+        editor.load_history(&self.history_location).ok();
         loop {
-            let readline = editor.readline(prompt.as_str());
+            // The editor keeps reading lines until the brackets are balanced
+            let readline = editor.readline(">> ");
             match readline {
                 Ok(command) => {
                     ctrl_c_acc = 0;
-                    input_buffer.push(command);
-                    let input = input_buffer.join(" ");
+                    let input = command.lines().collect::<Vec<_>>().join("\n");
                     match complete_input(&input) {
-                        Ok(Input::Complete(forms)) => {
+                        Ok(_) => {
                             let output = self.session.handle_command(&input);
                             for line in output {
                                 println!("{}", line);
                             }
-                            prompt = String::from(">> ");
                             self.session.executed.push(input.to_string());
+                            if let Some(helper) = editor.helper_mut() {
+                                helper.add_symbols(self.session.get_contracts_symbols());
+                            }
                             editor.add_history_entry(input);
-                            input_buffer.clear();
-                        }
-                        Ok(Input::Incomplete(str)) => {
-                            prompt = format!("{}.. ", str);
                         }
                         Err((expected, got)) => {
                             println!("Error: expected closing {}, got {}", expected, got);
-                            prompt = String::from(">> ");
-                            input_buffer.pop();
                         }
                     }
                 }
@@ -158,8 +175,123 @@ impl Terminal {
                 }
             }
         }
-        editor
-            .save_history(HISTORY_FILE.unwrap_or("history.txt"))
-            .unwrap();
+        if let Err(e) = editor.save_history(&self.history_location) {
+            println!(
+                "{}",
+                yellow!(format!(
+                    "unable to save history to {}: {}",
+                    self.history_location, e
+                ))
+            );
+        }
+    }
+
+    /// Runs a script of expressions and commands non-interactively, one
+    /// input per line (expressions can span several lines). Returns `false`
+    /// if the script is malformed or one of its `::expect` assertions failed.
+    pub fn run_script(&mut self, script: &str) -> bool {
+        let mut input_buffer = vec![];
+        let mut expectations = 0;
+        let mut failures = 0;
+        let mut malformed = false;
+
+        for line in script.lines() {
+            let line = line.trim();
+            if input_buffer.is_empty() && (line.is_empty() || line.starts_with(";;")) {
+                continue;
+            }
+            input_buffer.push(line);
+            let input = input_buffer.join("\n");
+            match complete_input(&input) {
+                Ok(Input::Complete(_)) => {
+                    println!(">> {}", input);
+                    let output = if let Some(expected) = input.strip_prefix("::expect ") {
+                        expectations += 1;
+                        match self.session.check_expectation(expected) {
+                            Ok(()) => vec![green!(format!("✔ {}", expected.trim()))],
+                            Err(e) => {
+                                failures += 1;
+                                vec![red!(format!("x {}", e))]
+                            }
+                        }
+                    } else {
+                        self.session.handle_command(&input)
+                    };
+                    for line in output {
+                        println!("{}", line);
+                    }
+                    self.session.executed.push(input.to_string());
+                    input_buffer.clear();
+                }
+                Ok(Input::Incomplete(_)) => {}
+                Err((expected, got)) => {
+                    println!(">> {}", input);
+                    println!(
+                        "{}",
+                        red!(format!("Error: expected closing {}, got {}", expected, got))
+                    );
+                    malformed = true;
+                    input_buffer.clear();
+                }
+            }
+        }
+        if !input_buffer.is_empty() {
+            println!(">> {}", input_buffer.join("\n"));
+            println!("{}", red!("Error: unexpected end of script"));
+            malformed = true;
+        }
+
+        if failures > 0 {
+            println!(
+                "{}",
+                red!(format!(
+                    "{} of {} expectations failed",
+                    failures, expectations
+                ))
+            );
+        } else if expectations > 0 {
+            println!("{}", green!(format!("{} expectations met", expectations)));
+        }
+        failures == 0 && !malformed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_terminal() -> Terminal {
+        let mut settings = SessionSettings::default();
+        settings.include_boot_contracts = vec!["costs".into(), "costs-2".into()];
+        let mut session = Session::new(settings);
+        session.start().expect("session could not start");
+        Terminal::load(session)
+    }
+
+    #[test]
+    fn run_script() {
+        let script = r#"
+;; multi-line forms can contain comments
+(+ u1 u1) ;; trailing comment
+::expect u2
+
+(let ((a u2)) ;; ( unbalanced brackets in comments are ignored
+  ;; the comment doesn't end the form
+  (+ a
+     u1))
+::expect u3
+::advance_chain_tip 2
+"#;
+        let mut terminal = new_terminal();
+        assert!(terminal.run_script(script));
+
+        let mut terminal = new_terminal();
+        assert!(!terminal.run_script("(+ u1 u1)\n::expect u3"));
+
+        let mut terminal = new_terminal();
+        assert!(!terminal.run_script("(+ u1 u1}\n::expect u2"));
+
+        let mut terminal = new_terminal();
+        assert!(!terminal.run_script("(+ u1\n  u1"));
     }
 }
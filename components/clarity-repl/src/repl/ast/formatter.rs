@@ -0,0 +1,571 @@
+use super::parser;
+use super::parser::lexer::token::Token;
+use super::parser::lexer::Lexer;
+use crate::clarity::diagnostic::{Diagnostic, Level};
+use crate::clarity::representations::Span;
+
+// Forms whose arguments after the first `n` ones are a body, indented under
+// the form instead of aligned with its first argument.
+const BODY_FORMS: &[(&str, usize)] = &[
+    ("define-public", 1),
+    ("define-read-only", 1),
+    ("define-private", 1),
+    ("define-constant", 1),
+    ("define-data-var", 1),
+    ("define-map", 1),
+    ("define-fungible-token", 1),
+    ("define-non-fungible-token", 1),
+    ("define-trait", 1),
+    ("let", 1),
+    ("begin", 0),
+    ("match", 1),
+    ("if", 1),
+    ("as-contract", 0),
+    ("at-block", 1),
+    ("asserts!", 1),
+    ("unwrap!", 1),
+    ("unwrap-err!", 1),
+];
+
+const FUNCTION_DEFINITIONS: &[&str] = &["define-public", "define-read-only", "define-private"];
+
+#[derive(Debug, Clone)]
+pub struct FormatterSettings {
+    /// Number of spaces per indentation level
+    pub indentation: usize,
+    /// Lists longer than this are broken over several lines
+    pub max_line_length: usize,
+}
+
+impl Default for FormatterSettings {
+    fn default() -> Self {
+        FormatterSettings {
+            indentation: 2,
+            max_line_length: 80,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Node {
+    Atom(String),
+    Comment(String),
+    List(Vec<Item>),
+    Tuple(Vec<Item>),
+    Colon,
+    Comma,
+}
+
+#[derive(Debug, PartialEq)]
+struct Item {
+    node: Node,
+    // Number of line breaks between the previous token and this one
+    newlines: u32,
+}
+
+impl Item {
+    fn is_comment(&self) -> bool {
+        matches!(self.node, Node::Comment(_))
+    }
+
+    // A comment following an expression on the same line
+    fn is_trailing_comment(&self) -> bool {
+        self.is_comment() && self.newlines == 0
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum LexemeKind {
+    Lparen,
+    Rparen,
+    Lbrace,
+    Rbrace,
+    Colon,
+    Comma,
+    Comment,
+    Atom,
+}
+
+// A token of the source, where the tokens not separated by whitespace or
+// delimiters (e.g. `'SP000000000000000000002Q6VF78.pox`) are merged.
+struct Lexeme {
+    kind: LexemeKind,
+    text: String,
+    span: Span,
+}
+
+/// Formats Clarity source code, normalizing the indentation, the line
+/// breaks and the spacing, while preserving comments and annotations.
+/// Returns the syntax errors if the source cannot be parsed.
+pub fn format_source(
+    source: &str,
+    settings: &FormatterSettings,
+) -> Result<String, Vec<Diagnostic>> {
+    let (_, diagnostics, success) = parser::parse(source);
+    if !success {
+        return Err(diagnostics
+            .into_iter()
+            .filter(|diagnostic| diagnostic.level == Level::Error)
+            .collect());
+    }
+
+    let lexemes = lex(source);
+    let mut position = 0;
+    let items = parse_items(&lexemes, &mut position, 0);
+
+    let mut printer = Printer::new(settings);
+    printer.print_top_level(&items);
+    Ok(printer.output)
+}
+
+fn lex(source: &str) -> Vec<Lexeme> {
+    let lines: Vec<Vec<char>> = source.split('\n').map(|l| l.chars().collect()).collect();
+    let mut lexer = Lexer::new(source);
+    let mut lexemes: Vec<Lexeme> = vec![];
+    loop {
+        let placed_token = lexer.read_token();
+        let kind = match placed_token.token {
+            Token::Eof => break,
+            Token::Whitespace => continue,
+            Token::Lparen => LexemeKind::Lparen,
+            Token::Rparen => LexemeKind::Rparen,
+            Token::Lbrace => LexemeKind::Lbrace,
+            Token::Rbrace => LexemeKind::Rbrace,
+            Token::Colon => LexemeKind::Colon,
+            Token::Comma => LexemeKind::Comma,
+            Token::Comment(_) => LexemeKind::Comment,
+            _ => LexemeKind::Atom,
+        };
+        let span = placed_token.span;
+        let text = source_text(&lines, &span);
+        if kind == LexemeKind::Atom {
+            if let Some(last) = lexemes.last_mut() {
+                if last.kind == LexemeKind::Atom
+                    && last.span.end_line == span.start_line
+                    && last.span.end_column + 1 == span.start_column
+                {
+                    last.text.push_str(&text);
+                    last.span.end_column = span.end_column;
+                    continue;
+                }
+            }
+        }
+        let text = match kind {
+            LexemeKind::Comment => text.trim_end().to_string(),
+            _ => text,
+        };
+        lexemes.push(Lexeme { kind, text, span });
+    }
+    lexemes
+}
+
+// Returns the text of the source covered by `span`.
+fn source_text(lines: &[Vec<char>], span: &Span) -> String {
+    let start_line = span.start_line as usize - 1;
+    let end_line = span.end_line as usize - 1;
+    let start_column = span.start_column as usize - 1;
+    let end_column = span.end_column as usize;
+    if start_line == end_line {
+        let line = &lines[start_line];
+        return line[start_column.min(line.len())..end_column.min(line.len())]
+            .iter()
+            .collect();
+    }
+    let mut text: String = lines[start_line][start_column..].iter().collect();
+    for line in lines[start_line + 1..end_line].iter() {
+        text.push('\n');
+        text.extend(line.iter());
+    }
+    text.push('\n');
+    let last = &lines[end_line];
+    text.extend(last[..end_column.min(last.len())].iter());
+    text
+}
+
+// Builds the items of a list, tuple or of the top level, up to the closing
+// delimiter.
+fn parse_items(lexemes: &[Lexeme], position: &mut usize, mut last_line: u32) -> Vec<Item> {
+    let mut items = vec![];
+    while let Some(lexeme) = lexemes.get(*position) {
+        *position += 1;
+        let newlines = lexeme.span.start_line.saturating_sub(last_line);
+        let node = match lexeme.kind {
+            LexemeKind::Rparen | LexemeKind::Rbrace => break,
+            LexemeKind::Lparen => Node::List(parse_items(lexemes, position, lexeme.span.end_line)),
+            LexemeKind::Lbrace => Node::Tuple(parse_items(lexemes, position, lexeme.span.end_line)),
+            LexemeKind::Colon => Node::Colon,
+            LexemeKind::Comma => Node::Comma,
+            LexemeKind::Comment => Node::Comment(lexeme.text.clone()),
+            LexemeKind::Atom => Node::Atom(lexeme.text.clone()),
+        };
+        // The next item is compared to the end of this one, e.g. its closing delimiter
+        last_line = match lexemes.get(*position - 1) {
+            Some(last) => last.span.end_line,
+            None => lexeme.span.end_line,
+        };
+        items.push(Item { node, newlines });
+    }
+    items
+}
+
+enum Entry<'a> {
+    Pair(&'a Item, &'a Item),
+    Comment(&'a Item),
+}
+
+// Splits the items of a tuple in `key: value` pairs, dropping the separators.
+fn tuple_entries(items: &[Item]) -> Vec<Entry> {
+    let mut entries = vec![];
+    let mut key = None;
+    for item in items {
+        match item.node {
+            Node::Colon | Node::Comma => (),
+            Node::Comment(_) => entries.push(Entry::Comment(item)),
+            _ => match key.take() {
+                Some(key) => entries.push(Entry::Pair(key, item)),
+                None => key = Some(item),
+            },
+        }
+    }
+    if let Some(key) = key {
+        entries.push(Entry::Pair(key, key));
+    }
+    entries
+}
+
+// Renders `node` on a single line, if it contains no comment.
+fn flat(node: &Node) -> Option<String> {
+    match node {
+        Node::Atom(text) if !text.contains('\n') => Some(text.clone()),
+        Node::Atom(_) | Node::Comment(_) => None,
+        Node::Colon => Some(":".to_string()),
+        Node::Comma => Some(",".to_string()),
+        Node::List(items) => {
+            let items = items
+                .iter()
+                .map(|item| flat(&item.node))
+                .collect::<Option<Vec<String>>>()?;
+            Some(format!("({})", items.join(" ")))
+        }
+        Node::Tuple(items) => {
+            let mut entries = vec![];
+            for entry in tuple_entries(items) {
+                match entry {
+                    Entry::Pair(key, value) if std::ptr::eq(key, value) => {
+                        entries.push(flat(&key.node)?)
+                    }
+                    Entry::Pair(key, value) => {
+                        entries.push(format!("{}: {}", flat(&key.node)?, flat(&value.node)?))
+                    }
+                    Entry::Comment(_) => return None,
+                }
+            }
+            if entries.is_empty() {
+                Some("{}".to_string())
+            } else {
+                Some(format!("{{ {} }}", entries.join(", ")))
+            }
+        }
+    }
+}
+
+fn head_atom(items: &[Item]) -> Option<&str> {
+    match items.first().map(|item| &item.node) {
+        Some(Node::Atom(name)) => Some(name.as_str()),
+        _ => None,
+    }
+}
+
+// Function definitions, and `let` or `begin` with several expressions in
+// their body, are always broken over several lines.
+fn must_break(items: &[Item]) -> bool {
+    match head_atom(items) {
+        Some(name) if FUNCTION_DEFINITIONS.contains(&name) => true,
+        Some("let") => items.len() > 3,
+        Some("begin") => items.len() > 2,
+        _ => false,
+    }
+}
+
+struct Printer<'a> {
+    settings: &'a FormatterSettings,
+    output: String,
+    column: usize,
+    // Indentation of the current line
+    indentation: usize,
+}
+
+impl<'a> Printer<'a> {
+    fn new(settings: &'a FormatterSettings) -> Self {
+        Printer {
+            settings,
+            output: String::new(),
+            column: 0,
+            indentation: 0,
+        }
+    }
+
+    fn write(&mut self, text: &str) {
+        self.output.push_str(text);
+        self.column = match text.rfind('\n') {
+            Some(index) => text[index + 1..].chars().count(),
+            None => self.column + text.chars().count(),
+        };
+    }
+
+    fn newline(&mut self, indentation: usize, blank_line: bool) {
+        let trimmed = self.output.trim_end_matches(' ').len();
+        self.output.truncate(trimmed);
+        if blank_line {
+            self.output.push('\n');
+        }
+        self.output.push('\n');
+        self.output.push_str(&" ".repeat(indentation));
+        self.column = indentation;
+        self.indentation = indentation;
+    }
+
+    fn fits(&self, text: &str, trailing: usize) -> bool {
+        self.column + text.chars().count() + trailing <= self.settings.max_line_length
+    }
+
+    fn print_top_level(&mut self, items: &[Item]) {
+        for (i, item) in items.iter().enumerate() {
+            if i > 0 {
+                if item.is_trailing_comment() {
+                    self.write(" ");
+                } else {
+                    self.newline(0, item.newlines > 1);
+                }
+            }
+            self.print_node(&item.node, 0);
+        }
+        if !items.is_empty() {
+            self.newline(0, false);
+        }
+    }
+
+    // Prints `node` at the current column, followed by `trailing` closing
+    // delimiters.
+    fn print_node(&mut self, node: &Node, trailing: usize) {
+        match node {
+            Node::Atom(text) | Node::Comment(text) => self.write(text),
+            Node::Colon => self.write(":"),
+            Node::Comma => self.write(","),
+            Node::List(items) => match flat(node) {
+                Some(text) if !must_break(items) && self.fits(&text, trailing) => self.write(&text),
+                _ => self.print_list(items, trailing),
+            },
+            Node::Tuple(items) => match flat(node) {
+                Some(text) if self.fits(&text, trailing) => self.write(&text),
+                _ => self.print_tuple(items),
+            },
+        }
+    }
+
+    fn print_list(&mut self, items: &[Item], trailing: usize) {
+        let column = self.column;
+        self.write("(");
+        if items.is_empty() {
+            self.write(")");
+            return;
+        }
+
+        // The arguments on the first line, and the column of the others
+        let (header, indentation) = match head_atom(items) {
+            Some(name) => match BODY_FORMS.iter().find(|(form, _)| *form == name) {
+                Some((_, header)) => (*header, column + self.settings.indentation),
+                // Align the arguments with the first one when they all fit
+                None => {
+                    let aligned = column + name.chars().count() + 2;
+                    let fits = items[1..].iter().all(|item| match flat(&item.node) {
+                        Some(text) => {
+                            aligned + text.chars().count() < self.settings.max_line_length
+                        }
+                        None => false,
+                    });
+                    if fits && aligned <= self.settings.max_line_length / 2 {
+                        (1, aligned)
+                    } else {
+                        (1, column + self.settings.indentation)
+                    }
+                }
+            },
+            // e.g. the bindings of a `let`
+            None => (0, column + 1),
+        };
+
+        let last = items.len() - 1;
+        let trailing_at = |i: usize| {
+            if i == last && !items[i].is_comment() {
+                trailing + 1
+            } else {
+                0
+            }
+        };
+
+        self.print_node(&items[0].node, trailing_at(0));
+        let mut i = 1;
+        while i <= header && i < items.len() && !items[i].is_comment() {
+            self.write(" ");
+            self.print_node(&items[i].node, trailing_at(i));
+            i += 1;
+        }
+
+        // The branches of `match` are printed next to the names they bind
+        let rest = &items[i..];
+        let pairs: &[usize] = match head_atom(items) {
+            Some("match") if !rest.iter().any(|item| item.is_comment()) => match rest.len() {
+                3 => &[0],
+                4 => &[0, 2],
+                _ => &[],
+            },
+            _ => &[],
+        };
+
+        let first = i;
+        while i < items.len() {
+            let item = &items[i];
+            if item.is_trailing_comment() {
+                self.write(" ");
+                self.print_node(&item.node, 0);
+            } else {
+                self.newline(indentation, i > first && item.newlines > 1);
+                self.print_node(&item.node, trailing_at(i));
+                if pairs.contains(&(i - first)) {
+                    i += 1;
+                    self.write(" ");
+                    self.print_node(&items[i].node, trailing_at(i));
+                }
+            }
+            i += 1;
+        }
+
+        if items[last].is_comment() {
+            self.newline(column, false);
+        }
+        self.write(")");
+    }
+
+    // The entries of a broken tuple are indented from the line opening it,
+    // and the closing brace is on its own line.
+    fn print_tuple(&mut self, items: &[Item]) {
+        let column = self.indentation;
+        let indentation = column + self.settings.indentation;
+        self.write("{");
+        for (i, entry) in tuple_entries(items).into_iter().enumerate() {
+            match entry {
+                Entry::Comment(item) if item.is_trailing_comment() => {
+                    self.write(" ");
+                    self.print_node(&item.node, 0);
+                }
+                Entry::Comment(item) => {
+                    self.newline(indentation, i > 0 && item.newlines > 1);
+                    self.print_node(&item.node, 0);
+                }
+                Entry::Pair(key, value) => {
+                    self.newline(indentation, i > 0 && key.newlines > 1);
+                    self.print_node(&key.node, 0);
+                    if !std::ptr::eq(key, value) {
+                        self.write(": ");
+                        self.print_node(&value.node, 1);
+                    }
+                    self.write(",");
+                }
+            }
+        }
+        self.newline(column, false);
+        self.write("}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(source: &str) -> String {
+        let formatted = format_source(source, &FormatterSettings::default()).unwrap();
+        let reformatted = format_source(&formatted, &FormatterSettings::default()).unwrap();
+        assert_eq!(formatted, reformatted, "formatting is not idempotent");
+        formatted
+    }
+
+    #[test]
+    fn spacing_and_indentation() {
+        assert_eq!(
+            format("(define-constant   ERR_UNAUTHORIZED (err  u401))"),
+            "(define-constant ERR_UNAUTHORIZED (err u401))\n"
+        );
+        assert_eq!(
+            format("(define-data-var counter uint u0)\n\n\n\n(define-map names principal {name: (string-ascii 32),id:uint})"),
+            "(define-data-var counter uint u0)\n\n(define-map names principal { name: (string-ascii 32), id: uint })\n"
+        );
+        assert_eq!(
+            format(
+                "(define-read-only (get-counter)\n(ok (var-get counter)))\n(define-public (increment)\n        (begin (var-set counter (+ (var-get counter) u1)) (ok true)))"
+            ),
+            "(define-read-only (get-counter)\n  (ok (var-get counter)))\n(define-public (increment)\n  (begin\n    (var-set counter (+ (var-get counter) u1))\n    (ok true)))\n"
+        );
+        assert_eq!(
+            format("(contract-call? 'SP000000000000000000002Q6VF78.pox get-pox-info)"),
+            "(contract-call? 'SP000000000000000000002Q6VF78.pox get-pox-info)\n"
+        );
+    }
+
+    #[test]
+    fn let_match_and_tuples() {
+        let source = "(define-public (withdraw (amount uint))
+    (let ((balance (default-to u0 (map-get? balances tx-sender))) (remaining (- balance amount)))
+        (match (stx-transfer? amount (as-contract tx-sender) tx-sender) success (ok {amount: amount, remaining: remaining, sender: tx-sender, recipient: (as-contract tx-sender)}) error (err error))))";
+        assert_eq!(
+            format(source),
+            "(define-public (withdraw (amount uint))
+  (let ((balance (default-to u0 (map-get? balances tx-sender)))
+        (remaining (- balance amount)))
+    (match (stx-transfer? amount (as-contract tx-sender) tx-sender)
+      success (ok {
+        amount: amount,
+        remaining: remaining,
+        sender: tx-sender,
+        recipient: (as-contract tx-sender),
+      })
+      error (err error))))
+"
+        );
+    }
+
+    #[test]
+    fn comments_and_annotations() {
+        let source = ";; Counter contract
+(define-data-var counter uint u0) ;; current value
+
+;; #[allow(unchecked_data)]
+(define-public (set (value uint))
+    (begin
+        ;; no upper bound
+        (var-set counter value)   ;; stored as is
+        (ok true)
+        ;; done
+))";
+        assert_eq!(
+            format(source),
+            ";; Counter contract
+(define-data-var counter uint u0) ;; current value
+
+;; #[allow(unchecked_data)]
+(define-public (set (value uint))
+  (begin
+    ;; no upper bound
+    (var-set counter value) ;; stored as is
+    (ok true)
+    ;; done
+  ))
+"
+        );
+    }
+
+    #[test]
+    fn syntax_errors() {
+        assert!(format_source("(define-public (foo)", &FormatterSettings::default()).is_err());
+    }
+}
@@ -1,3 +1,4 @@
+pub mod formatter;
 pub mod parser;
 use clarity::vm::costs::cost_functions::ClarityCostFunction;
 use clarity::vm::costs::{
@@ -28,6 +28,21 @@ pub struct Parser<'a> {
 pub const MAX_STRING_LEN: usize = 128;
 pub const MAX_CONTRACT_NAME_LEN: usize = 40;
 
+// Forms only allowed at the top level of a contract
+const TOP_LEVEL_FORMS: &[&str] = &[
+    "define-constant",
+    "define-data-var",
+    "define-map",
+    "define-public",
+    "define-read-only",
+    "define-private",
+    "define-fungible-token",
+    "define-non-fungible-token",
+    "define-trait",
+    "use-trait",
+    "impl-trait",
+];
+
 impl<'a> Parser<'a> {
     pub fn new(input: &'a str) -> Self {
         let mut p = Self {
@@ -98,11 +113,60 @@ impl<'a> Parser<'a> {
         }
     }
 
+    // Checks if the next token starts a top-level form at the beginning of a
+    // line, e.g. `(define-public`. When found inside a list or a tuple, it
+    // is most likely missing its closing delimiter, and the parser recovers
+    // by closing it there, to continue with the following definitions.
+    fn at_top_level_form(&self) -> bool {
+        let token = &self.tokens[self.next_token];
+        if token.token != Token::Lparen || token.span.start_column != 1 {
+            return false;
+        }
+        match self.tokens.get(self.next_token + 1) {
+            Some(PlacedToken {
+                token: Token::Ident(name),
+                ..
+            }) => TOP_LEVEL_FORMS.contains(&name.as_str()),
+            _ => false,
+        }
+    }
+
+    // Span of the last token before the next one, ignoring whitespace and comments
+    fn last_token_span(&self) -> Option<Span> {
+        self.tokens[..self.next_token]
+            .iter()
+            .rev()
+            .find(|token| !matches!(token.token, Token::Whitespace | Token::Comment(_)))
+            .map(|token| token.span.clone())
+    }
+
+    fn recover_unclosed(&mut self, opening: PlacedToken, closing: Token, span: &mut Span) {
+        self.add_diagnostic(PlacedError {
+            e: ParserError::ExpectedClosing(closing),
+            span: self.tokens[self.next_token].span.clone(),
+        });
+        self.add_diagnostic(PlacedError {
+            e: ParserError::NoteToMatchThis(opening.token),
+            span: opening.span,
+        });
+        if let Some(last_span) = self.last_token_span() {
+            span.end_line = last_span.end_line;
+            span.end_column = last_span.end_column;
+        }
+    }
+
     fn parse_list(&mut self, lparen: PlacedToken) -> PreSymbolicExpression {
         let mut nodes = vec![];
         let mut span = lparen.span.clone();
         let mut whitespace = true;
         loop {
+            whitespace |= self.ignore_whitespace();
+            if self.at_top_level_form() {
+                self.recover_unclosed(lparen, Token::Rparen, &mut span);
+                let mut e = PreSymbolicExpression::list(nodes.into_boxed_slice());
+                e.span = span;
+                return e;
+            }
             if let Some(node) = self.parse_node() {
                 if !whitespace {
                     self.add_diagnostic(PlacedError {
@@ -158,6 +222,12 @@ impl<'a> Parser<'a> {
 
         loop {
             self.ignore_whitespace();
+            if self.at_top_level_form() {
+                self.recover_unclosed(lbrace, Token::Rbrace, &mut span);
+                let mut e = PreSymbolicExpression::tuple(nodes.into_boxed_slice());
+                e.span = span;
+                return e;
+            }
             let token = self.tokens[self.next_token].clone();
             match token.token {
                 Token::Comma => {
@@ -2472,4 +2542,54 @@ mod tests {
             "expected whitespace before expression"
         );
     }
+
+    #[test]
+    fn test_recover_at_top_level_forms() {
+        let (stmts, diagnostics, success) = parse(
+            "(define-public (foo)\n    (ok u1)\n(define-read-only (bar) { a: u1\n(define-private (baz) (ok u2))",
+        );
+        assert_eq!(success, false);
+        assert_eq!(stmts.len(), 3);
+        assert_eq!(
+            stmts[0].span,
+            Span {
+                start_line: 1,
+                start_column: 1,
+                end_line: 2,
+                end_column: 11
+            }
+        );
+        assert_eq!(stmts[2].match_list().unwrap().len(), 3);
+
+        assert_eq!(diagnostics.len(), 6);
+        assert_eq!(diagnostics[0].message, "expected closing ')'");
+        assert_eq!(
+            diagnostics[0].spans[0],
+            Span {
+                start_line: 3,
+                start_column: 1,
+                end_line: 3,
+                end_column: 1
+            }
+        );
+        assert_eq!(diagnostics[1].message, "to match this '('");
+        assert_eq!(diagnostics[2].message, "expected closing '}'");
+        assert_eq!(
+            diagnostics[3].spans[0],
+            Span {
+                start_line: 3,
+                start_column: 25,
+                end_line: 3,
+                end_column: 25
+            }
+        );
+        assert_eq!(diagnostics[4].message, "expected closing ')'");
+        assert_eq!(diagnostics[5].spans[0].start_line, 3);
+
+        // Nested lists starting a line are not mistaken for definitions
+        let (stmts, diagnostics, success) = parse("(define-public (foo)\n(ok u1))");
+        assert_eq!(success, true);
+        assert_eq!(stmts.len(), 1);
+        assert_eq!(diagnostics.len(), 0);
+    }
 }
@@ -1,5 +1,7 @@
 use sha2::{Digest, Sha512_256};
 
+use super::fork::Fork;
+
 use clarity::types::chainstate::BlockHeaderHash;
 use clarity::types::chainstate::BurnchainHeaderHash;
 use clarity::types::chainstate::ConsensusHash;
@@ -8,7 +10,7 @@ use clarity::types::chainstate::StacksAddress;
 use clarity::types::chainstate::StacksBlockId;
 use clarity::types::chainstate::VRFSeed;
 use clarity::types::StacksEpochId;
-use clarity::util::hash::Sha512Trunc256Sum;
+use clarity::util::hash::{hex_bytes, to_hex, Sha512Trunc256Sum};
 use clarity::vm::analysis::AnalysisDatabase;
 use clarity::vm::database::BurnStateDB;
 use clarity::vm::database::{ClarityBackingStore, ClarityDatabase, HeadersDB};
@@ -16,10 +18,10 @@ use clarity::vm::errors::{
     CheckErrors, IncomparableError, InterpreterError, InterpreterResult as Result, RuntimeErrorType,
 };
 use clarity::vm::types::QualifiedContractIdentifier;
-use clarity::vm::types::TupleData;
+use clarity::vm::types::{SequenceData, TupleData, Value};
 use clarity::vm::EvalHook;
 use clarity::vm::StacksEpoch;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::hash::Hash;
 
 #[derive(Clone, Debug)]
@@ -31,6 +33,20 @@ pub struct Datastore {
     current_chain_tip: StacksBlockId,
     chain_height: u32,
     height_at_chain_tip: HashMap<StacksBlockId, u32>,
+    // Network whose state is read when missing from the datastore
+    fork: Option<Fork>,
+}
+
+/// Content of a `Datastore`, in a form that can be saved to disk. Blocks are
+/// identified by their height, their ids being derived from it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DatastoreState {
+    blocks: BTreeMap<u32, BTreeMap<String, String>>,
+    block_id_lookup: BTreeMap<u32, u32>,
+    metadata: Vec<(String, String, String)>,
+    open_chain_tip: u32,
+    current_chain_tip: u32,
+    chain_height: u32,
 }
 
 #[derive(Clone, Debug)]
@@ -57,6 +73,85 @@ pub struct StacksConstants {
     pub epoch_21_start_height: u32,
 }
 
+/// Burnchain data of a block, replacing the synthetic data of the burnchain
+/// when set.
+#[derive(Clone, Debug, Default)]
+pub struct BurnBlockData {
+    pub header_hash: Option<BurnchainHeaderHash>,
+    pub burn_block_height: Option<u32>,
+    pub pox_payout_addrs: Option<(Vec<TupleData>, u128)>,
+}
+
+/// Content of a `BurnBlockData`, in a form that can be saved to disk.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct BurnBlockState {
+    header_hash: Option<String>,
+    burn_block_height: Option<u32>,
+    // Hex encoded versions and hashbytes of the addresses, and payout
+    pox_payout_addrs: Option<(Vec<(String, String)>, u128)>,
+}
+
+impl BurnBlockData {
+    pub fn get_state(&self) -> BurnBlockState {
+        let buffer_to_hex = |tuple: &TupleData, name: &str| match tuple.get(name) {
+            Ok(Value::Sequence(SequenceData::Buffer(buffer))) => to_hex(&buffer.data),
+            _ => String::new(),
+        };
+        BurnBlockState {
+            header_hash: self.header_hash.map(|hash| to_hex(&hash.0)),
+            burn_block_height: self.burn_block_height,
+            pox_payout_addrs: self.pox_payout_addrs.as_ref().map(|(addrs, payout)| {
+                let addrs = addrs
+                    .iter()
+                    .map(|addr| {
+                        (
+                            buffer_to_hex(addr, "version"),
+                            buffer_to_hex(addr, "hashbytes"),
+                        )
+                    })
+                    .collect();
+                (addrs, *payout)
+            }),
+        }
+    }
+
+    pub fn from_state(state: BurnBlockState) -> Result<BurnBlockData, String> {
+        let header_hash = match state.header_hash {
+            Some(ref hash) => Some(
+                BurnchainHeaderHash::from_hex(hash)
+                    .map_err(|e| format!("invalid header hash {}: {:?}", hash, e))?,
+            ),
+            None => None,
+        };
+        let pox_payout_addrs = match state.pox_payout_addrs {
+            Some((addrs, payout)) => {
+                let mut tuples = vec![];
+                for (version, hashbytes) in addrs.iter() {
+                    let buffer = |hex: &str| {
+                        hex_bytes(hex)
+                            .map_err(|e| format!("{:?}", e))
+                            .and_then(|bytes| Value::buff_from(bytes).map_err(|e| e.to_string()))
+                            .map_err(|e| format!("invalid PoX address {}: {}", hex, e))
+                    };
+                    let tuple = TupleData::from_data(vec![
+                        ("version".into(), buffer(version)?),
+                        ("hashbytes".into(), buffer(hashbytes)?),
+                    ])
+                    .map_err(|e| e.to_string())?;
+                    tuples.push(tuple);
+                }
+                Some((tuples, payout))
+            }
+            None => None,
+        };
+        Ok(BurnBlockData {
+            header_hash,
+            burn_block_height: state.burn_block_height,
+            pox_payout_addrs,
+        })
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct BurnDatastore {
     store: HashMap<StacksBlockId, BlockInfo>,
@@ -67,6 +162,9 @@ pub struct BurnDatastore {
     current_chain_tip: StacksBlockId,
     chain_height: u32,
     height_at_chain_tip: HashMap<StacksBlockId, u32>,
+    burn_height_lookup: HashMap<u32, StacksBlockId>,
+    // Burnchain data set in the session, by Stacks block height
+    burn_blocks: BTreeMap<u32, BurnBlockData>,
     constants: StacksConstants,
 }
 
@@ -149,6 +247,7 @@ impl Datastore {
             current_chain_tip: id,
             chain_height: 0,
             height_at_chain_tip: id_height_map,
+            fork: None,
         }
     }
 
@@ -173,6 +272,62 @@ impl Datastore {
         self.current_chain_tip = self.open_chain_tip;
         self.chain_height
     }
+
+    pub fn get_state(&self) -> DatastoreState {
+        let height = |id: &StacksBlockId| {
+            *self
+                .height_at_chain_tip
+                .get(id)
+                .expect("Could not find block in height_at_chain_tip map")
+        };
+        DatastoreState {
+            blocks: self
+                .store
+                .iter()
+                .map(|(id, map)| (height(id), map.clone().into_iter().collect()))
+                .collect(),
+            block_id_lookup: self
+                .block_id_lookup
+                .iter()
+                .map(|(id, lookup_id)| (height(id), height(lookup_id)))
+                .collect(),
+            metadata: self
+                .metadata
+                .iter()
+                .map(|((contract, key), value)| (contract.clone(), key.clone(), value.clone()))
+                .collect(),
+            open_chain_tip: height(&self.open_chain_tip),
+            current_chain_tip: height(&self.current_chain_tip),
+            chain_height: self.chain_height,
+        }
+    }
+
+    pub fn from_state(state: DatastoreState) -> Datastore {
+        Datastore {
+            store: state
+                .blocks
+                .into_iter()
+                .map(|(height, map)| (height_to_id(height), map.into_iter().collect()))
+                .collect(),
+            block_id_lookup: state
+                .block_id_lookup
+                .into_iter()
+                .map(|(height, lookup_height)| (height_to_id(height), height_to_id(lookup_height)))
+                .collect(),
+            metadata: state
+                .metadata
+                .into_iter()
+                .map(|(contract, key, value)| ((contract, key), value))
+                .collect(),
+            open_chain_tip: height_to_id(state.open_chain_tip),
+            current_chain_tip: height_to_id(state.current_chain_tip),
+            chain_height: state.chain_height,
+            height_at_chain_tip: (0..=state.chain_height)
+                .map(|height| (height_to_id(height), height))
+                .collect(),
+            fork: None,
+        }
+    }
 }
 
 impl ClarityBackingStore for Datastore {
@@ -189,10 +344,14 @@ impl ClarityBackingStore for Datastore {
             .get(&self.current_chain_tip)
             .expect("Could not find current chain tip in block_id_lookup map");
 
-        if let Some(map) = self.store.get(lookup_id) {
+        let value = if let Some(map) = self.store.get(lookup_id) {
             map.get(key).map(|v| v.clone())
         } else {
             panic!("Block does not exist for current chain tip");
+        };
+        match (value, &self.fork) {
+            (None, Some(fork)) => fork.get(key),
+            (value, _) => value,
         }
     }
 
@@ -323,6 +482,9 @@ impl BurnDatastore {
         let mut id_height_map = HashMap::new();
         id_height_map.insert(id, 0);
 
+        let mut burn_height_lookup = HashMap::new();
+        burn_height_lookup.insert(0, id);
+
         BurnDatastore {
             store,
             sortition_lookup,
@@ -332,6 +494,8 @@ impl BurnDatastore {
             current_chain_tip: id,
             chain_height: 0,
             height_at_chain_tip,
+            burn_height_lookup,
+            burn_blocks: BTreeMap::new(),
             constants,
         }
     }
@@ -349,7 +513,12 @@ impl BurnDatastore {
             let bytes = height_to_hashed_bytes(height);
             let id = StacksBlockId(bytes.clone());
             let sortition_id = SortitionId(bytes.clone());
-            let block_info = height_to_block(height);
+            let mut block_info = height_to_block(height);
+            if let Some(data) = self.burn_blocks.get(&height) {
+                apply_burn_block_data(&mut block_info, data);
+            }
+            self.burn_height_lookup
+                .insert(block_info.burn_block_height, id);
             self.block_id_lookup.insert(id, current_lookup_id);
             self.height_at_chain_tip.insert(id, height);
             self.sortition_lookup.insert(sortition_id, id);
@@ -362,6 +531,83 @@ impl BurnDatastore {
         self.open_chain_tip = height_to_id(self.chain_height);
         self.current_chain_tip = self.open_chain_tip;
     }
+
+    /// Returns the burnchain data set, by Stacks block height
+    pub fn get_burn_blocks_state(&self) -> BTreeMap<u32, BurnBlockState> {
+        self.burn_blocks
+            .iter()
+            .map(|(height, data)| (*height, data.get_state()))
+            .collect()
+    }
+
+    /// Returns a burnchain with the same constants and the burnchain data of
+    /// `burn_blocks`, advanced to `height`
+    pub fn at_height(
+        &self,
+        height: u32,
+        burn_blocks: BTreeMap<u32, BurnBlockState>,
+    ) -> Result<BurnDatastore, String> {
+        let mut burn_datastore = BurnDatastore::new(self.constants.clone());
+        for (block_height, state) in burn_blocks.into_iter() {
+            burn_datastore.set_burn_block_data(block_height, BurnBlockData::from_state(state)?);
+        }
+        burn_datastore.advance_chain_tip(height);
+        Ok(burn_datastore)
+    }
+
+    /// Sets the burnchain data of the Stacks block at `height`, which can be
+    /// a block not mined yet. The fields left unset keep their previous value.
+    pub fn set_burn_block_data(&mut self, height: u32, data: BurnBlockData) {
+        let burn_block = self.burn_blocks.entry(height).or_default();
+        if data.header_hash.is_some() {
+            burn_block.header_hash = data.header_hash;
+        }
+        if data.burn_block_height.is_some() {
+            burn_block.burn_block_height = data.burn_block_height;
+        }
+        if data.pox_payout_addrs.is_some() {
+            burn_block.pox_payout_addrs = data.pox_payout_addrs;
+        }
+
+        let id = height_to_id(height);
+        if let Some(block_info) = self.store.get_mut(&id) {
+            let previous_burn_block_height = block_info.burn_block_height;
+            apply_burn_block_data(block_info, burn_block);
+            if self.burn_height_lookup.get(&previous_burn_block_height) == Some(&id) {
+                self.burn_height_lookup.remove(&previous_burn_block_height);
+            }
+            self.burn_height_lookup
+                .insert(block_info.burn_block_height, id);
+        }
+    }
+
+    // Returns the block mined at the burnchain `height`, if it precedes the
+    // block of `sortition_id`.
+    fn get_block_at_burn_height(
+        &self,
+        height: u32,
+        sortition_id: &SortitionId,
+    ) -> Option<&BlockInfo> {
+        let tip_height = self.get_burn_block_height(sortition_id)?;
+        if height >= tip_height {
+            return None;
+        }
+        self.burn_height_lookup
+            .get(&height)
+            .and_then(|id| self.store.get(id))
+    }
+}
+
+fn apply_burn_block_data(block_info: &mut BlockInfo, data: &BurnBlockData) {
+    if let Some(header_hash) = data.header_hash {
+        block_info.burn_block_header_hash = header_hash;
+    }
+    if let Some(burn_block_height) = data.burn_block_height {
+        block_info.burn_block_height = burn_block_height;
+    }
+    if let Some(ref pox_payout_addrs) = data.pox_payout_addrs {
+        block_info.pox_payout_addrs = pox_payout_addrs.clone();
+    }
 }
 
 impl HeadersDB for BurnDatastore {
@@ -472,9 +718,7 @@ impl BurnStateDB for BurnDatastore {
         height: u32,
         sortition_id: &SortitionId,
     ) -> Option<BurnchainHeaderHash> {
-        self.sortition_lookup
-            .get(sortition_id)
-            .and_then(|id| self.store.get(id))
+        self.get_block_at_burn_height(height, sortition_id)
             .and_then(|block_info| Some(block_info.burn_block_header_hash))
     }
 
@@ -506,9 +750,7 @@ impl BurnStateDB for BurnDatastore {
         height: u32,
         sortition_id: &SortitionId,
     ) -> Option<(Vec<TupleData>, u128)> {
-        self.sortition_lookup
-            .get(sortition_id)
-            .and_then(|id| self.store.get(id))
+        self.get_block_at_burn_height(height, sortition_id)
             .and_then(|block_info| Some(block_info.pox_payout_addrs.clone()))
     }
 }
@@ -600,6 +842,45 @@ impl Datastore {
         }
     }
 
+    pub fn get_fork(&self) -> Option<&Fork> {
+        self.fork.as_ref()
+    }
+
+    pub fn set_fork(&mut self, fork: Option<Fork>) {
+        self.fork = fork;
+    }
+
+    /// Remove the K-V starting with `prefix` at the open chain tip
+    pub fn remove_with_prefix(&mut self, prefix: &str) {
+        let lookup_id = self
+            .block_id_lookup
+            .get(&self.open_chain_tip)
+            .expect("Could not find open chain tip in block_id_lookup map");
+
+        if let Some(map) = self.store.get_mut(lookup_id) {
+            map.retain(|key, _| !key.starts_with(prefix));
+        }
+    }
+
+    /// List the K-V starting with `prefix` at the current chain tip, sorted by key
+    pub fn get_with_prefix(&self, prefix: &str) -> Vec<(String, String)> {
+        let lookup_id = self
+            .block_id_lookup
+            .get(&self.current_chain_tip)
+            .expect("Could not find current chain tip in block_id_lookup map");
+
+        let mut entries: Vec<(String, String)> = match self.store.get(lookup_id) {
+            Some(map) => map
+                .iter()
+                .filter(|(key, _)| key.starts_with(prefix))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+            None => panic!("Block does not exist for current chain tip"),
+        };
+        entries.sort();
+        entries
+    }
+
     pub fn make_contract_hash_key(contract: &QualifiedContractIdentifier) -> String {
         format!("clarity-contract::{}", contract)
     }
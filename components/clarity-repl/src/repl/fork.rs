@@ -0,0 +1,218 @@
+use clarity::types::StacksEpochId;
+use clarity::vm::database::{ClaritySerializable, STXBalance, StoreType};
+use clarity::vm::types::{PrincipalData, QualifiedContractIdentifier};
+use clarity::vm::ClarityVersion;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// Contract of a network, with the Clarity version and epoch it is
+/// evaluated with.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ForkedContract {
+    pub source: String,
+    pub clarity_version: ClarityVersion,
+    pub epoch: StacksEpochId,
+}
+
+/// Chain state of a network, read at a given block, typically from a
+/// Stacks node. Values are hex encoded Clarity values, as stored in the
+/// datastore.
+pub trait ForkSource: Send + Sync {
+    fn get_contract(
+        &self,
+        contract_id: &QualifiedContractIdentifier,
+    ) -> Result<Option<ForkedContract>, String>;
+
+    fn get_data_var(
+        &self,
+        contract_id: &QualifiedContractIdentifier,
+        var_name: &str,
+    ) -> Result<Option<String>, String>;
+
+    /// Returns the entry of `key` (hex encoded) in the map, as an optional
+    fn get_map_entry(
+        &self,
+        contract_id: &QualifiedContractIdentifier,
+        map_name: &str,
+        key: &str,
+    ) -> Result<Option<String>, String>;
+
+    fn get_stx_balance(&self, principal: &PrincipalData) -> Result<u128, String>;
+}
+
+// Data fetched from the fork source, which can be persisted on disk.
+#[derive(Default, Serialize, Deserialize)]
+struct ForkCache {
+    // Contracts, `None` for contracts unknown to the network
+    contracts: BTreeMap<String, Option<ForkedContract>>,
+    // Datastore entries, by key
+    entries: BTreeMap<String, Option<String>>,
+}
+
+struct ForkState {
+    cache: ForkCache,
+    // Contracts of the network deployed in the session, whose data is read from the fork
+    deployed_contracts: BTreeSet<String>,
+}
+
+/// State of a network forked into a session. Contracts are deployed in the
+/// session when they are first used, and their data-vars, map entries and
+/// the STX balances missing from the session are fetched lazily.
+#[derive(Clone)]
+pub struct Fork {
+    pub block_height: u32,
+    source: Arc<dyn ForkSource>,
+    state: Arc<Mutex<ForkState>>,
+    cache_location: Option<PathBuf>,
+}
+
+impl fmt::Debug for Fork {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Fork")
+            .field("block_height", &self.block_height)
+            .field("cache_location", &self.cache_location)
+            .finish()
+    }
+}
+
+impl Fork {
+    /// Creates a fork reading from `source`. When `cache_location` is set,
+    /// the data previously fetched is loaded from this file, and updated as
+    /// new data is fetched.
+    pub fn new(
+        source: Arc<dyn ForkSource>,
+        block_height: u32,
+        cache_location: Option<PathBuf>,
+    ) -> Fork {
+        let cache = cache_location
+            .as_ref()
+            .and_then(|path| fs::read(path).ok())
+            .and_then(|content| serde_json::from_slice(&content).ok())
+            .unwrap_or_default();
+        Fork {
+            block_height,
+            source,
+            state: Arc::new(Mutex::new(ForkState {
+                cache,
+                deployed_contracts: BTreeSet::new(),
+            })),
+            cache_location,
+        }
+    }
+
+    pub fn get_contract(
+        &self,
+        contract_id: &QualifiedContractIdentifier,
+    ) -> Result<Option<ForkedContract>, String> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(contract) = state.cache.contracts.get(&contract_id.to_string()) {
+            return Ok(contract.clone());
+        }
+        let contract = self.source.get_contract(contract_id)?;
+        state
+            .cache
+            .contracts
+            .insert(contract_id.to_string(), contract.clone());
+        self.save(&state.cache);
+        Ok(contract)
+    }
+
+    pub fn is_deployed(&self, contract_id: &QualifiedContractIdentifier) -> bool {
+        let state = self.state.lock().unwrap();
+        state.deployed_contracts.contains(&contract_id.to_string())
+    }
+
+    /// Marks a contract of the network as deployed in the session, its data
+    /// being read from the fork from now on.
+    pub fn set_deployed(&self, contract_id: &QualifiedContractIdentifier) {
+        let mut state = self.state.lock().unwrap();
+        state.deployed_contracts.insert(contract_id.to_string());
+    }
+
+    /// Returns the value of a datastore entry missing from the session.
+    pub fn get(&self, key: &str) -> Option<String> {
+        let mut state = self.state.lock().unwrap();
+        // The data of the contracts deployed in the session is not forked
+        if let Some(key) = key.strip_prefix("vm::") {
+            let contract_id = key.split("::").next()?;
+            if !state.deployed_contracts.contains(contract_id) {
+                return None;
+            }
+        }
+        if let Some(value) = state.cache.entries.get(key) {
+            return value.clone();
+        }
+        let value = match self.fetch(key) {
+            Ok(Some(value)) => value,
+            Ok(None) => return None,
+            Err(e) => {
+                println!("{}", yellow!(format!("unable to fetch {}: {}", key, e)));
+                return None;
+            }
+        };
+        state.cache.entries.insert(key.to_string(), value.clone());
+        self.save(&state.cache);
+        value
+    }
+
+    // Fetch an entry from the source, returning `None` for the entries which
+    // are not forked.
+    fn fetch(&self, key: &str) -> Result<Option<Option<String>>, String> {
+        if let Some(key) = key.strip_prefix("vm::") {
+            // vm::<contract>::<store type>::<name>[::<map key>]
+            let parts: Vec<&str> = key.splitn(4, "::").collect();
+            if parts.len() < 3 {
+                return Ok(None);
+            }
+            let contract_id = QualifiedContractIdentifier::parse(parts[0])
+                .map_err(|e| format!("invalid contract id {}", e))?;
+            let store_type = parts[1].parse::<u8>().ok();
+            if store_type == Some(StoreType::Variable as u8) && parts.len() == 3 {
+                return self.source.get_data_var(&contract_id, parts[2]).map(Some);
+            }
+            if store_type == Some(StoreType::DataMap as u8) && parts.len() == 4 {
+                return self
+                    .source
+                    .get_map_entry(&contract_id, parts[2], parts[3])
+                    .map(Some);
+            }
+        } else if let Some(key) = key.strip_prefix("vm-account::") {
+            // vm-account::<principal>::<store type>
+            let (principal, store_type) = match key.rsplit_once("::") {
+                Some(parts) => parts,
+                None => return Ok(None),
+            };
+            if store_type.parse::<u8>().ok() == Some(StoreType::STXBalance as u8) {
+                let principal = PrincipalData::parse(principal)
+                    .map_err(|e| format!("invalid principal {}", e))?;
+                let balance = self.source.get_stx_balance(&principal)?;
+                return Ok(Some(Some(STXBalance::initial(balance).serialize())));
+            }
+        }
+        Ok(None)
+    }
+
+    fn save(&self, cache: &ForkCache) {
+        let path = match self.cache_location {
+            Some(ref path) => path,
+            None => return,
+        };
+        let result = serde_json::to_vec(cache)
+            .map_err(|e| e.to_string())
+            .and_then(|content| {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+                }
+                fs::write(path, content).map_err(|e| e.to_string())
+            });
+        if let Err(e) = result {
+            println!(
+                "{}",
+                yellow!(format!("unable to write {}: {}", path.display(), e))
+            );
+        }
+    }
+}
@@ -4,9 +4,13 @@ use std::collections::{btree_map::Entry, BTreeMap, BTreeSet};
 use crate::analysis::annotation::{Annotation, AnnotationKind};
 use crate::analysis::ast_dependency_detector::{ASTDependencyDetector, Dependency};
 use crate::analysis::coverage::TestCoverageReport;
+use crate::analysis::fix::DiagnosticFix;
+use crate::analysis::version_checker::VersionChecker;
 use crate::analysis::{self, AnalysisPass as REPLAnalysisPass};
+use crate::repl::ast::build_ast as build_ast_with_recovery;
 use crate::repl::datastore::BurnDatastore;
 use crate::repl::datastore::Datastore;
+use crate::repl::datastore::{BurnBlockData, BurnBlockState};
 use crate::repl::Settings;
 use crate::utils;
 use clarity::consts::CHAIN_ID_TESTNET;
@@ -23,7 +27,7 @@ use clarity::vm::contexts::{CallStack, ContractContext, Environment, GlobalConte
 use clarity::vm::contracts::Contract;
 use clarity::vm::costs::cost_functions::ClarityCostFunction;
 use clarity::vm::costs::{runtime_cost, ExecutionCost, LimitedCostTracker};
-use clarity::vm::database::ClarityDatabase;
+use clarity::vm::database::{ClarityDatabase, StoreType};
 use clarity::vm::diagnostic::{Diagnostic, Level};
 use clarity::vm::errors::Error;
 use clarity::vm::representations::SymbolicExpressionType::{Atom, List};
@@ -37,7 +41,7 @@ use clarity::vm::{events::*, ClarityVersion};
 use clarity::vm::{ContractEvaluationResult, EvalHook};
 use clarity::vm::{CostSynthesis, ExecutionResult, ParsedContract};
 
-use super::datastore::StacksConstants;
+use super::datastore::{DatastoreState, StacksConstants};
 use super::{ClarityContract, DEFAULT_EPOCH};
 
 pub const BLOCK_LIMIT_MAINNET: ExecutionCost = ExecutionCost {
@@ -53,9 +57,26 @@ pub struct ClarityInterpreter {
     pub datastore: Datastore,
     pub burn_datastore: BurnDatastore,
     tx_sender: StandardPrincipalData,
+    tx_sponsor: Option<StandardPrincipalData>,
     accounts: BTreeSet<String>,
     tokens: BTreeMap<String, BTreeMap<String, u128>>,
     repl_settings: Settings,
+    // Fixes for the diagnostics of the last analysis of each contract
+    pub fixes: HashMap<QualifiedContractIdentifier, Vec<DiagnosticFix>>,
+}
+
+/// State of the chain and accounts of an interpreter, which can be saved to
+/// disk and restored later.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct InterpreterState {
+    datastore: DatastoreState,
+    tx_sender: String,
+    #[serde(default)]
+    tx_sponsor: Option<String>,
+    accounts: BTreeSet<String>,
+    tokens: BTreeMap<String, BTreeMap<String, u128>>,
+    #[serde(default)]
+    burn_blocks: BTreeMap<u32, BurnBlockState>,
 }
 
 #[derive(Debug)]
@@ -122,10 +143,12 @@ impl ClarityInterpreter {
         ClarityInterpreter {
             datastore,
             tx_sender,
+            tx_sponsor: None,
             accounts,
             tokens,
             repl_settings,
             burn_datastore: BurnDatastore::new(constants),
+            fixes: HashMap::new(),
         }
     }
 
@@ -266,13 +289,27 @@ impl ClarityInterpreter {
         let source_code = contract.expect_in_memory_code_source();
         let contract_identifier =
             contract.expect_resolved_contract_identifier(Some(&self.tx_sender));
-        build_ast_with_diagnostics(
+        let (ast, diagnostics, success) = build_ast_with_diagnostics(
             &contract_identifier,
             &source_code,
             &mut (),
             contract.clarity_version.clone(),
             contract.epoch.clone(),
-        )
+        );
+        if success {
+            return (ast, diagnostics, success);
+        }
+
+        // The parser of the VM stops at the first syntax error, rebuild the
+        // AST with the parser of the REPL, recovering at top-level forms, to
+        // report every error and get the AST of the following definitions.
+        // The first result is kept if the latter parser accepts the contract.
+        let (recovered_ast, recovered_diagnostics, recovered_success) =
+            build_ast_with_recovery(&contract_identifier, &source_code, &mut ());
+        if recovered_success {
+            return (ast, diagnostics, success);
+        }
+        (recovered_ast, recovered_diagnostics, recovered_success)
     }
 
     pub fn collect_annotations(
@@ -327,6 +364,21 @@ impl ClarityInterpreter {
         annotations: &Vec<Annotation>,
     ) -> Result<(ContractAnalysis, Vec<Diagnostic>), (String, Option<Diagnostic>, Option<Error>)>
     {
+        // Check the built-ins against the contract's Clarity version and epoch
+        let mut version_diagnostics = match VersionChecker::run(
+            &contract_ast.expressions,
+            contract.clarity_version,
+            contract.epoch,
+        ) {
+            Ok(diagnostics) => diagnostics,
+            Err(diagnostics) => {
+                let error = diagnostics
+                    .into_iter()
+                    .find(|diagnostic| diagnostic.level == Level::Error);
+                return Err(("Analysis".to_string(), error, None));
+            }
+        };
+
         let mut analysis_db = AnalysisDatabase::new(&mut self.datastore);
 
         // Run standard clarity analyses
@@ -345,13 +397,21 @@ impl ClarityInterpreter {
         };
 
         // Run REPL-only analyses
-        match analysis::run_analysis(
+        let (result, fixes) = analysis::run_analysis(
             &mut contract_analysis,
             &mut analysis_db,
             annotations,
             &self.repl_settings.analysis,
-        ) {
-            Ok(diagnostics) => Ok((contract_analysis, diagnostics)),
+        );
+        self.fixes.insert(
+            contract.expect_resolved_contract_identifier(Some(&self.tx_sender)),
+            fixes,
+        );
+        match result {
+            Ok(mut diagnostics) => {
+                version_diagnostics.append(&mut diagnostics);
+                Ok((contract_analysis, version_diagnostics))
+            }
             Err(mut diagnostics) => {
                 // The last diagnostic should be the error
                 let error = diagnostics.pop().unwrap();
@@ -444,6 +504,7 @@ impl ClarityInterpreter {
                 &self.burn_datastore,
             );
             let tx_sender: PrincipalData = self.tx_sender.clone().into();
+            let tx_sponsor: Option<PrincipalData> = self.tx_sponsor.clone().map(|p| p.into());
             conn.begin();
             conn.set_clarity_epoch_version(contract.epoch);
             conn.commit();
@@ -482,7 +543,7 @@ impl ClarityInterpreter {
                         &mut call_stack,
                         Some(tx_sender.clone()),
                         Some(tx_sender.clone()),
-                        None,
+                        tx_sponsor.clone(),
                     );
 
                     let result = match contract_ast.expressions[0].expr {
@@ -782,6 +843,71 @@ impl ClarityInterpreter {
         self.tx_sender.clone()
     }
 
+    /// Sets the principal sponsoring the transactions, returned by
+    /// `tx-sponsor?`.
+    pub fn set_tx_sponsor(&mut self, tx_sponsor: Option<StandardPrincipalData>) {
+        self.tx_sponsor = tx_sponsor;
+    }
+
+    pub fn get_tx_sponsor(&self) -> Option<StandardPrincipalData> {
+        self.tx_sponsor.clone()
+    }
+
+    /// Debits the fee of a transaction from the STX balance of `payer`,
+    /// failing if the balance is insufficient.
+    pub fn pay_fee(&mut self, payer: PrincipalData, fee: u64) -> Result<(), String> {
+        {
+            let conn = ClarityDatabase::new(
+                &mut self.datastore,
+                &self.burn_datastore,
+                &self.burn_datastore,
+            );
+
+            let mut global_context = GlobalContext::new(
+                false,
+                CHAIN_ID_TESTNET,
+                conn,
+                LimitedCostTracker::new_free(),
+                DEFAULT_EPOCH,
+            );
+            global_context.begin();
+            let mut cur_balance = global_context.database.get_stx_balance_snapshot(&payer);
+            let available = cur_balance.get_available_balance();
+            if available < fee as u128 {
+                global_context.roll_back();
+                return Err(format!(
+                    "{} can not pay a fee of {} µSTX (balance: {} µSTX)",
+                    payer, fee, available
+                ));
+            }
+            cur_balance.debit(fee as u128);
+            cur_balance.save();
+            global_context.commit().unwrap();
+        }
+        self.debit_token(payer.to_string(), "STX".to_string(), fee.into());
+        Ok(())
+    }
+
+    pub fn get_account_nonce(&mut self, principal: &PrincipalData) -> u64 {
+        let mut conn = ClarityDatabase::new(
+            &mut self.datastore,
+            &self.burn_datastore,
+            &self.burn_datastore,
+        );
+        conn.get_account_nonce(principal)
+    }
+
+    pub fn set_account_nonce(&mut self, principal: &PrincipalData, nonce: u64) {
+        let mut conn = ClarityDatabase::new(
+            &mut self.datastore,
+            &self.burn_datastore,
+            &self.burn_datastore,
+        );
+        conn.begin();
+        conn.set_account_nonce(principal, nonce);
+        conn.commit();
+    }
+
     pub fn advance_chain_tip(&mut self, count: u32) -> u32 {
         self.burn_datastore.advance_chain_tip(count);
         self.datastore.advance_chain_tip(count)
@@ -791,6 +917,123 @@ impl ClarityInterpreter {
         self.datastore.get_current_block_height()
     }
 
+    pub fn set_burn_block_data(&mut self, height: u32, data: BurnBlockData) {
+        self.burn_datastore.set_burn_block_data(height, data);
+    }
+
+    pub fn get_state(&self) -> InterpreterState {
+        InterpreterState {
+            datastore: self.datastore.get_state(),
+            tx_sender: self.tx_sender.to_address(),
+            tx_sponsor: self.tx_sponsor.as_ref().map(|p| p.to_address()),
+            accounts: self.accounts.clone(),
+            tokens: self.tokens.clone(),
+            burn_blocks: self.burn_datastore.get_burn_blocks_state(),
+        }
+    }
+
+    pub fn restore_state(&mut self, state: InterpreterState) -> Result<(), String> {
+        let tx_sender = PrincipalData::parse_standard_principal(&state.tx_sender)
+            .map_err(|e| format!("invalid tx-sender {}: {}", state.tx_sender, e))?;
+        let tx_sponsor = match state.tx_sponsor {
+            Some(ref tx_sponsor) => Some(
+                PrincipalData::parse_standard_principal(tx_sponsor)
+                    .map_err(|e| format!("invalid tx-sponsor {}: {}", tx_sponsor, e))?,
+            ),
+            None => None,
+        };
+        let mut datastore = Datastore::from_state(state.datastore);
+        // The burnchain is derived from the block height and the burnchain
+        // data set in the session
+        let burn_datastore = self
+            .burn_datastore
+            .at_height(datastore.get_current_block_height(), state.burn_blocks)?;
+        let fork = self.datastore.get_fork().cloned();
+        self.datastore = datastore;
+        self.datastore.set_fork(fork);
+        self.burn_datastore = burn_datastore;
+        self.tx_sender = tx_sender;
+        self.tx_sponsor = tx_sponsor;
+        self.accounts = state.accounts;
+        self.tokens = state.tokens;
+        Ok(())
+    }
+
+    pub fn get_data_var(
+        &mut self,
+        contract_id: &QualifiedContractIdentifier,
+        var_name: &str,
+    ) -> Result<Value, String> {
+        self.ensure_metadata(contract_id, StoreType::VariableMeta, "data-var", var_name)?;
+        let key = ClarityDatabase::make_key_for_trip(contract_id, StoreType::Variable, var_name);
+        match self.datastore.get(&key) {
+            Some(hex) => Value::try_deserialize_hex_untyped(&hex)
+                .map_err(|e| format!("unable to decode data-var {}: {}", var_name, e)),
+            None => Err(format!("data-var {} is not initialized", var_name)),
+        }
+    }
+
+    /// Returns the entries of a map, sorted by their serialized keys.
+    pub fn get_map_entries(
+        &mut self,
+        contract_id: &QualifiedContractIdentifier,
+        map_name: &str,
+    ) -> Result<Vec<(Value, Value)>, String> {
+        self.ensure_metadata(contract_id, StoreType::DataMapMeta, "map", map_name)?;
+        let prefix = format!(
+            "{}::",
+            ClarityDatabase::make_key_for_trip(contract_id, StoreType::DataMap, map_name)
+        );
+        let mut entries = vec![];
+        for (key, value) in self.datastore.get_with_prefix(&prefix) {
+            let key = Value::try_deserialize_hex_untyped(&key[prefix.len()..])
+                .map_err(|e| format!("unable to decode key of map {}: {}", map_name, e))?;
+            // Entries are stored as optionals, deleted entries being `none`
+            match Value::try_deserialize_hex_untyped(&value) {
+                Ok(Value::Optional(data)) => {
+                    if let Some(value) = data.data {
+                        entries.push((key, *value));
+                    }
+                }
+                _ => return Err(format!("unable to decode entry of map {}", map_name)),
+            }
+        }
+        Ok(entries)
+    }
+
+    pub fn get_map_entry(
+        &mut self,
+        contract_id: &QualifiedContractIdentifier,
+        map_name: &str,
+        key: &Value,
+    ) -> Result<Option<Value>, String> {
+        self.ensure_metadata(contract_id, StoreType::DataMapMeta, "map", map_name)?;
+        let key = ClarityDatabase::make_key_for_data_map_entry(contract_id, map_name, key);
+        match self.datastore.get(&key) {
+            Some(hex) => match Value::try_deserialize_hex_untyped(&hex) {
+                Ok(Value::Optional(data)) => Ok(data.data.map(|value| *value)),
+                _ => Err(format!("unable to decode entry of map {}", map_name)),
+            },
+            None => Ok(None),
+        }
+    }
+
+    // Check that `name` is defined by the contract, using the metadata
+    // stored when the contract was deployed.
+    fn ensure_metadata(
+        &mut self,
+        contract_id: &QualifiedContractIdentifier,
+        store_type: StoreType,
+        kind: &str,
+        name: &str,
+    ) -> Result<(), String> {
+        let key = ClarityDatabase::make_metadata_key(store_type, name);
+        match self.datastore.get_metadata(contract_id, &key) {
+            Ok(Some(_)) => Ok(()),
+            _ => Err(format!("{} {} not found in {}", kind, name, contract_id)),
+        }
+    }
+
     fn credit_token(&mut self, account: String, token: String, value: u128) {
         self.accounts.insert(account.clone());
         match self.tokens.entry(token) {
@@ -1,7 +1,9 @@
+pub mod ast;
 pub mod boot;
 pub mod datastore;
 pub mod debug;
 pub mod diagnostic;
+pub mod fork;
 pub mod interpreter;
 pub mod session;
 pub mod settings;
@@ -1,26 +1,35 @@
 use super::boot::{STACKS_BOOT_CODE_MAINNET, STACKS_BOOT_CODE_TESTNET};
+use super::datastore::BurnBlockData;
 use super::diagnostic::output_diagnostic;
+use super::fork::Fork;
+use super::interpreter::InterpreterState;
 use super::{
-    ClarityCodeSource, ClarityContract, ClarityInterpreter, ContractDeployer, DEFAULT_EPOCH,
+    ClarityCodeSource, ClarityContract, ClarityInterpreter, ContractDeployer,
+    DEFAULT_CLARITY_VERSION, DEFAULT_EPOCH,
 };
 use crate::analysis::ast_dependency_detector::{ASTDependencyDetector, Dependency};
+use crate::analysis::audit::{build_audit, ContractAudit};
 use crate::analysis::coverage::{self, TestCoverageReport};
-use crate::repl::settings::InitialContract;
+use crate::repl::settings::{FeeMode, InitialContract};
 use crate::repl::Settings;
 use crate::utils;
 use ansi_term::{Colour, Style};
+use clarity::address::C32_ADDRESS_VERSION_TESTNET_MULTISIG;
 use clarity::codec::StacksMessageCodec;
-use clarity::types::chainstate::StacksAddress;
+use clarity::types::chainstate::{BurnchainHeaderHash, StacksAddress};
 use clarity::types::StacksEpochId;
+use clarity::util::hash::Hash160;
 use clarity::vm::analysis::ContractAnalysis;
 use clarity::vm::ast::ContractAST;
-use clarity::vm::diagnostic::Diagnostic;
+use clarity::vm::diagnostic::{Diagnostic, Level};
 use clarity::vm::docs::{make_api_reference, make_define_reference, make_keyword_reference};
 use clarity::vm::errors::Error;
 use clarity::vm::functions::define::DefineFunctions;
 use clarity::vm::functions::NativeFunctions;
+use clarity::vm::representations::{SymbolicExpression, SymbolicExpressionType};
 use clarity::vm::types::{
-    PrincipalData, QualifiedContractIdentifier, StandardPrincipalData, Value,
+    PrincipalData, QualifiedContractIdentifier, SequenceData, StandardPrincipalData, TupleData,
+    Value,
 };
 use clarity::vm::variables::NativeVariables;
 use clarity::vm::{
@@ -106,6 +115,29 @@ pub struct CostsReport {
     pub cost_result: CostSynthesis,
 }
 
+// Multisig account simulated in the session
+#[derive(Clone, Debug)]
+struct MultisigPrincipal {
+    required_signatures: u8,
+    signers: Vec<String>,
+}
+
+// State of a session captured by `::snapshot`
+#[derive(Clone, Debug)]
+struct SessionSnapshot {
+    interpreter: ClarityInterpreter,
+    contracts: BTreeMap<String, BTreeMap<String, Vec<String>>>,
+    asts: BTreeMap<QualifiedContractIdentifier, ContractAST>,
+}
+
+// State of a session written by `::save_session`
+#[derive(Serialize, Deserialize)]
+struct SessionState {
+    interpreter: InterpreterState,
+    contracts: BTreeMap<String, BTreeMap<String, Vec<String>>>,
+    asts: BTreeMap<String, Vec<SymbolicExpression>>,
+}
+
 #[derive(Clone, Debug)]
 pub struct Session {
     session_id: u32,
@@ -121,6 +153,10 @@ pub struct Session {
     pub initial_contracts_analysis: Vec<(ContractAnalysis, String, String)>,
     pub show_costs: bool,
     pub executed: Vec<String>,
+    snapshots: BTreeMap<String, SessionSnapshot>,
+    // Result of the last snippet evaluated, `None` if it failed
+    last_result: Option<Value>,
+    multisig_principals: BTreeMap<String, MultisigPrincipal>,
 }
 
 impl Session {
@@ -148,6 +184,9 @@ impl Session {
             show_costs: false,
             settings,
             executed: Vec::new(),
+            snapshots: BTreeMap::new(),
+            last_result: None,
+            multisig_principals: BTreeMap::new(),
         }
     }
 
@@ -243,15 +282,28 @@ impl Session {
             cmd if cmd.starts_with("::set_tx_sender") => {
                 self.parse_and_set_tx_sender(&mut output, cmd)
             }
+            cmd if cmd.starts_with("::set_tx_sponsor") => {
+                self.parse_and_set_tx_sponsor(&mut output, cmd)
+            }
+            cmd if cmd.starts_with("::create_multisig") => {
+                self.parse_and_create_multisig(&mut output, cmd)
+            }
             cmd if cmd.starts_with("::get_assets_maps_gui") => {
                 self.get_assets_maps_gui(&mut output)
             }
             cmd if cmd.starts_with("::get_assets_maps") => self.get_accounts(&mut output),
             cmd if cmd.starts_with("::get_costs") => self.get_costs(&mut output, cmd),
+            cmd if cmd.starts_with("::get_audit_report") => {
+                self.display_audit_report(&mut output, cmd)
+            }
             cmd if cmd.starts_with("::get_contracts_gui") => {
                 self.get_contracts_functions(&mut output)
             }
             cmd if cmd.starts_with("::get_contracts") => self.get_contracts(&mut output),
+            cmd if cmd.starts_with("::get_map_gui") => self.get_map_gui(&mut output, cmd),
+            cmd if cmd.starts_with("::get_map") => self.get_map(&mut output, cmd),
+            cmd if cmd.starts_with("::get_var_gui") => self.get_var_gui(&mut output, cmd),
+            cmd if cmd.starts_with("::get_var") => self.get_var(&mut output, cmd),
             cmd if cmd.starts_with("::get_block_height_gui") => {
                 self.get_block_height_gui(&mut output)
             }
@@ -259,6 +311,30 @@ impl Session {
             cmd if cmd.starts_with("::advance_chain_tip") => {
                 self.parse_and_advance_chain_tip(&mut output, cmd)
             }
+            cmd if cmd.starts_with("::set_burn_block_header_hash") => {
+                self.parse_and_set_burn_block_header_hash(&mut output, cmd)
+            }
+            cmd if cmd.starts_with("::set_burn_block_height") => {
+                self.parse_and_set_burn_block_height(&mut output, cmd)
+            }
+            cmd if cmd.starts_with("::set_pox_payout_addrs") => {
+                self.parse_and_set_pox_payout_addrs(&mut output, cmd)
+            }
+            cmd if cmd.starts_with("::snapshot") => self.parse_and_take_snapshot(&mut output, cmd),
+            cmd if cmd.starts_with("::restore") => {
+                self.parse_and_restore_snapshot(&mut output, cmd)
+            }
+            #[cfg(feature = "cli")]
+            cmd if cmd.starts_with("::save_session") => {
+                self.parse_and_save_session(&mut output, cmd)
+            }
+            #[cfg(feature = "cli")]
+            cmd if cmd.starts_with("::load_session") => {
+                self.parse_and_load_session(&mut output, cmd)
+            }
+            cmd if cmd.starts_with("::expect") => {
+                self.parse_and_check_expectation(&mut output, cmd)
+            }
             cmd if cmd.starts_with("::toggle_costs") => self.toggle_costs(&mut output),
             cmd if cmd.starts_with("::encode") => self.encode(&mut output, cmd),
             cmd if cmd.starts_with("::decode") => self.decode(&mut output, cmd),
@@ -351,6 +427,14 @@ impl Session {
         let formatted_lines: Vec<String> = lines.map(|l| l.to_string()).collect();
         let contract_name = name.unwrap_or("<stdin>".to_string());
 
+        self.last_result = match result {
+            Ok(ref result) => match &result.result {
+                EvaluationResult::Contract(contract_result) => contract_result.result.clone(),
+                EvaluationResult::Snippet(snippet_result) => Some(snippet_result.result.clone()),
+            },
+            Err(_) => None,
+        };
+
         match result {
             Ok(result) => {
                 for diagnostic in &result.diagnostics {
@@ -501,7 +585,9 @@ impl Session {
         recipient: &str,
     ) -> Result<ExecutionResult, Vec<Diagnostic>> {
         let snippet = format!("(stx-transfer? u{} tx-sender '{})", amount, recipient);
-        self.eval(snippet.clone(), None, false)
+        // The cost of the transfer is needed for cost-based fees
+        let cost_track = self.settings.repl_settings.accounting.is_some();
+        self.eval(snippet.clone(), None, cost_track)
     }
 
     pub fn deploy_contract(
@@ -512,6 +598,8 @@ impl Session {
         test_name: Option<String>,
         ast: &mut Option<ContractAST>,
     ) -> Result<ExecutionResult, Vec<Diagnostic>> {
+        self.deploy_forked_dependencies(contract)?;
+
         let mut hooks: Vec<&mut dyn EvalHook> = Vec::new();
         let mut coverage = if let Some(test_name) = test_name {
             Some(TestCoverageReport::new(test_name.into()))
@@ -670,6 +758,7 @@ impl Session {
         let contract_identifier =
             contract.expect_resolved_contract_identifier(Some(&self.interpreter.get_tx_sender()));
 
+        self.deploy_forked_dependencies(&contract)?;
         let result = self.interpreter.run(&contract, cost_track, eval_hooks);
 
         match result {
@@ -693,6 +782,66 @@ impl Session {
         }
     }
 
+    /// Forks the state of a network into the session, advancing the chain
+    /// tip to the height of the fork.
+    pub fn set_fork(&mut self, fork: Fork) {
+        let height = self.interpreter.get_block_height();
+        if fork.block_height > height {
+            self.advance_chain_tip(fork.block_height - height);
+        }
+        self.interpreter.datastore.set_fork(Some(fork));
+    }
+
+    // Deploy the contracts of the forked network used by `contract`, their
+    // own dependencies being deployed first.
+    fn deploy_forked_dependencies(
+        &mut self,
+        contract: &ClarityContract,
+    ) -> Result<(), Vec<Diagnostic>> {
+        let fork = match self.interpreter.datastore.get_fork() {
+            Some(fork) => fork.clone(),
+            None => return Ok(()),
+        };
+        let (ast, _, _) = self.interpreter.build_ast(contract);
+        let mut dependencies = BTreeSet::new();
+        for expr in ast.expressions.iter() {
+            collect_contract_ids(expr, &mut dependencies);
+        }
+        for contract_id in dependencies {
+            if self.contracts.contains_key(&contract_id.to_string())
+                || fork.is_deployed(&contract_id)
+            {
+                continue;
+            }
+            let forked_contract = match fork.get_contract(&contract_id) {
+                Ok(Some(contract)) => contract,
+                Ok(None) => continue,
+                Err(e) => {
+                    return Err(vec![Diagnostic {
+                        level: Level::Error,
+                        message: format!("unable to fetch contract {}: {}", contract_id, e),
+                        spans: vec![],
+                        suggestion: None,
+                    }])
+                }
+            };
+            let forked_contract = ClarityContract {
+                code_source: ClarityCodeSource::ContractInMemory(forked_contract.source),
+                name: contract_id.name.to_string(),
+                deployer: ContractDeployer::ContractIdentifier(contract_id.clone()),
+                clarity_version: forked_contract.clarity_version,
+                epoch: forked_contract.epoch,
+            };
+            self.deploy_contract(&forked_contract, None, false, None, &mut None)?;
+            // The data initialized by the deployment is read from the fork instead
+            self.interpreter
+                .datastore
+                .remove_with_prefix(&format!("vm::{}::", contract_id));
+            fork.set_deployed(&contract_id);
+        }
+        Ok(())
+    }
+
     pub fn lookup_api_reference(&self, keyword: &str) -> Option<&String> {
         self.api_reference.get(keyword)
     }
@@ -707,6 +856,42 @@ impl Session {
         keys
     }
 
+    /// Returns the identifiers of the contracts deployed in the session, and
+    /// the names of the functions, maps, variables, constants and tokens
+    /// they define.
+    pub fn get_contracts_symbols(&self) -> BTreeSet<String> {
+        let tx_sender = self.interpreter.get_tx_sender();
+        let mut symbols = BTreeSet::new();
+        for (contract_id, ast) in self.asts.iter() {
+            symbols.insert(format!("'{}", contract_id));
+            if contract_id.issuer == tx_sender {
+                symbols.insert(format!(".{}", contract_id.name));
+            }
+            for expr in ast.expressions.iter() {
+                let list = match expr.match_list() {
+                    Some(list) if list.len() > 1 => list,
+                    _ => continue,
+                };
+                let is_define = list[0]
+                    .match_atom()
+                    .and_then(|name| DefineFunctions::lookup_by_name(name))
+                    .is_some();
+                if !is_define {
+                    continue;
+                }
+                // The name is the first element of the signature of functions
+                let name = match list[1].match_list() {
+                    Some(signature) => signature.get(0).and_then(|e| e.match_atom()),
+                    None => list[1].match_atom(),
+                };
+                if let Some(name) = name {
+                    symbols.insert(name.to_string());
+                }
+            }
+        }
+        symbols
+    }
+
     fn display_help(&self, output: &mut Vec<String>) {
         let help_colour = Colour::Yellow;
         let coming_soon_colour = Colour::Black.bold();
@@ -735,6 +920,18 @@ impl Session {
             "{}",
             help_colour.paint("::set_tx_sender <principal>\t\tSet tx-sender variable to principal")
         ));
+        output.push(format!(
+            "{}",
+            help_colour.paint(
+                "::set_tx_sponsor [<principal>]\t\tSet the sponsor of the transactions, or remove it"
+            )
+        ));
+        output.push(format!(
+            "{}",
+            help_colour.paint(
+                "::create_multisig <n> <principal>...\tDerive a multisig principal requiring <n> signatures"
+            )
+        ));
         output.push(format!(
             "{}",
             help_colour.paint("::get_assets_maps\t\t\tGet assets maps for active accounts")
@@ -747,6 +944,22 @@ impl Session {
             "{}",
             help_colour.paint("::get_contracts\t\t\t\tGet contracts")
         ));
+        output.push(format!(
+            "{}",
+            help_colour.paint(
+                "::get_audit_report <contract-id>\tDisplay the data flow and calls of a contract"
+            )
+        ));
+        output.push(format!(
+            "{}",
+            help_colour.paint(
+                "::get_map <contract-id> <map> [key]\tDisplay the entries of a map, or the entry of key"
+            )
+        ));
+        output.push(format!(
+            "{}",
+            help_colour.paint("::get_var <contract-id> <var>\t\tDisplay the value of a data-var")
+        ));
         output.push(format!(
             "{}",
             help_colour.paint("::get_block_height\t\t\tGet current block height")
@@ -755,6 +968,43 @@ impl Session {
             "{}",
             help_colour.paint("::advance_chain_tip <count>\t\tSimulate mining of <count> blocks")
         ));
+        output.push(format!(
+            "{}",
+            help_colour.paint(
+                "::set_burn_block_header_hash <height> <hash>\tSet the burnchain header hash of block <height>"
+            )
+        ));
+        output.push(format!(
+            "{}",
+            help_colour.paint(
+                "::set_burn_block_height <height> <burn-height>\tSet the burnchain height of block <height>"
+            )
+        ));
+        output.push(format!(
+            "{}",
+            help_colour.paint(
+                "::set_pox_payout_addrs <height> <payout> <pox-addr>...\tSet the PoX payouts of block <height>"
+            )
+        ));
+        output.push(format!(
+            "{}",
+            help_colour.paint("::snapshot <name>\t\t\tSave the state of the session as <name>")
+        ));
+        output.push(format!(
+            "{}",
+            help_colour
+                .paint("::restore <name>\t\t\tRestore the state of the session saved as <name>")
+        ));
+        output.push(format!(
+            "{}",
+            help_colour
+                .paint("::save_session <filename>\t\tWrite the state of the session to a file")
+        ));
+        output.push(format!(
+            "{}",
+            help_colour
+                .paint("::load_session <filename>\t\tRestore the state of the session from a file")
+        ));
         output.push(format!(
             "{}",
             help_colour.paint("::toggle_costs\t\t\t\tDisplay cost analysis after every expression")
@@ -776,6 +1026,10 @@ impl Session {
             "{}",
             help_colour.paint("::read <filename>\t\t\tRead expressions from a file")
         ));
+        output.push(format!(
+            "{}",
+            help_colour.paint("::expect <value>\t\t\tCheck the result of the last expression")
+        ));
     }
 
     fn parse_and_advance_chain_tip(&mut self, output: &mut Vec<String>, command: &str) {
@@ -805,6 +1059,144 @@ impl Session {
         self.interpreter.advance_chain_tip(count)
     }
 
+    fn parse_and_set_burn_block_header_hash(&mut self, output: &mut Vec<String>, command: &str) {
+        let args: Vec<_> = command.split_whitespace().collect();
+        if args.len() != 3 {
+            return output.push(red!("Usage: ::set_burn_block_header_hash <height> <hash>"));
+        }
+        let height = match args[1].parse::<u32>() {
+            Ok(height) => height,
+            Err(_) => return output.push(red!("Unable to parse height")),
+        };
+        match self.set_burn_block_header_hash(height, args[2]) {
+            Ok(()) => output.push(green!(format!(
+                "Burnchain header hash of block {} set to {}",
+                height, args[2]
+            ))),
+            Err(e) => output.push(red!(e)),
+        }
+    }
+
+    /// Sets the hash of the burnchain block of the Stacks block at `height`,
+    /// returned by `get-burn-block-info?` and `get-block-info?`.
+    pub fn set_burn_block_header_hash(&mut self, height: u32, hash: &str) -> Result<(), String> {
+        let header_hash = BurnchainHeaderHash::from_hex(hash.trim_start_matches("0x"))
+            .map_err(|e| format!("Unable to parse header hash {}: {:?}", hash, e))?;
+        self.interpreter.set_burn_block_data(
+            height,
+            BurnBlockData {
+                header_hash: Some(header_hash),
+                ..Default::default()
+            },
+        );
+        Ok(())
+    }
+
+    fn parse_and_set_burn_block_height(&mut self, output: &mut Vec<String>, command: &str) {
+        let args: Vec<_> = command.split_whitespace().collect();
+        if args.len() != 3 {
+            return output.push(red!(
+                "Usage: ::set_burn_block_height <height> <burn-height>"
+            ));
+        }
+        let (height, burn_block_height) = match (args[1].parse::<u32>(), args[2].parse::<u32>()) {
+            (Ok(height), Ok(burn_block_height)) => (height, burn_block_height),
+            _ => return output.push(red!("Unable to parse height")),
+        };
+        self.set_burn_block_height(height, burn_block_height);
+        output.push(green!(format!(
+            "Burnchain height of block {} set to {}",
+            height, burn_block_height
+        )));
+    }
+
+    /// Sets the height of the burnchain block of the Stacks block at `height`,
+    /// returned by `burn-block-height` and used to look up burnchain blocks.
+    pub fn set_burn_block_height(&mut self, height: u32, burn_block_height: u32) {
+        self.interpreter.set_burn_block_data(
+            height,
+            BurnBlockData {
+                burn_block_height: Some(burn_block_height),
+                ..Default::default()
+            },
+        );
+    }
+
+    fn parse_and_set_pox_payout_addrs(&mut self, output: &mut Vec<String>, command: &str) {
+        let args: Vec<_> = command.splitn(4, ' ').collect();
+        if args.len() < 3 {
+            return output.push(red!(
+                "Usage: ::set_pox_payout_addrs <height> <payout> <pox-addr>..."
+            ));
+        }
+        let (height, payout) = match (args[1].parse::<u32>(), args[2].parse::<u128>()) {
+            (Ok(height), Ok(payout)) => (height, payout),
+            _ => return output.push(red!("Unable to parse height and payout")),
+        };
+        let pox_addrs = args.get(3).unwrap_or(&"");
+        match self.set_pox_payout_addrs(height, pox_addrs, payout) {
+            Ok(count) => output.push(green!(format!(
+                "{} PoX addresses paid {} in block {}",
+                count, payout, height
+            ))),
+            Err(e) => output.push(red!(e)),
+        }
+    }
+
+    /// Sets the PoX reward addresses paid by the burnchain block of the
+    /// Stacks block at `height`, and the amount paid. `pox_addrs` are Clarity
+    /// expressions of `{version: (buff 1), hashbytes: (buff 32)}` tuples.
+    /// Returns the number of addresses.
+    pub fn set_pox_payout_addrs(
+        &mut self,
+        height: u32,
+        pox_addrs: &str,
+        payout: u128,
+    ) -> Result<usize, String> {
+        let result = self
+            .eval(format!("(list {})", pox_addrs), None, false)
+            .map_err(|_| format!("Unable to evaluate PoX addresses {}", pox_addrs))?;
+        let value = match result.result {
+            EvaluationResult::Contract(contract_result) => contract_result.result,
+            EvaluationResult::Snippet(snippet_result) => Some(snippet_result.result),
+        };
+        let items = match value {
+            Some(Value::Sequence(SequenceData::List(list))) => list.data,
+            _ => return Err(format!("Unable to evaluate PoX addresses {}", pox_addrs)),
+        };
+        let mut addrs = vec![];
+        for item in items.into_iter() {
+            let is_buffer = |tuple: &TupleData, name: &str, max_len: usize| match tuple.get(name) {
+                Ok(Value::Sequence(SequenceData::Buffer(buffer))) => {
+                    !buffer.data.is_empty() && buffer.data.len() <= max_len
+                }
+                _ => false,
+            };
+            match item {
+                Value::Tuple(tuple)
+                    if is_buffer(&tuple, "version", 1) && is_buffer(&tuple, "hashbytes", 32) =>
+                {
+                    addrs.push(tuple)
+                }
+                item => {
+                    return Err(format!(
+                    "Expected a {{version: (buff 1), hashbytes: (buff 32)}} PoX address, got {}",
+                    item
+                ))
+                }
+            }
+        }
+        let count = addrs.len();
+        self.interpreter.set_burn_block_data(
+            height,
+            BurnBlockData {
+                pox_payout_addrs: Some((addrs, payout)),
+                ..Default::default()
+            },
+        );
+        Ok(count)
+    }
+
     fn parse_and_set_tx_sender(&mut self, output: &mut Vec<String>, command: &str) {
         let args: Vec<_> = command.split(' ').collect();
 
@@ -835,6 +1227,349 @@ impl Session {
         self.interpreter.get_tx_sender().to_address()
     }
 
+    fn parse_and_set_tx_sponsor(&mut self, output: &mut Vec<String>, command: &str) {
+        let args: Vec<_> = command.split_whitespace().collect();
+        match args.len() {
+            1 => {
+                self.set_tx_sponsor(None);
+                output.push(green!("tx-sponsor removed"));
+            }
+            2 => match PrincipalData::parse_standard_principal(&args[1]) {
+                Ok(tx_sponsor) => {
+                    self.set_tx_sponsor(Some(tx_sponsor.to_address()));
+                    output.push(green!(format!("tx-sponsor switched to {}", tx_sponsor)));
+                }
+                _ => output.push(red!("Unable to parse the address")),
+            },
+            _ => output.push(red!("Usage: ::set_tx_sponsor [<address>]")),
+        }
+    }
+
+    /// Sets the principal sponsoring the transactions, `None` for
+    /// transactions paying their own fees.
+    pub fn set_tx_sponsor(&mut self, address: Option<String>) {
+        let tx_sponsor = address.map(|address| {
+            PrincipalData::parse_standard_principal(&address).expect("Unable to parse address")
+        });
+        self.interpreter.set_tx_sponsor(tx_sponsor)
+    }
+
+    pub fn get_tx_sponsor(&self) -> Option<String> {
+        self.interpreter
+            .get_tx_sponsor()
+            .map(|tx_sponsor| tx_sponsor.to_address())
+    }
+
+    /// Debits the fee of a transaction from the STX balance of `payer`,
+    /// usually its sponsor or its sender.
+    pub fn pay_fee(&mut self, payer: &str, fee: u64) -> Result<(), String> {
+        let payer = PrincipalData::parse_standard_principal(payer)
+            .map_err(|e| format!("Unable to parse address {}: {}", payer, e))?;
+        self.interpreter.pay_fee(payer.into(), fee)
+    }
+
+    /// Returns the fee charged by the session for a transaction of the given
+    /// cost, `None` if the fees are not charged.
+    pub fn get_transaction_fee(&self, cost: Option<&CostSynthesis>) -> Option<u64> {
+        let accounting = self.settings.repl_settings.accounting.as_ref()?;
+        let fee = match accounting.fee_mode {
+            FeeMode::Flat => accounting.fee,
+            FeeMode::Cost => {
+                // Basis points of the block limit, rounded up
+                let basis_points: u64 = match cost {
+                    Some(cost) => vec![
+                        (cost.total.runtime, cost.limit.runtime),
+                        (cost.total.read_count, cost.limit.read_count),
+                        (cost.total.read_length, cost.limit.read_length),
+                        (cost.total.write_count, cost.limit.write_count),
+                        (cost.total.write_length, cost.limit.write_length),
+                    ]
+                    .into_iter()
+                    .filter(|(_, limit)| *limit > 0)
+                    .map(|(used, limit)| {
+                        ((used as u128 * 10_000 + limit as u128 - 1) / limit as u128) as u64
+                    })
+                    .sum(),
+                    None => 0,
+                };
+                accounting.fee.saturating_mul(basis_points.max(1))
+            }
+        };
+        Some(fee)
+    }
+
+    /// Checks the nonce of a transaction sent by `sender`, when the nonces
+    /// are tracked in the session, and returns the nonce of the transaction.
+    pub fn check_nonce(&mut self, sender: &str, nonce: Option<u64>) -> Result<Option<u64>, String> {
+        if self.settings.repl_settings.accounting.is_none() {
+            return Ok(nonce);
+        }
+        let principal = PrincipalData::parse(sender)
+            .map_err(|e| format!("Unable to parse address {}: {}", sender, e))?;
+        let expected = self.interpreter.get_account_nonce(&principal);
+        match nonce {
+            Some(nonce) if nonce < expected => {
+                Err(format!("nonce {} of {} was already used", nonce, sender))
+            }
+            Some(nonce) if nonce > expected => Err(format!(
+                "expected nonce {} for {}, got {}",
+                expected, sender, nonce
+            )),
+            _ => Ok(Some(expected)),
+        }
+    }
+
+    /// Returns the nonce of an account, when the nonces are tracked in the
+    /// session.
+    pub fn get_account_nonce(&mut self, account: &str) -> Option<u64> {
+        self.settings.repl_settings.accounting.as_ref()?;
+        let principal = PrincipalData::parse(account).ok()?;
+        Some(self.interpreter.get_account_nonce(&principal))
+    }
+
+    /// Consumes the current nonce of `sender`, when the nonces are tracked
+    /// in the session.
+    pub fn increment_nonce(&mut self, sender: &str) -> Result<(), String> {
+        if self.settings.repl_settings.accounting.is_none() {
+            return Ok(());
+        }
+        let principal = PrincipalData::parse(sender)
+            .map_err(|e| format!("Unable to parse address {}: {}", sender, e))?;
+        let nonce = self.interpreter.get_account_nonce(&principal);
+        self.interpreter.set_account_nonce(&principal, nonce + 1);
+        Ok(())
+    }
+
+    fn parse_and_create_multisig(&mut self, output: &mut Vec<String>, command: &str) {
+        let args: Vec<_> = command.split_whitespace().collect();
+        if args.len() < 3 {
+            return output.push(red!("Usage: ::create_multisig <n> <address>..."));
+        }
+        let required_signatures = match args[1].parse::<u8>() {
+            Ok(required_signatures) => required_signatures,
+            Err(_) => return output.push(red!("Unable to parse the number of signatures")),
+        };
+        let signers: Vec<String> = args[2..].iter().map(|s| s.to_string()).collect();
+        match self.create_multisig_principal(required_signatures, &signers) {
+            Ok(address) => output.push(green!(format!(
+                "{} ({} of {} signatures)",
+                address,
+                required_signatures,
+                signers.len()
+            ))),
+            Err(e) => output.push(red!(e)),
+        }
+    }
+
+    /// Derives the principal of a multisig account from the addresses of its
+    /// signers, `required_signatures` of them having to sign its transactions.
+    /// Unlike on chain, where it is derived from the public keys of the signers,
+    /// the address is only meaningful in the session.
+    pub fn create_multisig_principal(
+        &mut self,
+        required_signatures: u8,
+        signers: &[String],
+    ) -> Result<String, String> {
+        if required_signatures == 0 || required_signatures as usize > signers.len() {
+            return Err(format!(
+                "Unable to require {} signatures from {} signers",
+                required_signatures,
+                signers.len()
+            ));
+        }
+        let mut bytes = vec![required_signatures];
+        let mut addresses = vec![];
+        for signer in signers.iter() {
+            let signer = PrincipalData::parse_standard_principal(signer)
+                .map_err(|e| format!("Unable to parse address {}: {}", signer, e))?;
+            if addresses.contains(&signer.to_address()) {
+                return Err(format!("Duplicate signer {}", signer));
+            }
+            bytes.extend_from_slice(&signer.1);
+            addresses.push(signer.to_address());
+        }
+        let address = StandardPrincipalData(
+            C32_ADDRESS_VERSION_TESTNET_MULTISIG,
+            Hash160::from_data(&bytes).0,
+        )
+        .to_address();
+        self.multisig_principals.insert(
+            address.clone(),
+            MultisigPrincipal {
+                required_signatures,
+                signers: addresses,
+            },
+        );
+        Ok(address)
+    }
+
+    /// Checks that a transaction sent by `sender` is signed by enough of its
+    /// signers, when `sender` is a multisig principal of the session.
+    pub fn check_signatures(&self, sender: &str, signatures: &[String]) -> Result<(), String> {
+        let multisig = match self.multisig_principals.get(sender) {
+            Some(multisig) => multisig,
+            None if signatures.is_empty() => return Ok(()),
+            None => return Err(format!("{} is not a multisig principal", sender)),
+        };
+        let mut signers = BTreeSet::new();
+        for signature in signatures.iter() {
+            if !multisig.signers.contains(signature) {
+                return Err(format!("{} is not a signer of {}", signature, sender));
+            }
+            signers.insert(signature);
+        }
+        if signers.len() < multisig.required_signatures as usize {
+            return Err(format!(
+                "{} requires {} signatures, got {}",
+                sender,
+                multisig.required_signatures,
+                signers.len()
+            ));
+        }
+        Ok(())
+    }
+
+    /// Saves the chain state, accounts and deployed contracts of the session
+    /// under `name`, replacing any previous snapshot with the same name.
+    pub fn take_snapshot(&mut self, name: &str) {
+        let snapshot = SessionSnapshot {
+            interpreter: self.interpreter.clone(),
+            contracts: self.contracts.clone(),
+            asts: self.asts.clone(),
+        };
+        self.snapshots.insert(name.to_string(), snapshot);
+    }
+
+    /// Restores the session to the state saved under `name`. The snapshot is
+    /// kept, so that it can be restored again.
+    pub fn restore_snapshot(&mut self, name: &str) -> Result<(), String> {
+        let snapshot = self
+            .snapshots
+            .get(name)
+            .ok_or(format!("Unknown snapshot {}", name))?
+            .clone();
+        self.interpreter = snapshot.interpreter;
+        self.contracts = snapshot.contracts;
+        self.asts = snapshot.asts;
+        Ok(())
+    }
+
+    pub fn save_session(&self, path: &str) -> Result<(), String> {
+        let state = SessionState {
+            interpreter: self.interpreter.get_state(),
+            contracts: self.contracts.clone(),
+            asts: self
+                .asts
+                .iter()
+                .map(|(contract_id, ast)| (contract_id.to_string(), ast.expressions.clone()))
+                .collect(),
+        };
+        let content = serde_json::to_vec(&state)
+            .map_err(|e| format!("unable to serialize session: {}", e))?;
+        fs::write(path, content).map_err(|e| format!("unable to write {}: {}", path, e))
+    }
+
+    pub fn load_session(&mut self, path: &str) -> Result<(), String> {
+        let content = fs::read(path).map_err(|e| format!("unable to read {}: {}", path, e))?;
+        let state: SessionState = serde_json::from_slice(&content)
+            .map_err(|e| format!("unable to parse {}: {}", path, e))?;
+        let mut asts = BTreeMap::new();
+        for (contract_id, expressions) in state.asts.into_iter() {
+            let contract_id = QualifiedContractIdentifier::parse(&contract_id)
+                .map_err(|e| format!("invalid contract id {}: {}", contract_id, e))?;
+            let mut ast = ContractAST::new(contract_id.clone(), vec![]);
+            ast.expressions = expressions;
+            asts.insert(contract_id, ast);
+        }
+        self.interpreter.restore_state(state.interpreter)?;
+        self.contracts = state.contracts;
+        self.asts = asts;
+        Ok(())
+    }
+
+    /// Checks that the last snippet evaluated returned `expected`, as
+    /// displayed in the console (whitespaces are not significant).
+    pub fn check_expectation(&self, expected: &str) -> Result<(), String> {
+        let normalize = |value: &str| value.split_whitespace().collect::<Vec<_>>().join(" ");
+        let expected = normalize(expected);
+        match self.last_result {
+            Some(ref value) if normalize(&value.to_string()) == expected => Ok(()),
+            Some(ref value) => Err(format!("expected {}, got {}", expected, value)),
+            None => Err(format!("expected {}, got no result", expected)),
+        }
+    }
+
+    fn parse_and_check_expectation(&mut self, output: &mut Vec<String>, command: &str) {
+        let expected = match command.split_once(' ') {
+            Some((_, expected)) if !expected.trim().is_empty() => expected,
+            _ => return output.push(red!("Usage: ::expect <value>")),
+        };
+        match self.check_expectation(expected) {
+            Ok(()) => output.push(green!(format!("✔ {}", expected.trim()))),
+            Err(e) => output.push(red!(format!("x {}", e))),
+        }
+    }
+
+    fn parse_and_take_snapshot(&mut self, output: &mut Vec<String>, command: &str) {
+        let args: Vec<_> = command.split_whitespace().collect();
+        if args.len() != 2 {
+            return output.push(red!("Usage: ::snapshot <name>"));
+        }
+        self.take_snapshot(args[1]);
+        let height = self.interpreter.get_block_height();
+        output.push(green!(format!(
+            "Snapshot {} taken at block height {}",
+            args[1], height
+        )));
+    }
+
+    fn parse_and_restore_snapshot(&mut self, output: &mut Vec<String>, command: &str) {
+        let args: Vec<_> = command.split_whitespace().collect();
+        if args.len() != 2 {
+            return output.push(red!("Usage: ::restore <name>"));
+        }
+        match self.restore_snapshot(args[1]) {
+            Ok(()) => {
+                let height = self.interpreter.get_block_height();
+                output.push(green!(format!(
+                    "Snapshot {} restored, current height: {}",
+                    args[1], height
+                )));
+            }
+            Err(e) => output.push(red!(e)),
+        }
+    }
+
+    #[cfg(feature = "cli")]
+    fn parse_and_save_session(&mut self, output: &mut Vec<String>, command: &str) {
+        let filename = match command.split_once(" ") {
+            Some((_, filename)) => filename.trim(),
+            _ => return output.push(red!("Usage: ::save_session <filename>")),
+        };
+        match self.save_session(filename) {
+            Ok(()) => output.push(green!(format!("Session saved to {}", filename))),
+            Err(e) => output.push(red!(e)),
+        }
+    }
+
+    #[cfg(feature = "cli")]
+    fn parse_and_load_session(&mut self, output: &mut Vec<String>, command: &str) {
+        let filename = match command.split_once(" ") {
+            Some((_, filename)) => filename.trim(),
+            _ => return output.push(red!("Usage: ::load_session <filename>")),
+        };
+        match self.load_session(filename) {
+            Ok(()) => {
+                let height = self.interpreter.get_block_height();
+                output.push(green!(format!(
+                    "Session loaded from {}, current height: {}",
+                    filename, height
+                )));
+            }
+            Err(e) => output.push(red!(e)),
+        }
+    }
+
     fn get_block_height(&mut self, output: &mut Vec<String>) {
         let height = self.interpreter.get_block_height();
         output.push(green!(format!("Current height: {}", height)));
@@ -928,32 +1663,212 @@ impl Session {
         self.run_snippet(output, true, &snippet.to_string());
     }
 
-    #[cfg(feature = "cli")]
-    fn get_accounts(&self, output: &mut Vec<String>) {
-        let accounts = self.interpreter.get_accounts();
-        if accounts.len() > 0 {
-            let tokens = self.interpreter.get_tokens();
-            let mut headers = vec!["Address".to_string()];
-            headers.append(&mut tokens.clone());
-            let mut headers_cells = vec![];
-            for header in headers.iter() {
-                headers_cells.push(Cell::new(&header));
-            }
-            let mut table = Table::new();
-            table.add_row(Row::new(headers_cells));
-            for account in accounts.iter() {
-                let mut cells = vec![];
+    /// Returns the data flow and call graph model of a deployed contract. The
+    /// contract can be identified by its full id, or by `.name` for contracts
+    /// deployed by the current tx-sender.
+    pub fn get_audit_report(&self, contract_id: &str) -> Option<ContractAudit> {
+        let contract_id = self.resolve_contract_id(contract_id)?;
+        self.asts
+            .get(&contract_id)
+            .map(|ast| build_audit(&ast.expressions))
+    }
 
-                if let Some(name) = self.get_account_name(account) {
-                    cells.push(Cell::new(&format!("{} ({})", account, name)));
-                } else {
-                    cells.push(Cell::new(account));
-                }
+    // Parse a contract id, `.name` designating a contract deployed by the
+    // current tx-sender.
+    fn resolve_contract_id(&self, contract_id: &str) -> Option<QualifiedContractIdentifier> {
+        let contract_id = if contract_id.starts_with('.') {
+            format!("{}{}", self.get_tx_sender(), contract_id)
+        } else {
+            contract_id.to_string()
+        };
+        QualifiedContractIdentifier::parse(&contract_id).ok()
+    }
+
+    fn display_audit_report(&self, output: &mut Vec<String>, cmd: &str) {
+        let args: Vec<_> = cmd.split_whitespace().collect();
+        if args.len() != 2 {
+            output.push(red!("Usage: ::get_audit_report <contract-id>"));
+            return;
+        }
+        match self.get_audit_report(args[1]) {
+            Some(report) => match serde_json::to_string_pretty(&report) {
+                Ok(report) => output.push(report),
+                Err(e) => output.push(red!(format!("Unable to serialize report: {}", e))),
+            },
+            None => output.push(red!(format!("Unknown contract {}", args[1]))),
+        }
+    }
+
+    /// Returns the entries of a map of a deployed contract, or the entry of
+    /// `key` if specified.
+    pub fn get_map_entries(
+        &mut self,
+        contract_id: &str,
+        map_name: &str,
+        key: Option<&Value>,
+    ) -> Result<Vec<(Value, Value)>, String> {
+        let contract_id = self
+            .resolve_contract_id(contract_id)
+            .ok_or(format!("Unknown contract {}", contract_id))?;
+        match key {
+            Some(key) => Ok(self
+                .interpreter
+                .get_map_entry(&contract_id, map_name, key)?
+                .map(|value| vec![(key.clone(), value)])
+                .unwrap_or_default()),
+            None => self.interpreter.get_map_entries(&contract_id, map_name),
+        }
+    }
+
+    /// Returns the value of a data-var of a deployed contract.
+    pub fn get_data_var(&mut self, contract_id: &str, var_name: &str) -> Result<Value, String> {
+        let contract_id = self
+            .resolve_contract_id(contract_id)
+            .ok_or(format!("Unknown contract {}", contract_id))?;
+        self.interpreter.get_data_var(&contract_id, var_name)
+    }
+
+    fn parse_and_get_map_entries(
+        &mut self,
+        output: &mut Vec<String>,
+        cmd: &str,
+    ) -> Option<Vec<(Value, Value)>> {
+        let args: Vec<_> = cmd.trim().splitn(4, ' ').collect();
+        if args.len() < 3 {
+            output.push(red!("Usage: ::get_map <contract-id> <map-name> [key]"));
+            return None;
+        }
+        let key = match args.get(3) {
+            Some(snippet) => Some(self.evaluate_map_key(output, snippet)?),
+            None => None,
+        };
+        match self.get_map_entries(args[1], args[2], key.as_ref()) {
+            Ok(entries) => Some(entries),
+            Err(e) => {
+                output.push(red!(e));
+                None
+            }
+        }
+    }
+
+    fn evaluate_map_key(&mut self, output: &mut Vec<String>, snippet: &str) -> Option<Value> {
+        match self.eval(snippet.to_string(), None, false) {
+            Ok(result) => match result.result {
+                EvaluationResult::Contract(contract_result) => {
+                    if contract_result.result.is_none() {
+                        output.push(red!("No value"));
+                    }
+                    contract_result.result
+                }
+                EvaluationResult::Snippet(snippet_result) => Some(snippet_result.result),
+            },
+            Err(diagnostics) => {
+                let lines: Vec<String> = snippet.split('\n').map(|s| s.to_string()).collect();
+                for d in diagnostics {
+                    output.append(&mut output_diagnostic(&d, &"key".to_string(), &lines));
+                }
+                None
+            }
+        }
+    }
+
+    fn get_map(&mut self, output: &mut Vec<String>, cmd: &str) {
+        let entries = match self.parse_and_get_map_entries(output, cmd) {
+            Some(entries) => entries,
+            None => return,
+        };
+        if entries.is_empty() {
+            output.push(yellow!("No entries"));
+        }
+        for (key, value) in entries {
+            output.push(green!(format!(
+                "{} => {}",
+                utils::value_to_string(&key),
+                utils::value_to_string(&value)
+            )));
+        }
+    }
+
+    fn get_map_gui(&mut self, output: &mut Vec<String>, cmd: &str) {
+        let entries = match self.parse_and_get_map_entries(output, cmd) {
+            Some(entries) => entries,
+            None => return,
+        };
+        let entries: Vec<_> = entries
+            .iter()
+            .map(|(key, value)| {
+                json!({
+                    "key": utils::value_to_string(key),
+                    "value": utils::value_to_string(value),
+                })
+            })
+            .collect();
+        output.push(String::from("get_map_start"));
+        output.push(json!(entries).to_string());
+        output.push(String::from("get_map_end"));
+    }
+
+    fn parse_and_get_data_var(&mut self, output: &mut Vec<String>, cmd: &str) -> Option<Value> {
+        let args: Vec<_> = cmd.split_whitespace().collect();
+        if args.len() != 3 {
+            output.push(red!("Usage: ::get_var <contract-id> <var-name>"));
+            return None;
+        }
+        match self.get_data_var(args[1], args[2]) {
+            Ok(value) => Some(value),
+            Err(e) => {
+                output.push(red!(e));
+                None
+            }
+        }
+    }
+
+    fn get_var(&mut self, output: &mut Vec<String>, cmd: &str) {
+        if let Some(value) = self.parse_and_get_data_var(output, cmd) {
+            output.push(green!(utils::value_to_string(&value)));
+        }
+    }
+
+    fn get_var_gui(&mut self, output: &mut Vec<String>, cmd: &str) {
+        if let Some(value) = self.parse_and_get_data_var(output, cmd) {
+            output.push(String::from("get_var_start"));
+            output.push(json!({ "value": utils::value_to_string(&value) }).to_string());
+            output.push(String::from("get_var_end"));
+        }
+    }
+
+    #[cfg(feature = "cli")]
+    fn get_accounts(&mut self, output: &mut Vec<String>) {
+        let accounts = self.interpreter.get_accounts();
+        if accounts.len() > 0 {
+            let tokens = self.interpreter.get_tokens();
+            let mut headers = vec!["Address".to_string()];
+            headers.append(&mut tokens.clone());
+            if self.settings.repl_settings.accounting.is_some() {
+                headers.push("Nonce".to_string());
+            }
+            let mut headers_cells = vec![];
+            for header in headers.iter() {
+                headers_cells.push(Cell::new(&header));
+            }
+            let mut table = Table::new();
+            table.add_row(Row::new(headers_cells));
+            for account in accounts.iter() {
+                let mut cells = vec![];
+
+                if let Some(name) = self.get_account_name(account) {
+                    cells.push(Cell::new(&format!("{} ({})", account, name)));
+                } else {
+                    cells.push(Cell::new(account));
+                }
 
                 for token in tokens.iter() {
                     let balance = self.interpreter.get_balance_for_account(account, token);
                     cells.push(Cell::new(&format!("{}", balance)));
                 }
+                if let Some(nonce) = self.get_account_nonce(account) {
+                    cells.push(Cell::new(&format!("{}", nonce)));
+                }
                 table.add_row(Row::new(cells));
             }
             output.push(format!("{}", table));
@@ -961,18 +1876,21 @@ impl Session {
     }
 
     #[cfg(feature = "cli")]
-    fn get_assets_maps_gui(&self, output: &mut Vec<String>) {
+    fn get_assets_maps_gui(&mut self, output: &mut Vec<String>) {
         output.push(String::from("get_assets_maps_start"));
         self.get_accounts_gui(output);
         output.push(String::from("get_assets_maps_end"));
     }
 
-    fn get_accounts_gui(&self, output: &mut Vec<String>) {
+    fn get_accounts_gui(&mut self, output: &mut Vec<String>) {
         let accounts = self.interpreter.get_accounts();
         if accounts.len() > 0 {
             let tokens = self.interpreter.get_tokens();
             let mut headers = vec!["Address".to_string()];
             headers.append(&mut tokens.clone());
+            if self.settings.repl_settings.accounting.is_some() {
+                headers.push("Nonce".to_string());
+            }
             let mut headers_cells = String::new();
             for header in headers.iter() {
                 headers_cells.push_str(&format!("{} ", header.as_str()));
@@ -992,6 +1910,9 @@ impl Session {
                     let balance = self.interpreter.get_balance_for_account(account, token);
                     cells.push_str(&format!("{} ", balance));
                 }
+                if let Some(nonce) = self.get_account_nonce(account) {
+                    cells.push_str(&format!("{} ", nonce));
+                }
                 table.push_str(&format!("{}\n", cells));
             }
             let mut chars = table.chars();
@@ -1164,7 +2085,7 @@ impl Session {
         output.push(result);
     }
 
-    pub fn display_digest(&self) -> Result<String, String> {
+    pub fn display_digest(&mut self) -> Result<String, String> {
         let mut output = vec![];
         self.get_contracts(&mut output);
         self.get_accounts(&mut output);
@@ -1213,6 +2134,29 @@ fn decode_hex(byteString: &str) -> Result<Vec<u8>, DecodeHexError> {
     }
 }
 
+// Collect the contracts referenced by an expression, e.g. in a `contract-call?`
+fn collect_contract_ids(
+    expr: &SymbolicExpression,
+    contract_ids: &mut BTreeSet<QualifiedContractIdentifier>,
+) {
+    match &expr.expr {
+        SymbolicExpressionType::LiteralValue(Value::Principal(PrincipalData::Contract(
+            contract_id,
+        ))) => {
+            contract_ids.insert(contract_id.clone());
+        }
+        SymbolicExpressionType::Field(trait_id) => {
+            contract_ids.insert(trait_id.contract_identifier.clone());
+        }
+        SymbolicExpressionType::List(list) => {
+            for expr in list.iter() {
+                collect_contract_ids(expr, contract_ids);
+            }
+        }
+        _ => (),
+    }
+}
+
 fn build_api_reference() -> HashMap<String, String> {
     let mut api_reference = HashMap::new();
     for func in NativeFunctions::ALL.iter() {
@@ -1260,6 +2204,7 @@ fn build_api_reference() -> HashMap<String, String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::repl::fork::{ForkSource, ForkedContract};
 
     #[test]
     fn encode_simple() {
@@ -1411,4 +2356,550 @@ mod tests {
         );
         assert_eq!(session.handle_command("(at-block (unwrap-panic (get-block-info? id-header-hash u10000)) (contract-call? .contract get-x))")[0], green!("u1"));
     }
+
+    #[test]
+    fn get_map_and_var() {
+        let mut settings = SessionSettings::default();
+        settings.include_boot_contracts = vec!["costs".into(), "costs-2".into()];
+
+        let mut session = Session::new(settings);
+        session.start().expect("session could not start");
+
+        let snippet = "
+            (define-data-var counter uint u2)
+            (define-map owners uint principal)
+            (map-set owners u1 tx-sender)
+            (map-set owners u2 tx-sender)
+            (map-delete owners u2)";
+
+        let contract = ClarityContract {
+            code_source: ClarityCodeSource::ContractInMemory(snippet.to_string()),
+            name: "storage".to_string(),
+            deployer: ContractDeployer::Address("ST000000000000000000002AMW42H".into()),
+            clarity_version: ClarityVersion::Clarity1,
+            epoch: DEFAULT_EPOCH,
+        };
+        let _ = session.deploy_contract(&contract, None, false, None, &mut None);
+
+        let contract_id = "ST000000000000000000002AMW42H.storage";
+        assert_eq!(
+            session.handle_command(&format!("::get_var {} counter", contract_id)),
+            vec![green!("u2")]
+        );
+        assert_eq!(
+            session.handle_command(&format!("::get_map {} owners", contract_id)),
+            vec![green!("u1 => ST000000000000000000002AMW42H")]
+        );
+        assert_eq!(
+            session.handle_command(&format!("::get_map {} owners (+ u1 u1)", contract_id)),
+            vec![yellow!("No entries")]
+        );
+        assert_eq!(
+            session.handle_command(&format!("::get_map_gui {} owners u1", contract_id)),
+            vec![
+                "get_map_start".to_string(),
+                "[{\"key\":\"u1\",\"value\":\"ST000000000000000000002AMW42H\"}]".to_string(),
+                "get_map_end".to_string(),
+            ]
+        );
+        assert_eq!(
+            session.handle_command(&format!("::get_var {} unknown", contract_id)),
+            vec![red!(format!(
+                "data-var unknown not found in {}",
+                contract_id
+            ))]
+        );
+    }
+
+    #[test]
+    fn expect_last_result() {
+        let mut settings = SessionSettings::default();
+        settings.include_boot_contracts = vec!["costs".into(), "costs-2".into()];
+
+        let mut session = Session::new(settings);
+        session.start().expect("session could not start");
+
+        assert_eq!(
+            session.check_expectation("u1"),
+            Err("expected u1, got no result".to_string())
+        );
+        session.handle_command("(ok (+ u1 u2))");
+        assert_eq!(session.check_expectation("(ok  u3)"), Ok(()));
+        assert_eq!(
+            session.handle_command("::expect (ok u4)"),
+            vec![red!("x expected (ok u4), got (ok u3)")]
+        );
+        session.handle_command("(unwrap-panic none)");
+        assert_eq!(
+            session.handle_command("::expect (ok u3)"),
+            vec![red!("x expected (ok u3), got no result")]
+        );
+    }
+
+    #[test]
+    fn contracts_symbols() {
+        let mut settings = SessionSettings::default();
+        settings.include_boot_contracts = vec![];
+
+        let mut session = Session::new(settings);
+        session.start().expect("session could not start");
+
+        let snippet = "
+            (define-constant owner tx-sender)
+            (define-data-var counter uint u0)
+            (define-map owners uint principal)
+            (define-read-only (get-counter) (var-get counter))
+            (define-public (incr) (ok (var-set counter (+ (var-get counter) u1))))
+        ";
+        let contract = ClarityContract {
+            code_source: ClarityCodeSource::ContractInMemory(snippet.to_string()),
+            name: "counter".to_string(),
+            deployer: ContractDeployer::Address("ST000000000000000000002AMW42H".into()),
+            clarity_version: DEFAULT_CLARITY_VERSION,
+            epoch: DEFAULT_EPOCH,
+        };
+        session
+            .deploy_contract(&contract, None, false, None, &mut None)
+            .expect("unable to deploy contract");
+
+        let symbols: Vec<String> = session.get_contracts_symbols().into_iter().collect();
+        assert_eq!(
+            symbols,
+            vec![
+                "'ST000000000000000000002AMW42H.counter",
+                ".counter",
+                "counter",
+                "get-counter",
+                "incr",
+                "owner",
+                "owners",
+            ]
+        );
+    }
+
+    #[test]
+    fn report_every_syntax_error() {
+        let mut settings = SessionSettings::default();
+        settings.include_boot_contracts = vec![];
+
+        let mut session = Session::new(settings);
+        session.start().expect("session could not start");
+
+        let snippet = "(define-public (foo)
+    (ok u1)
+(define-read-only (bar)
+    (begin u2
+(define-private (baz) (ok u3))
+";
+        let contract = ClarityContract {
+            code_source: ClarityCodeSource::ContractInMemory(snippet.to_string()),
+            name: "broken".to_string(),
+            deployer: ContractDeployer::Address("ST000000000000000000002AMW42H".into()),
+            clarity_version: DEFAULT_CLARITY_VERSION,
+            epoch: DEFAULT_EPOCH,
+        };
+
+        // The definitions following the errors are still parsed
+        let (ast, _, success) = session.interpreter.build_ast(&contract);
+        assert!(!success);
+        assert_eq!(ast.expressions.len(), 3);
+
+        let diagnostics = match session.deploy_contract(&contract, None, false, None, &mut None) {
+            Err(diagnostics) => diagnostics,
+            Ok(_) => panic!("expected the deployment to fail"),
+        };
+        let errors: Vec<(String, u32)> = diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.level == Level::Error)
+            .map(|diagnostic| (diagnostic.message.clone(), diagnostic.spans[0].start_line))
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                ("expected closing ')'".to_string(), 3),
+                ("expected closing ')'".to_string(), 5),
+                ("expected closing ')'".to_string(), 5),
+            ]
+        );
+    }
+
+    #[test]
+    fn sponsored_and_multisig_transactions() {
+        let mut settings = SessionSettings::default();
+        settings.include_boot_contracts = vec!["costs".into(), "costs-2".into()];
+
+        let mut session = Session::new(settings);
+        session.start().expect("session could not start");
+
+        let sponsor = "ST1SJ3DTE5DN7X54YDH5D64R3BCB6A2AG2ZQ8YPD5";
+        session.handle_command(&format!("::mint_stx {} 1000", sponsor));
+        assert_eq!(
+            session.handle_command(&format!("::set_tx_sponsor {}", sponsor)),
+            vec![green!(format!("tx-sponsor switched to {}", sponsor))]
+        );
+        assert_eq!(session.get_tx_sponsor(), Some(sponsor.to_string()));
+        session.pay_fee(sponsor, 400).expect("unable to pay fee");
+        assert_eq!(
+            session.handle_command(&format!("(stx-get-balance '{})", sponsor))[0],
+            green!("u600")
+        );
+        assert!(session.pay_fee(sponsor, 700).is_err());
+        assert_eq!(
+            session.handle_command("::set_tx_sponsor"),
+            vec![green!("tx-sponsor removed")]
+        );
+        assert_eq!(session.get_tx_sponsor(), None);
+
+        let signers = vec![
+            "ST1SJ3DTE5DN7X54YDH5D64R3BCB6A2AG2ZQ8YPD5".to_string(),
+            "ST2CY5V39NHDPWSXMW9QDT3HC3GD6Q6XX4CFRK9AG".to_string(),
+            "ST2JHG361ZXG51QTKY2NQCVBPPRRE2KZB1HR05NNC".to_string(),
+        ];
+        let multisig = session
+            .create_multisig_principal(2, &signers)
+            .expect("unable to create multisig");
+        assert!(multisig.starts_with("SN"));
+        assert_eq!(
+            session.create_multisig_principal(2, &signers),
+            Ok(multisig.clone())
+        );
+        assert!(session.create_multisig_principal(4, &signers).is_err());
+        assert_eq!(session.check_signatures(&multisig, &signers[0..2]), Ok(()));
+        assert!(session.check_signatures(&multisig, &signers[0..1]).is_err());
+        assert!(session
+            .check_signatures(&multisig, &vec![signers[0].clone(), signers[0].clone()])
+            .is_err());
+        assert!(session
+            .check_signatures(&signers[0], &signers[0..1])
+            .is_err());
+        assert_eq!(session.check_signatures(&signers[0], &[]), Ok(()));
+    }
+
+    #[test]
+    fn fees_and_nonces() {
+        use crate::repl::settings::AccountingSettings;
+        use clarity::vm::costs::LimitedCostTracker;
+
+        let mut settings = SessionSettings::default();
+        settings.include_boot_contracts = vec!["costs".into(), "costs-2".into()];
+
+        let mut session = Session::new(settings.clone());
+        session.start().expect("session could not start");
+        let sender = "ST1SJ3DTE5DN7X54YDH5D64R3BCB6A2AG2ZQ8YPD5";
+        assert_eq!(session.get_transaction_fee(None), None);
+        assert_eq!(session.check_nonce(sender, Some(3)), Ok(Some(3)));
+        assert_eq!(session.get_account_nonce(sender), None);
+
+        settings.repl_settings.accounting = Some(AccountingSettings {
+            fee_mode: FeeMode::Flat,
+            fee: 300,
+        });
+        let mut session = Session::new(settings.clone());
+        session.start().expect("session could not start");
+        assert_eq!(session.get_transaction_fee(None), Some(300));
+        assert_eq!(session.check_nonce(sender, None), Ok(Some(0)));
+        session.increment_nonce(sender).unwrap();
+        session.increment_nonce(sender).unwrap();
+        assert_eq!(session.get_account_nonce(sender), Some(2));
+        assert_eq!(session.check_nonce(sender, Some(2)), Ok(Some(2)));
+        assert_eq!(
+            session.check_nonce(sender, Some(1)),
+            Err(format!("nonce 1 of {} was already used", sender))
+        );
+        assert_eq!(
+            session.check_nonce(sender, Some(3)),
+            Err(format!("expected nonce 2 for {}, got 3", sender))
+        );
+
+        session.handle_command(&format!("::mint_stx {} 1000", sender));
+        session.pay_fee(sender, 300).unwrap();
+        let assets = session.handle_command("::get_assets_maps").join("\n");
+        assert!(assets.contains("Nonce"));
+        assert!(assets.contains("700"));
+        // Transfers are cost tracked, for cost-based fees
+        let execution = session.stx_transfer(100, sender).unwrap();
+        assert!(execution.cost.is_some());
+
+        settings.repl_settings.accounting = Some(AccountingSettings {
+            fee_mode: FeeMode::Cost,
+            fee: 2,
+        });
+        let session = Session::new(settings);
+        let mut cost = CostSynthesis::from_cost_tracker(&LimitedCostTracker::new_free());
+        cost.limit.runtime = 1_000;
+        cost.total.runtime = 250;
+        cost.limit.read_count = 100;
+        cost.total.read_count = 1;
+        assert_eq!(
+            session.get_transaction_fee(Some(&cost)),
+            Some(2 * (2_500 + 100))
+        );
+        assert_eq!(session.get_transaction_fee(None), Some(2));
+    }
+
+    #[test]
+    fn burnchain_state() {
+        let mut settings = SessionSettings::default();
+        settings.include_boot_contracts = vec!["costs".into(), "costs-2".into()];
+
+        // Evaluates a Clarity 2 expression in a new contract
+        let eval_clarity_2 = |session: &mut Session, name: &str, snippet: &str| {
+            let contract = ClarityContract {
+                code_source: ClarityCodeSource::ContractInMemory(snippet.to_string()),
+                name: name.to_string(),
+                deployer: ContractDeployer::Address("ST000000000000000000002AMW42H".into()),
+                clarity_version: ClarityVersion::Clarity2,
+                epoch: StacksEpochId::Epoch21,
+            };
+            let result = session
+                .deploy_contract(&contract, None, false, None, &mut None)
+                .expect("unable to evaluate snippet");
+            match result.result {
+                EvaluationResult::Contract(contract_result) => {
+                    contract_result.result.unwrap().to_string()
+                }
+                EvaluationResult::Snippet(snippet_result) => snippet_result.result.to_string(),
+            }
+        };
+
+        let mut session = Session::new(settings.clone());
+        session.start().expect("session could not start");
+        let hash = "0x000000000000000000077a8a6c25cc29d9e8a9c1d4d2c6e1d0c5e1f1a2b3c4d5";
+
+        assert!(session.set_burn_block_header_hash(2, "0x1234").is_err());
+        session.handle_command(&format!("::set_burn_block_header_hash 2 {}", hash));
+        session.handle_command("::advance_chain_tip 5");
+        session.handle_command("(get-block-info? burnchain-header-hash u2)");
+        assert_eq!(
+            session.check_expectation(&format!("(some {})", hash)),
+            Ok(())
+        );
+        assert_eq!(
+            eval_clarity_2(
+                &mut session,
+                "hash",
+                "(get-burn-block-info? header-hash u2)"
+            ),
+            format!("(some {})", hash)
+        );
+
+        assert_eq!(
+            session.set_pox_payout_addrs(
+                2,
+                "{version: 0x01, hashbytes: 0x0102} {version: 0x04, hashbytes: 0x03}",
+                1000
+            ),
+            Ok(2)
+        );
+        assert!(session.set_pox_payout_addrs(2, "u1", 1000).is_err());
+        assert!(session
+            .set_pox_payout_addrs(2, "{version: 0x0102, hashbytes: 0x03}", 1000)
+            .is_err());
+        let pox_addrs = eval_clarity_2(&mut session, "pox", "(get-burn-block-info? pox-addrs u2)");
+        assert!(pox_addrs.contains("(hashbytes 0x0102) (version 0x01)"));
+        assert!(pox_addrs.contains("(hashbytes 0x03) (version 0x04)"));
+        assert!(pox_addrs.contains("(payout u1000)"));
+        // The data of the other blocks is synthetic
+        assert!(
+            !eval_clarity_2(&mut session, "other", "(get-burn-block-info? pox-addrs u3)")
+                .contains("hashbytes")
+        );
+
+        // The burnchain data set is saved with the session
+        let path = std::env::temp_dir().join("clarinet-burnchain-test.json");
+        let path = path.to_str().unwrap();
+        session.save_session(path).expect("unable to save session");
+        session.set_burn_block_header_hash(3, hash).unwrap();
+
+        let mut session = Session::new(settings);
+        session.start().expect("session could not start");
+        session.load_session(path).expect("unable to load session");
+        assert_eq!(
+            eval_clarity_2(
+                &mut session,
+                "loaded",
+                "(get-burn-block-info? header-hash u2)"
+            ),
+            format!("(some {})", hash)
+        );
+        assert_ne!(
+            eval_clarity_2(
+                &mut session,
+                "not-saved",
+                "(get-burn-block-info? header-hash u3)"
+            ),
+            format!("(some {})", hash)
+        );
+        let pox_addrs = eval_clarity_2(
+            &mut session,
+            "loaded-pox",
+            "(get-burn-block-info? pox-addrs u2)",
+        );
+        assert!(pox_addrs.contains("(hashbytes 0x0102) (version 0x01)"));
+        assert!(pox_addrs.contains("(payout u1000)"));
+    }
+
+    #[test]
+    fn snapshot_and_restore() {
+        let mut settings = SessionSettings::default();
+        settings.include_boot_contracts = vec!["costs".into(), "costs-2".into()];
+
+        let mut session = Session::new(settings);
+        session.start().expect("session could not start");
+
+        let snippet = "
+            (define-data-var x uint u0)
+            (define-public (incr)
+                (begin
+                    (var-set x (+ (var-get x) u1))
+                    (ok (var-get x))))";
+
+        let contract = ClarityContract {
+            code_source: ClarityCodeSource::ContractInMemory(snippet.to_string()),
+            name: "contract".to_string(),
+            deployer: ContractDeployer::Address("ST000000000000000000002AMW42H".into()),
+            clarity_version: ClarityVersion::Clarity1,
+            epoch: DEFAULT_EPOCH,
+        };
+        let _ = session.deploy_contract(&contract, None, false, None, &mut None);
+
+        let height = session.interpreter.get_block_height();
+        session.handle_command("::snapshot deployed");
+        session.handle_command("(contract-call? .contract incr)");
+        session.advance_chain_tip(10);
+        assert_eq!(
+            session.handle_command("::get_var .contract x"),
+            vec![green!("u1")]
+        );
+
+        let path = std::env::temp_dir().join("clarinet-snapshot-test.json");
+        let path = path.to_str().unwrap();
+        session.save_session(path).expect("unable to save session");
+
+        assert_eq!(
+            session.handle_command("::restore deployed"),
+            vec![green!(format!(
+                "Snapshot deployed restored, current height: {}",
+                height
+            ))]
+        );
+        assert_eq!(
+            session.handle_command("::get_var .contract x"),
+            vec![green!("u0")]
+        );
+        assert_eq!(
+            session.handle_command("::restore unknown"),
+            vec![red!("Unknown snapshot unknown")]
+        );
+
+        session.load_session(path).expect("unable to load session");
+        let _ = fs::remove_file(path);
+        assert_eq!(session.interpreter.get_block_height(), height + 10);
+        assert_eq!(
+            session.handle_command("::get_var .contract x"),
+            vec![green!("u1")]
+        );
+        assert_eq!(
+            session.handle_command("(contract-call? .contract incr)")[0],
+            green!("(ok u2)")
+        );
+        assert!(session
+            .get_audit_report("ST000000000000000000002AMW42H.contract")
+            .is_some());
+    }
+
+    // Stands in for a Stacks node serving the state of a counter contract
+    struct StandInNode;
+
+    impl ForkSource for StandInNode {
+        fn get_contract(
+            &self,
+            contract_id: &QualifiedContractIdentifier,
+        ) -> Result<Option<ForkedContract>, String> {
+            let source = "
+                (define-data-var count uint u0)
+                (define-map owners uint uint)
+                (define-read-only (get-count) (var-get count))
+                (define-read-only (get-count-ascii) (int-to-ascii (var-get count)))
+                (define-read-only (get-owner (id uint)) (map-get? owners id))
+                (define-public (incr)
+                    (begin
+                        (var-set count (+ (var-get count) u1))
+                        (ok (var-get count))))";
+            match contract_id.name.as_str() {
+                "counter" => Ok(Some(ForkedContract {
+                    source: source.to_string(),
+                    clarity_version: ClarityVersion::Clarity2,
+                    epoch: StacksEpochId::Epoch21,
+                })),
+                _ => Ok(None),
+            }
+        }
+
+        fn get_data_var(
+            &self,
+            contract_id: &QualifiedContractIdentifier,
+            var_name: &str,
+        ) -> Result<Option<String>, String> {
+            // u41
+            Ok(Some("0100000000000000000000000000000029".to_string()))
+        }
+
+        fn get_map_entry(
+            &self,
+            contract_id: &QualifiedContractIdentifier,
+            map_name: &str,
+            key: &str,
+        ) -> Result<Option<String>, String> {
+            match key {
+                // u1 => (some u7)
+                "0100000000000000000000000000000001" => {
+                    Ok(Some("0a0100000000000000000000000000000007".to_string()))
+                }
+                _ => Ok(Some("09".to_string())),
+            }
+        }
+
+        fn get_stx_balance(&self, principal: &PrincipalData) -> Result<u128, String> {
+            Ok(1000)
+        }
+    }
+
+    #[test]
+    fn fork_network_state() {
+        let mut settings = SessionSettings::default();
+        settings.include_boot_contracts = vec!["costs".into(), "costs-2".into()];
+
+        let mut session = Session::new(settings);
+        session.start().expect("session could not start");
+        session.set_fork(Fork::new(Arc::new(StandInNode), 100, None));
+
+        assert_eq!(session.handle_command("block-height")[0], green!("u100"));
+        let counter = "'SP000000000000000000002Q6VF78.counter";
+        // The contract is deployed with its Clarity version, 2, on first use
+        assert_eq!(
+            session.handle_command(&format!("(contract-call? {} get-count)", counter))[0],
+            green!("u41")
+        );
+        assert_eq!(
+            session.handle_command(&format!("(contract-call? {} incr)", counter))[0],
+            green!("(ok u42)")
+        );
+        assert_eq!(
+            session.handle_command(&format!("(contract-call? {} get-count)", counter))[0],
+            green!("u42")
+        );
+        assert_eq!(
+            session.handle_command(&format!("(contract-call? {} get-owner u1)", counter))[0],
+            green!("(some u7)")
+        );
+        assert_eq!(
+            session.handle_command(&format!("(contract-call? {} get-owner u2)", counter))[0],
+            green!("none")
+        );
+        assert_eq!(
+            session.handle_command("(stx-get-balance 'SP000000000000000000002Q6VF78)")[0],
+            green!("u1000")
+        );
+    }
 }
@@ -63,19 +63,49 @@ pub struct SessionSettings {
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Settings {
     pub analysis: analysis::Settings,
+    pub accounting: Option<AccountingSettings>,
 }
 
 impl Default for Settings {
     fn default() -> Self {
         Self {
             analysis: analysis::Settings::default(),
+            accounting: None,
         }
     }
 }
 
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeeMode {
+    /// The same fee is charged for every transaction
+    Flat,
+    /// The fee is charged per basis point of the block limit consumed by the
+    /// transaction, summed over the dimensions of its cost
+    Cost,
+}
+
+impl Default for FeeMode {
+    fn default() -> Self {
+        FeeMode::Flat
+    }
+}
+
+/// When set, the fees of the transactions are charged and the nonces of
+/// the accounts are tracked, as on chain.
+#[derive(Debug, Default, PartialEq, Clone, Deserialize, Serialize)]
+pub struct AccountingSettings {
+    #[serde(default)]
+    pub fee_mode: FeeMode,
+    /// Amount of the fee (µSTX), or rate when the fee is cost-based
+    #[serde(default)]
+    pub fee: u64,
+}
+
 #[derive(Debug, Default, Clone, Deserialize, Serialize)]
 pub struct SettingsFile {
     pub analysis: Option<analysis::SettingsFile>,
+    pub accounting: Option<AccountingSettings>,
 }
 
 impl From<SettingsFile> for Settings {
@@ -85,6 +115,9 @@ impl From<SettingsFile> for Settings {
         } else {
             analysis::Settings::default()
         };
-        Self { analysis }
+        Self {
+            analysis,
+            accounting: file.accounting,
+        }
     }
 }
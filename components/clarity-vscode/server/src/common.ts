@@ -22,12 +22,16 @@ export function initConnection(
   connection.onInitialize(() => {
     const capabilities: ServerCapabilities = {
       textDocumentSync: {
-        change: TextDocumentSyncKind.None,
+        // the content of the documents is needed to format them
+        change: TextDocumentSyncKind.Full,
         willSave: false,
         openClose: true,
         save: { includeText: false },
       },
       completionProvider: {},
+      documentFormattingProvider: true,
+      codeActionProvider: true,
+      hoverProvider: true,
     };
     return { capabilities };
   });
@@ -0,0 +1,262 @@
+use clarity_repl::clarity::types::StacksEpochId;
+use clarity_repl::clarity::vm::types::{PrincipalData, QualifiedContractIdentifier};
+use clarity_repl::clarity::vm::ClarityVersion;
+use clarity_repl::repl::fork::{ForkSource, ForkedContract};
+
+use crate::rpc_client::{RpcError, StacksRpc};
+
+/// Reads the state of a network from a Stacks node, and its API's extended
+/// endpoints, at the block `tip` (index block hash) or at the chain tip.
+pub struct StacksRpcForkSource {
+    pub rpc: StacksRpc,
+    pub tip: Option<String>,
+}
+
+impl StacksRpcForkSource {
+    pub fn new(url: &str, tip: Option<String>) -> Self {
+        Self {
+            rpc: StacksRpc::new(url),
+            tip,
+        }
+    }
+}
+
+impl ForkSource for StacksRpcForkSource {
+    fn get_contract(
+        &self,
+        contract_id: &QualifiedContractIdentifier,
+    ) -> Result<Option<ForkedContract>, String> {
+        // Contracts never change, the endpoint does not support the tip parameter
+        let contract = match self.rpc.get_contract_info(&contract_id.to_string()) {
+            Ok(contract) => contract,
+            Err(RpcError::StatusCode(404)) => return Ok(None),
+            Err(e) => return Err(e.to_string()),
+        };
+        // The epoch of a deployment is not reported, contracts are evaluated
+        // in the first epoch supporting their Clarity version
+        let (clarity_version, epoch) = match contract.clarity_version {
+            Some(2) => (ClarityVersion::Clarity2, StacksEpochId::Epoch21),
+            _ => (ClarityVersion::Clarity1, StacksEpochId::Epoch2_05),
+        };
+        Ok(Some(ForkedContract {
+            source: contract.source_code,
+            clarity_version,
+            epoch,
+        }))
+    }
+
+    fn get_data_var(
+        &self,
+        contract_id: &QualifiedContractIdentifier,
+        var_name: &str,
+    ) -> Result<Option<String>, String> {
+        let principal = contract_id.issuer.to_address();
+        match self.rpc.get_data_var(
+            &principal,
+            &contract_id.name.to_string(),
+            var_name,
+            self.tip.as_deref(),
+        ) {
+            Ok(result) => Ok(Some(result.data.trim_start_matches("0x").to_string())),
+            Err(RpcError::StatusCode(404)) => Ok(None),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    fn get_map_entry(
+        &self,
+        contract_id: &QualifiedContractIdentifier,
+        map_name: &str,
+        key: &str,
+    ) -> Result<Option<String>, String> {
+        let principal = contract_id.issuer.to_address();
+        match self.rpc.get_map_entry(
+            &principal,
+            &contract_id.name.to_string(),
+            map_name,
+            key,
+            self.tip.as_deref(),
+        ) {
+            Ok(result) => Ok(Some(result.data.trim_start_matches("0x").to_string())),
+            Err(RpcError::StatusCode(404)) => Ok(None),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    fn get_stx_balance(&self, principal: &PrincipalData) -> Result<u128, String> {
+        let balance = self
+            .rpc
+            .get_balance(&principal.to_string(), self.tip.as_deref())
+            .map_err(|e| e.to_string())?;
+        u128::from_str_radix(balance.balance.trim_start_matches("0x"), 16)
+            .map_err(|e| format!("invalid balance {}: {}", balance.balance, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clarity_repl::repl::fork::Fork;
+    use clarity_repl::repl::{Session, SessionSettings};
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    const COUNTER: &str = "SP000000000000000000002Q6VF78.counter";
+
+    // Requests received by the stand-in node: method, path and body
+    type Requests = Arc<Mutex<Vec<(String, String, String)>>>;
+
+    // Responds to the requests of the stand-in node, serving the state of a
+    // counter contract at the block 0xabcd
+    fn route(method: &str, path: &str) -> (u16, String) {
+        let tip = "?proof=0&tip=abcd";
+        match (method, path) {
+            ("GET", "/extended/v1/contract/SP000000000000000000002Q6VF78.counter") => (
+                200,
+                format!(
+                    r#"{{"contract_id":"{}","block_height":5,"clarity_version":2,"source_code":"(define-data-var count uint u0) (define-map owners uint uint) (define-read-only (get-count) (var-get count)) (define-read-only (get-owner (id uint)) (map-get? owners id))"}}"#,
+                    COUNTER
+                ),
+            ),
+            ("GET", path)
+                if path
+                    == format!(
+                        "/v2/data_var/SP000000000000000000002Q6VF78/counter/count{}",
+                        tip
+                    ) =>
+            {
+                (
+                    200,
+                    r#"{"data":"0x0100000000000000000000000000000029"}"#.into(),
+                )
+            }
+            ("POST", path)
+                if path
+                    == format!(
+                        "/v2/map_entry/SP000000000000000000002Q6VF78/counter/owners{}",
+                        tip
+                    ) =>
+            {
+                (
+                    200,
+                    r#"{"data":"0x0a0100000000000000000000000000000007"}"#.into(),
+                )
+            }
+            ("GET", "/v2/accounts/SP000000000000000000002Q6VF78?proof=1&tip=abcd") => (
+                200,
+                r#"{"balance":"0x3e8","nonce":0,"balance_proof":"","nonce_proof":""}"#.into(),
+            ),
+            ("GET", path) if path.starts_with("/v2/accounts/") => (500, "{}".into()),
+            _ => (404, "{}".into()),
+        }
+    }
+
+    // Serves `route` on a local port, returning the url of the stand-in node
+    fn start_stand_in_node() -> (String, Requests) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests: Requests = Arc::new(Mutex::new(vec![]));
+        let received = requests.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = match stream {
+                    Ok(stream) => stream,
+                    Err(_) => break,
+                };
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut content_length = 0;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = header.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            content_length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+
+                let mut parts = request_line.split_whitespace();
+                let method = parts.next().unwrap_or_default().to_string();
+                let path = parts.next().unwrap_or_default().to_string();
+                let (status, content) = route(&method, &path);
+                received
+                    .lock()
+                    .unwrap()
+                    .push((method, path, String::from_utf8(body).unwrap()));
+                let response = format!(
+                    "HTTP/1.1 {} Stand-in\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    content.len(),
+                    content
+                );
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        (url, requests)
+    }
+
+    #[test]
+    fn read_state_from_node() {
+        let (url, requests) = start_stand_in_node();
+        let source = StacksRpcForkSource::new(&url, Some("0xabcd".into()));
+        let counter = QualifiedContractIdentifier::parse(COUNTER).unwrap();
+
+        let contract = source.get_contract(&counter).unwrap().unwrap();
+        assert_eq!(contract.clarity_version, ClarityVersion::Clarity2);
+        assert_eq!(contract.epoch, StacksEpochId::Epoch21);
+        let unknown = QualifiedContractIdentifier::parse(&format!("{}-v2", COUNTER)).unwrap();
+        assert!(source.get_contract(&unknown).unwrap().is_none());
+
+        assert_eq!(
+            source.get_data_var(&counter, "count").unwrap(),
+            Some("0100000000000000000000000000000029".into())
+        );
+        assert_eq!(source.get_data_var(&counter, "total").unwrap(), None);
+
+        assert_eq!(
+            source
+                .get_map_entry(&counter, "owners", "0100000000000000000000000000000001")
+                .unwrap(),
+            Some("0a0100000000000000000000000000000007".into())
+        );
+        assert!(requests.lock().unwrap().contains(&(
+            "POST".into(),
+            "/v2/map_entry/SP000000000000000000002Q6VF78/counter/owners?proof=0&tip=abcd".into(),
+            r#""0x0100000000000000000000000000000001""#.into()
+        )));
+
+        let principal = PrincipalData::parse("SP000000000000000000002Q6VF78").unwrap();
+        assert_eq!(source.get_stx_balance(&principal), Ok(1000));
+        let principal = PrincipalData::parse("ST000000000000000000002AMW42H").unwrap();
+        assert_eq!(
+            source.get_stx_balance(&principal),
+            Err("error status code 500".into())
+        );
+    }
+
+    #[test]
+    fn fork_node_into_session() {
+        let (url, _) = start_stand_in_node();
+        let source = StacksRpcForkSource::new(&url, Some("abcd".into()));
+
+        let mut settings = SessionSettings::default();
+        settings.include_boot_contracts = vec!["costs".into(), "costs-2".into()];
+        let mut session = Session::new(settings);
+        session.start().expect("session could not start");
+        session.set_fork(Fork::new(Arc::new(source), 10, None));
+
+        let output = session.handle_command(&format!("(contract-call? '{} get-count)", COUNTER));
+        assert!(output[0].contains("u41"));
+        let output = session.handle_command(&format!("(contract-call? '{} get-owner u1)", COUNTER));
+        assert!(output[0].contains("(some u7)"));
+    }
+}
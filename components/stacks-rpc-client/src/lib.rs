@@ -8,6 +8,8 @@ extern crate serde_derive;
 #[macro_use]
 extern crate serde_json;
 
+pub mod fork;
 pub mod rpc_client;
 
+pub use fork::StacksRpcForkSource;
 pub use rpc_client::{PoxInfo, StacksRpc};
@@ -94,12 +94,31 @@ pub struct Balance {
     pub nonce_proof: String,
 }
 
+#[derive(Deserialize, Debug)]
+pub struct DataResult {
+    pub data: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Block {
+    pub height: u64,
+    pub index_block_hash: String,
+}
+
 #[derive(Deserialize, Debug)]
 pub struct Contract {
     pub source: String,
     pub publish_height: u64,
 }
 
+#[derive(Deserialize, Debug)]
+pub struct ContractInfo {
+    pub contract_id: String,
+    pub block_height: u64,
+    pub source_code: String,
+    pub clarity_version: Option<u8>,
+}
+
 #[derive(Deserialize, Debug)]
 pub struct FeeEstimationReport {
     pub estimations: Vec<FeeEstimation>,
@@ -218,6 +237,9 @@ impl StacksRpc {
         let res = self.client.get(&request_url).send();
 
         match res {
+            Ok(response) if !response.status().is_success() => {
+                Err(RpcError::StatusCode(response.status().as_u16()))
+            }
             Ok(response) => match response.json() {
                 Ok(value) => Ok(value),
                 Err(e) => Err(RpcError::Message(format!("{}", e.to_string()))),
@@ -226,6 +248,116 @@ impl StacksRpc {
         }
     }
 
+    /// Returns the hex encoded value of a data-var, read at the block `tip`
+    /// (index block hash) or at the chain tip.
+    pub fn get_data_var(
+        &self,
+        principal: &str,
+        contract_name: &str,
+        var_name: &str,
+        tip: Option<&str>,
+    ) -> Result<DataResult, RpcError> {
+        let request_url = format!(
+            "{}/v2/data_var/{}/{}/{}?proof=0{}",
+            self.url,
+            principal,
+            contract_name,
+            var_name,
+            tip_parameter(tip)
+        );
+
+        let res = self
+            .client
+            .get(&request_url)
+            .send()
+            .map_err(|e| RpcError::Message(e.to_string()))?;
+        if !res.status().is_success() {
+            return Err(RpcError::StatusCode(res.status().as_u16()));
+        }
+        res.json().map_err(|e| RpcError::Message(e.to_string()))
+    }
+
+    /// Returns the hex encoded entry of `key` (hex encoded) in a map, as an
+    /// optional, read at the block `tip` (index block hash) or at the chain tip.
+    pub fn get_map_entry(
+        &self,
+        principal: &str,
+        contract_name: &str,
+        map_name: &str,
+        key: &str,
+        tip: Option<&str>,
+    ) -> Result<DataResult, RpcError> {
+        let request_url = format!(
+            "{}/v2/map_entry/{}/{}/{}?proof=0{}",
+            self.url,
+            principal,
+            contract_name,
+            map_name,
+            tip_parameter(tip)
+        );
+
+        let res = self
+            .client
+            .post(&request_url)
+            .json(&format!("0x{}", key.trim_start_matches("0x")))
+            .send()
+            .map_err(|e| RpcError::Message(e.to_string()))?;
+        if !res.status().is_success() {
+            return Err(RpcError::StatusCode(res.status().as_u16()));
+        }
+        res.json().map_err(|e| RpcError::Message(e.to_string()))
+    }
+
+    pub fn get_balance(&self, principal: &str, tip: Option<&str>) -> Result<Balance, RpcError> {
+        let request_url = format!(
+            "{}/v2/accounts/{}?proof=1{}",
+            self.url,
+            principal,
+            tip_parameter(tip)
+        );
+
+        let res = self
+            .client
+            .get(&request_url)
+            .send()
+            .map_err(|e| RpcError::Message(e.to_string()))?;
+        if !res.status().is_success() {
+            return Err(RpcError::StatusCode(res.status().as_u16()));
+        }
+        res.json().map_err(|e| RpcError::Message(e.to_string()))
+    }
+
+    /// Returns a contract and its Clarity version, using the API's extended
+    /// endpoints
+    pub fn get_contract_info(&self, contract_id: &str) -> Result<ContractInfo, RpcError> {
+        let request_url = format!("{}/extended/v1/contract/{}", self.url, contract_id);
+
+        let res = self
+            .client
+            .get(&request_url)
+            .send()
+            .map_err(|e| RpcError::Message(e.to_string()))?;
+        if !res.status().is_success() {
+            return Err(RpcError::StatusCode(res.status().as_u16()));
+        }
+        res.json().map_err(|e| RpcError::Message(e.to_string()))
+    }
+
+    /// Returns the block at `height`, using the API's extended endpoints
+    pub fn get_block_by_height(&self, height: u64) -> Result<Block, RpcError> {
+        let request_url = format!("{}/extended/v1/block/by_height/{}", self.url, height);
+
+        let res = self
+            .client
+            .get(&request_url)
+            .send()
+            .map_err(|e| RpcError::Message(e.to_string()))?;
+        if !res.status().is_success() {
+            return Err(RpcError::StatusCode(res.status().as_u16()));
+        }
+        res.json().map_err(|e| RpcError::Message(e.to_string()))
+    }
+
     pub fn call_read_only_fn(
         &self,
         contract_addr: &str,
@@ -280,3 +412,10 @@ impl StacksRpc {
         }
     }
 }
+
+fn tip_parameter(tip: Option<&str>) -> String {
+    match tip {
+        Some(tip) => format!("&tip={}", tip.trim_start_matches("0x")),
+        None => String::new(),
+    }
+}